@@ -1,11 +1,19 @@
 //! Backend Interface and Implementation Abstractions
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
 
-use crate::clipboard::{Entry, Preview};
+use crate::clipboard::{Entry, Preview, PreviewCache};
+use crate::secret;
 
-use super::GroupConfig;
+use super::{Dedupe, GroupColor, GroupConfig, LargeEntryPolicy, Settings};
+
+/// Bytes of Preview Text Cached on each Record
+static PREVIEW_CACHE_SIZE: usize = 256;
+
+/// Records of Volatile Groups Saved across Daemon Restarts
+pub type Snapshot = HashMap<String, Vec<Record>>;
 
 /// Backend Storage Record Object
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +22,17 @@ pub struct Record {
     pub entry: Entry,
     pub last_used: SystemTime,
     pub entry_date: SystemTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<PreviewCache>,
+    /// Revision of the Record at this Index (Grows whenever it is Replaced)
+    #[serde(default)]
+    pub revision: u64,
+    /// Hostname of the Machine the Record was Captured On
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// Pinned Records Survive Expiration and Clears
+    #[serde(default)]
+    pub pinned: bool,
 }
 
 impl Record {
@@ -21,16 +40,37 @@ impl Record {
         let now = SystemTime::now();
         Record {
             index,
+            preview: Some(entry.preview_cache(PREVIEW_CACHE_SIZE)),
             entry,
             last_used: now,
             entry_date: now,
+            revision: 0,
+            host: Some(crate::hostname()),
+            pinned: false,
         }
     }
+    /// Cached Preview Text (Only Regenerated when the Record has no Cache)
+    pub fn cached_preview(&self) -> String {
+        let cached = self
+            .preview
+            .as_ref()
+            .and_then(|c| c.render(PREVIEW_CACHE_SIZE));
+        cached.unwrap_or_else(|| self.entry.preview(PREVIEW_CACHE_SIZE))
+    }
     fn preview(&self, size: usize) -> Preview {
+        // only regenerate from the entry when the cache is too short
+        let preview = self.preview.as_ref().and_then(|c| c.render(size));
         Preview {
             index: self.index,
-            preview: self.entry.preview(size),
+            preview: preview.unwrap_or_else(|| self.entry.preview(size)),
             last_used: self.last_used,
+            mime: self.entry.mime(),
+            expires: None,
+            note: self.entry.note.clone(),
+            selection: self.entry.selection,
+            host: self.host.clone(),
+            pinned: self.pinned,
+            masked: secret::is_secret(&self.entry).then(|| secret::mask(&self.entry.to_text())),
         }
     }
 }
@@ -63,12 +103,24 @@ impl From<&GroupConfig> for CleanCfg {
 }
 
 /// Backend Group Implementation
+///
+/// Storage primitives for a single group. Higher level behavior such as
+/// deduplication, expiration and previews is shared by all backends.
 pub trait BackendGroup: Send + Sync {
+    /// Iterate all Records within the Group (Unordered)
     fn iter(&self) -> Box<dyn Iterator<Item = Record>>;
+    /// Retrieve Record at the Specified Index
     fn get(&self, index: &usize) -> Option<Record>;
+    /// Insert or Replace Record at the Specified Index
     fn insert(&mut self, index: usize, record: Record);
+    /// Remove Record at the Specified Index (if Present)
     fn delete(&mut self, index: &usize);
+    /// Allocate Next Unused Index for a New Record
     fn index(&mut self) -> usize;
+    /// Storage Keys of all Records (may Disagree with Record Indexes)
+    fn keys(&self) -> Vec<usize> {
+        self.iter().map(|r| r.index).collect()
+    }
 }
 
 impl dyn BackendGroup {
@@ -76,10 +128,13 @@ impl dyn BackendGroup {
     pub fn latest(&self) -> Option<Record> {
         self.iter().max_by_key(|r| r.last_used)
     }
-    /// Return Index of Record if Entry Exists
-    pub fn exists(&self, entry: &Entry) -> Option<usize> {
+    /// Return Index of Record if Entry Exists (According to Dedupe Strategy)
+    pub fn exists(&self, entry: &Entry, dedupe: Dedupe) -> Option<usize> {
+        if dedupe == Dedupe::Never {
+            return None;
+        }
         self.iter()
-            .find(|r| r.entry.body.matches(&entry.body))
+            .find(|r| dedupe.matches(&r.entry.body, &entry.body))
             .map(|r| r.index)
     }
     /// List Unsorted Previews
@@ -88,6 +143,26 @@ impl dyn BackendGroup {
         previews.sort_by_key(|p| p.index);
         previews
     }
+    /// Previews of Records at the Given Indexes (Skipping Missing Records)
+    pub fn preview_at(&self, indexes: &[usize], size: usize) -> Vec<Preview> {
+        indexes
+            .iter()
+            .filter_map(|index| self.get(index))
+            .map(|r| r.preview(size))
+            .collect()
+    }
+    /// Previews of Text Records Containing the Query (Case-Insensitive)
+    pub fn search(&self, query: &str, size: usize) -> Vec<Preview> {
+        let query = query.to_lowercase();
+        let mut previews: Vec<Preview> = self
+            .iter()
+            .filter(|r| r.entry.is_text())
+            .filter(|r| r.entry.to_text().to_lowercase().contains(&query))
+            .map(|r| r.preview(size))
+            .collect();
+        previews.sort_by_key(|p| p.index);
+        previews
+    }
     /// Find Latest or Index (if Specfied)
     pub fn find(&self, index: Option<usize>) -> Option<Record> {
         match index {
@@ -102,13 +177,36 @@ impl dyn BackendGroup {
             self.insert(index, record);
         }
     }
+    /// Pin or Unpin Record (False if Not Found)
+    pub fn pin(&mut self, index: usize, pinned: bool) -> bool {
+        match self.get(&index) {
+            Some(mut record) => {
+                record.pinned = pinned;
+                self.insert(index, record);
+                true
+            }
+            None => false,
+        }
+    }
     /// Add/Touch Entry Record in Database
-    pub fn push(&mut self, entry: Entry) -> usize {
-        let index = match self.exists(&entry) {
-            Some(index) => index,
+    pub fn push(&mut self, mut entry: Entry, dedupe: Dedupe) -> usize {
+        let mut pinned = false;
+        let index = match self.exists(&entry, dedupe) {
+            Some(index) => {
+                let existing = self.get(&index);
+                pinned = existing.as_ref().is_some_and(|r| r.pinned);
+                // keep existing note unless a new one is given
+                if entry.note.is_none() {
+                    entry.note = existing.and_then(|r| r.entry.note);
+                }
+                index
+            }
             None => self.index(),
         };
-        let record = Record::new(index, entry);
+        let record = Record {
+            pinned,
+            ..Record::new(index, entry)
+        };
         self.insert(index, record);
         index
     }
@@ -122,19 +220,44 @@ impl dyn BackendGroup {
             None => None,
         }
     }
-    /// Delete All Records within the Group
+    /// Rewrite Records from their Storage Keys Rebuilding Indexes and Preview Caches
+    ///
+    /// Returns the number of records rewritten and how many had an index
+    /// that disagreed with their key (e.g. after editing the store by hand).
+    pub fn reindex(&mut self) -> (usize, usize) {
+        let (mut records, mut repaired) = (0, 0);
+        for key in self.keys() {
+            let Some(mut record) = self.get(&key) else {
+                continue;
+            };
+            if record.index != key {
+                log::warn!("record key {key} had index {}", record.index);
+                record.index = key;
+                repaired += 1;
+            }
+            record.preview = Some(record.entry.preview_cache(PREVIEW_CACHE_SIZE));
+            // inserting also advances the stored sequence past the key
+            self.insert(key, record);
+            records += 1;
+        }
+        (records, repaired)
+    }
+    /// Delete All Unpinned Records within the Group
     pub fn clear(&mut self) {
-        let indexes: Vec<_> = self.iter().map(|r| r.index).collect();
+        let indexes: Vec<_> = self.iter().filter(|r| !r.pinned).map(|r| r.index).collect();
         for index in indexes {
             self.delete(&index);
         }
     }
-    /// Delete Expired Records within Backend
-    pub fn clean(&mut self, cfg: &CleanCfg) {
+    /// Delete Expired Records within Backend (Pinned Records are Never Deleted)
+    ///
+    /// Returns the indexes of every record deleted.
+    pub fn clean(&mut self, cfg: &CleanCfg) -> Vec<usize> {
+        let mut deleted = vec![];
         // categorize records into expired and unexpired
         let mut valid: Vec<(usize, SystemTime)> = vec![];
         let mut invalid: Vec<(usize, SystemTime)> = vec![];
-        for record in self.iter() {
+        for record in self.iter().filter(|r| !r.pinned) {
             match cfg.is_expired(record.last_used) {
                 true => invalid.push((record.index, record.last_used)),
                 false => valid.push((record.index, record.last_used)),
@@ -148,6 +271,7 @@ impl dyn BackendGroup {
         // delete remaining invalid records
         for (index, _) in invalid {
             self.delete(&index);
+            deleted.push(index);
         }
         // delete oldest valid  records until within maximum
         if let Some(max_size) = cfg.max_entries {
@@ -156,8 +280,10 @@ impl dyn BackendGroup {
             while valid.len() > max_size {
                 let (index, _) = valid.pop().expect("empty record set");
                 self.delete(&index);
+                deleted.push(index);
             }
         }
+        deleted
     }
 }
 
@@ -165,7 +291,69 @@ impl dyn BackendGroup {
 pub type Group<'a> = Option<&'a str>;
 
 /// Backend Implementation
+///
+/// Custom implementations are made available to configuration through
+/// the plugin registry (see `plugin::register_backend`).
 pub trait Backend: Send + Sync {
+    /// List Groups with Storage in this Backend
     fn groups(&self) -> Vec<String>;
+    /// Retrieve Handle to Group Storage (Created on Demand)
     fn group(&mut self, group: Group) -> Box<dyn BackendGroup>;
+    /// Retrieve Duration-Based Record Lifetime for Group (if any)
+    fn lifetime(&mut self, _group: Group) -> Option<Duration> {
+        None
+    }
+    /// Retrieve Preview Length Configured for Group (if any)
+    fn preview_length(&mut self, _group: Group) -> Option<usize> {
+        None
+    }
+    /// Retrieve Duplicate Detection Strategy for Group
+    fn dedupe(&mut self, _group: Group) -> Dedupe {
+        Dedupe::default()
+    }
+    /// Check if Group Rejects Modifications from Clients
+    fn readonly(&mut self, _group: Group) -> bool {
+        false
+    }
+    /// Check if Group Records are Encrypted at Rest
+    fn encrypted(&mut self, _group: Group) -> bool {
+        false
+    }
+    /// Drain (Group, Index) of Records Deleted by Cleanup since the Last Call
+    fn evicted(&mut self) -> Vec<(String, usize)> {
+        vec![]
+    }
+    /// Retrieve Display Label and Color Configured for Group
+    fn label(&mut self, _group: Group) -> (Option<String>, Option<GroupColor>) {
+        (None, None)
+    }
+    /// Retrieve Max Entry Size and Policy for Larger Entries (if Limited)
+    fn max_entry_size(&mut self, _group: Group) -> Option<(usize, LargeEntryPolicy)> {
+        None
+    }
+    /// Override Group Configuration at Runtime
+    fn configure(&mut self, _group: &str, _settings: &Settings) -> Result<(), String> {
+        Err("backend does not support runtime configuration".to_owned())
+    }
+    /// Verify Storage can be Opened (Run on Daemon Startup)
+    fn check(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+    /// Write Pending Changes to Disk (Run on Daemon Shutdown)
+    fn flush(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+    /// Copy Records of Groups Lost when the Daemon Exits (Memory Storage)
+    fn snapshot(&mut self) -> Snapshot {
+        Snapshot::new()
+    }
+    /// Reload Records Saved by a Previous Snapshot into their Groups
+    fn restore(&mut self, snapshot: Snapshot) {
+        for (group, records) in snapshot {
+            let mut store = self.group(Some(&group));
+            for record in records {
+                store.insert(record.index, record);
+            }
+        }
+    }
 }
@@ -1,25 +1,57 @@
 //! Configuration Settings for Backend Implementations
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Display;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::{Duration, SystemTime};
 
-use serde::Deserialize;
+use serde::{de::Error, Deserialize, Serialize};
 
 use super::backend::Backend;
+use super::encryption::EncryptionConfig;
+use super::plugin::{custom_backend, Options};
+use super::store_jsonl::Jsonl;
 use super::store_kv::Kv;
 use super::store_memory::Memory;
+use super::store_redb::Redb;
 
-use crate::{DEFAULT_DISK_STORE, XDG_PREFIX};
+use crate::clipboard::ClipBody;
+use crate::{mime, xdg_dirs, DEFAULT_DISK_STORE, DEFAULT_JSONL_STORE, DEFAULT_REDB_STORE};
 
 fn disk_default() -> PathBuf {
-    xdg::BaseDirectories::with_prefix(XDG_PREFIX)
+    xdg_dirs()
         .expect("Failed to read xdg base dirs")
         .get_cache_file(DEFAULT_DISK_STORE)
 }
 
+/// Expand `~` and Variables in Disk Store Path (Relative Paths are within the Data Dir)
+///
+/// `$XDG_*_HOME` fall back to their defaults when unset, so paths like
+/// `$XDG_DATA_HOME/wclipd/secrets` work without exporting them.
+pub fn disk_path(path: &str) -> Result<PathBuf, String> {
+    let base = xdg::BaseDirectories::new().map_err(|e| e.to_string())?;
+    let dir = |path: PathBuf| Some(path.to_string_lossy().into_owned());
+    let expanded = shellexpand::full_with_context_no_errors(
+        path,
+        || std::env::var("HOME").ok(),
+        |var| match var {
+            "XDG_DATA_HOME" => dir(base.get_data_home()),
+            "XDG_CACHE_HOME" => dir(base.get_cache_home()),
+            "XDG_STATE_HOME" => dir(base.get_state_home()),
+            "XDG_CONFIG_HOME" => dir(base.get_config_home()),
+            var => std::env::var(var).ok(),
+        },
+    );
+    let path = PathBuf::from(expanded.as_ref());
+    let path = match path.is_relative() {
+        true => xdg_dirs().map_err(|e| e.to_string())?.get_data_file(path),
+        false => path,
+    };
+    // normalize so equivalent spellings share a single store
+    Ok(path.components().collect())
+}
+
 /// Backend Configuration Settings
 pub type BackendConfig = HashMap<String, GroupConfig>;
 
@@ -31,31 +63,179 @@ pub struct GroupConfig {
     pub expiration: Expiration,
     pub min_entries: usize,
     pub max_entries: Option<usize>,
+    pub script: Option<PathBuf>,
+    pub readonly: bool,
+    pub capture: Vec<String>,
+    /// Preview Length Overriding the Global `list.preview_length`
+    pub preview_length: Option<usize>,
+    /// How Duplicate Entries are Detected
+    pub dedupe: Dedupe,
+    /// Display Label Shown next to the Group Name
+    pub label: Option<String>,
+    /// Terminal Color Used when Displaying the Group
+    pub color: Option<GroupColor>,
+    /// Encrypt Record Contents before Writing to Storage
+    pub encryption: Option<EncryptionConfig>,
+    /// Largest Entry (in Bytes) Stored without Applying `large_entry_policy`
+    pub max_entry_size: Option<usize>,
+    /// How Entries Larger than `max_entry_size` are Handled
+    pub large_entry_policy: LargeEntryPolicy,
 }
 
 impl Default for GroupConfig {
     fn default() -> Self {
         Self {
-            storage: Storage::Disk(disk_default()),
+            storage: Storage::Disk(disk_default(), DiskTuning::default()),
             expiration: Expiration::OnReboot,
             min_entries: 0,
             max_entries: None,
+            script: None,
+            readonly: false,
+            capture: vec![],
+            preview_length: None,
+            dedupe: Dedupe::default(),
+            label: None,
+            color: None,
+            encryption: None,
+            max_entry_size: None,
+            large_entry_policy: LargeEntryPolicy::default(),
+        }
+    }
+}
+
+/// Runtime Group Setting Overrides
+pub type Settings = BTreeMap<String, String>;
+
+impl GroupConfig {
+    /// Update Setting from Key/Value Pair
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let invalid = || format!("invalid {key}: {value:?}");
+        match key {
+            "storage" => self.storage = Storage::from_str(value)?,
+            "expiration" => self.expiration = Expiration::from_str(value)?,
+            "min_entries" => self.min_entries = value.parse().map_err(|_| invalid())?,
+            "max_entries" => {
+                self.max_entries = match value {
+                    "none" | "null" => None,
+                    value => Some(value.parse().map_err(|_| invalid())?),
+                }
+            }
+            "preview_length" => {
+                self.preview_length = match value {
+                    "none" | "null" => None,
+                    value => Some(value.parse().map_err(|_| invalid())?),
+                }
+            }
+            "dedupe" => self.dedupe = Dedupe::from_str(value)?,
+            "max_entry_size" => {
+                self.max_entry_size = match value {
+                    "none" | "null" => None,
+                    value => Some(value.parse().map_err(|_| invalid())?),
+                }
+            }
+            "large_entry_policy" => self.large_entry_policy = LargeEntryPolicy::from_str(value)?,
+            "label" => {
+                self.label = match value {
+                    "none" | "null" => None,
+                    value => Some(value.to_owned()),
+                }
+            }
+            "color" => {
+                self.color = match value {
+                    "none" | "null" => None,
+                    value => Some(GroupColor::from_str(value)?),
+                }
+            }
+            "readonly" => self.readonly = value.parse().map_err(|_| invalid())?,
+            "capture" => {
+                self.capture = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|m| !m.is_empty())
+                    .map(str::to_owned)
+                    .collect()
+            }
+            _ => return Err(format!("unknown setting: {key:?}")),
         }
+        Ok(())
+    }
+    /// Check if Live Entry Matches Group Capture Mime Patterns
+    pub fn captures(&self, mimes: &[String]) -> bool {
+        self.capture
+            .iter()
+            .any(|pattern| mimes.iter().any(|mime| mime::matches(pattern, mime)))
     }
 }
 
+/// Sled Tuning for a Disk Store (Unset Values Keep Sled Defaults)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DiskTuning {
+    /// Page Cache Size in Bytes
+    pub cache_capacity: Option<u64>,
+    /// Compress Stored Pages with zstd (Only Applied to New Stores)
+    pub compression: bool,
+    /// Milliseconds between Background Flushes
+    pub flush_every_ms: Option<u64>,
+}
+
 /// Backend Storage Options Available
 #[derive(Debug, Clone)]
 pub enum Storage {
-    Disk(PathBuf),
+    Disk(PathBuf, DiskTuning),
+    /// Single-File Redb Database
+    Redb(PathBuf),
+    /// Directory of Append-Only JSON Lines Logs
+    Jsonl(PathBuf),
     Memory,
+    Custom(String, Options),
 }
 
 impl Storage {
     pub fn backend(&self) -> Box<dyn Backend> {
         match self {
-            Storage::Disk(path) => Box::new(Kv::new(path.to_owned())),
+            Storage::Disk(path, tuning) => Box::new(Kv::new(path.to_owned(), tuning)),
+            Storage::Redb(path) => Box::new(Redb::new(path.to_owned())),
+            Storage::Jsonl(path) => Box::new(Jsonl::new(path.to_owned())),
             Storage::Memory => Box::new(Memory::new()),
+            Storage::Custom(name, options) => custom_backend(name, options).unwrap_or_else(|| {
+                log::error!("unknown custom backend {name:?}. falling back to memory");
+                Box::new(Memory::new())
+            }),
+        }
+    }
+}
+
+/// Storage Specified as String or Custom Plugin with Options
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StorageSpec {
+    Name(String),
+    Disk {
+        disk: String,
+        #[serde(flatten)]
+        tuning: DiskTuning,
+    },
+    Custom {
+        custom: String,
+        #[serde(default)]
+        options: Options,
+    },
+}
+
+impl<'de> Deserialize<'de> for Storage {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match StorageSpec::deserialize(deserializer)? {
+            StorageSpec::Name(s) => Storage::from_str(&s).map_err(D::Error::custom),
+            StorageSpec::Disk { disk, tuning } => match Storage::from_str(&disk) {
+                Ok(Self::Disk(path, _)) => Ok(Self::Disk(path, tuning)),
+                Ok(_) => Err(D::Error::custom(format!("{disk:?} is not a disk store"))),
+                Err(err) => Err(D::Error::custom(err)),
+            },
+            StorageSpec::Custom { custom, options } => Ok(Self::Custom(custom, options)),
         }
     }
 }
@@ -65,16 +245,42 @@ impl FromStr for Storage {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "memory" => Ok(Self::Memory),
+            name if name.starts_with("custom:") => {
+                let name = name.trim_start_matches("custom:").to_owned();
+                Ok(Self::Custom(name, Options::new()))
+            }
+            "redb" => {
+                let path = xdg_dirs()
+                    .expect("Failed to read xdg base dirs")
+                    .get_cache_file(DEFAULT_REDB_STORE);
+                Ok(Self::Redb(path))
+            }
+            path if path.starts_with("redb:") => {
+                let path = disk_path(path.trim_start_matches("redb:"))
+                    .map_err(|err| format!("invalid storage path {s:?}: {err}"))?;
+                Ok(Self::Redb(path))
+            }
+            "jsonl" => {
+                let path = xdg_dirs()
+                    .expect("Failed to read xdg base dirs")
+                    .get_data_file(DEFAULT_JSONL_STORE);
+                Ok(Self::Jsonl(path))
+            }
+            path if path.starts_with("jsonl:") => {
+                let path = disk_path(path.trim_start_matches("jsonl:"))
+                    .map_err(|err| format!("invalid storage path {s:?}: {err}"))?;
+                Ok(Self::Jsonl(path))
+            }
             "disk" => {
-                let path = xdg::BaseDirectories::with_prefix(XDG_PREFIX)
+                let path = xdg_dirs()
                     .expect("Failed to read xdg base dirs")
                     .get_cache_file(DEFAULT_DISK_STORE);
-                Ok(Self::Disk(path))
+                Ok(Self::Disk(path, DiskTuning::default()))
             }
             path => {
-                let path = PathBuf::from_str(&path)
-                    .map_err(|_| format!("invalid storate option: {s:?}"))?;
-                Ok(Self::Disk(path))
+                let path =
+                    disk_path(path).map_err(|err| format!("invalid storage path {s:?}: {err}"))?;
+                Ok(Self::Disk(path, DiskTuning::default()))
             }
         }
     }
@@ -83,8 +289,140 @@ impl FromStr for Storage {
 impl Display for Storage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Disk(path) => write!(f, "{path:?}"),
+            Self::Disk(path, _) => write!(f, "{path:?}"),
+            Self::Redb(path) => write!(f, "redb:{path:?}"),
+            Self::Jsonl(path) => write!(f, "jsonl:{path:?}"),
             Self::Memory => write!(f, "memory"),
+            Self::Custom(name, options) => write!(f, "custom:{name}{options:?}"),
+        }
+    }
+}
+
+/// Duplicate Detection Strategy for Group Entries
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Dedupe {
+    /// Identical Bytes
+    Exact,
+    /// Identical after Trimming Surrounding Whitespace from Text
+    #[default]
+    Trimmed,
+    /// Identical Trimmed Text Ignoring Case
+    IgnoreCase,
+    /// Never Deduplicate (Every Copy Creates a Record)
+    Never,
+}
+
+impl Dedupe {
+    /// Check if Bodies Count as Duplicates
+    pub fn matches(&self, a: &ClipBody, b: &ClipBody) -> bool {
+        match (self, a, b) {
+            (Self::Exact, a, b) => a == b,
+            (Self::Trimmed, a, b) => a.matches(b),
+            (Self::IgnoreCase, ClipBody::Text(a), ClipBody::Text(b)) => {
+                a.trim().to_lowercase() == b.trim().to_lowercase()
+            }
+            (Self::IgnoreCase, a, b) => a.matches(b),
+            (Self::Never, _, _) => false,
+        }
+    }
+}
+
+impl Display for Dedupe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exact => write!(f, "exact"),
+            Self::Trimmed => write!(f, "trimmed"),
+            Self::IgnoreCase => write!(f, "ignore_case"),
+            Self::Never => write!(f, "never"),
+        }
+    }
+}
+
+impl FromStr for Dedupe {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "exact" => Ok(Self::Exact),
+            "trimmed" => Ok(Self::Trimmed),
+            "ignore_case" | "case_insensitive" | "case-insensitive" => Ok(Self::IgnoreCase),
+            "never" | "none" | "false" => Ok(Self::Never),
+            _ => Err(format!("invalid dedupe: {s:?}")),
+        }
+    }
+}
+
+/// Handling of Entries Larger than the Group `max_entry_size`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum LargeEntryPolicy {
+    /// Refuse to Store the Entry
+    #[default]
+    Skip,
+    /// Store the Entry Anyway
+    Store,
+    /// Write the Body to a File and Store a `text/uri-list` Referencing it
+    SpillToFile,
+}
+
+impl Display for LargeEntryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Skip => write!(f, "skip"),
+            Self::Store => write!(f, "store"),
+            Self::SpillToFile => write!(f, "spill-to-file"),
+        }
+    }
+}
+
+impl FromStr for LargeEntryPolicy {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(Self::Skip),
+            "store" => Ok(Self::Store),
+            "spill-to-file" | "spill_to_file" => Ok(Self::SpillToFile),
+            _ => Err(format!("invalid large_entry_policy: {s:?}")),
+        }
+    }
+}
+
+/// Terminal Color Used to Visually Separate Groups
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupColor {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl GroupColor {
+    /// Wrap Text in ANSI Color Escape Codes
+    pub fn paint(&self, text: &str) -> String {
+        let code = match self {
+            Self::Red => 31,
+            Self::Green => 32,
+            Self::Yellow => 33,
+            Self::Blue => 34,
+            Self::Magenta => 35,
+            Self::Cyan => 36,
+        };
+        format!("\x1b[{code}m{text}\x1b[0m")
+    }
+}
+
+impl FromStr for GroupColor {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "red" => Ok(Self::Red),
+            "green" => Ok(Self::Green),
+            "yellow" => Ok(Self::Yellow),
+            "blue" => Ok(Self::Blue),
+            "magenta" | "purple" => Ok(Self::Magenta),
+            "cyan" => Ok(Self::Cyan),
+            _ => Err(format!("invalid color: {s:?}")),
         }
     }
 }
@@ -146,10 +484,12 @@ impl FromStr for Expiration {
             "never" => Ok(Self::Never),
             "login" | "onlogin" => Ok(Self::OnLogin),
             "reboot" | "onreboot" => Ok(Self::OnReboot),
-            _ => {
-                let seconds: u64 = s.parse().map_err(|_| format!("invalid lifetime: {s:?}"))?;
-                Ok(Self::Duration(Duration::from_secs(seconds)))
-            }
+            _ => match s.parse::<u64>() {
+                Ok(seconds) => Ok(Self::Duration(Duration::from_secs(seconds))),
+                Err(_) => humantime::parse_duration(s)
+                    .map(Self::Duration)
+                    .map_err(|_| format!("invalid lifetime: {s:?}")),
+            },
         }
     }
 }
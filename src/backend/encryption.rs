@@ -0,0 +1,193 @@
+//! At-Rest Encryption of Record Contents for Backend Groups
+//!
+//! Sealed records keep their index and timestamps in plaintext, while the
+//! entry (body, mimes, note and source) is stored as a single encrypted
+//! blob: `WCE1 | salt(16) | nonce(12) | ciphertext`.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::Deserialize;
+
+use super::backend::{BackendGroup, Record};
+use crate::clipboard::Entry;
+
+/// MimeType Marking Records Holding an Encrypted Entry
+static SEALED_MIME: &str = "application/x-wclipd-sealed";
+
+/// Header Identifying the Sealed Blob Format
+static MAGIC: &[u8; 4] = b"WCE1";
+
+const SALT_SIZE: usize = 16;
+const NONCE_SIZE: usize = 12;
+
+/// Group Encryption Settings (Key from Passphrase or Keyfile)
+#[derive(Clone, Deserialize)]
+pub struct EncryptionConfig {
+    #[serde(default)]
+    pub passphrase: Option<String>,
+    #[serde(default)]
+    pub keyfile: Option<PathBuf>,
+}
+
+impl std::fmt::Debug for EncryptionConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // never leak the passphrase into logs
+        f.debug_struct("EncryptionConfig")
+            .field(
+                "passphrase",
+                &self.passphrase.as_ref().map(|_| "<redacted>"),
+            )
+            .field("keyfile", &self.keyfile)
+            .finish()
+    }
+}
+
+impl EncryptionConfig {
+    /// Read Secret used to Derive Encryption Keys
+    fn secret(&self) -> Result<Vec<u8>, String> {
+        let secret = match (&self.passphrase, &self.keyfile) {
+            (Some(_), Some(_)) => return Err("set either passphrase or keyfile".to_owned()),
+            (Some(passphrase), None) => passphrase.as_bytes().to_vec(),
+            (None, Some(keyfile)) => {
+                let path = PathBuf::from(shellexpand::tilde(&keyfile.to_string_lossy()).as_ref());
+                std::fs::read(&path)
+                    .map_err(|err| format!("failed to read keyfile {path:?}: {err}"))?
+            }
+            (None, None) => return Err("missing passphrase or keyfile".to_owned()),
+        };
+        match secret.is_empty() {
+            true => Err("encryption secret is empty".to_owned()),
+            false => Ok(secret),
+        }
+    }
+}
+
+/// Record Encryption with Keys Derived (Argon2id) per Salt
+#[derive(Clone)]
+pub struct Cipher {
+    secret: Arc<Vec<u8>>,
+    /// Salt used for Newly Sealed Records
+    salt: [u8; SALT_SIZE],
+    /// Derived Keys Cached by Salt (Derivation is Deliberately Slow)
+    keys: Arc<Mutex<HashMap<[u8; SALT_SIZE], Key>>>,
+}
+
+impl Cipher {
+    pub fn new(cfg: &EncryptionConfig) -> Result<Self, String> {
+        let mut salt = [0u8; SALT_SIZE];
+        OsRng.fill_bytes(&mut salt);
+        Ok(Self {
+            secret: Arc::new(cfg.secret()?),
+            salt,
+            keys: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+    /// Derive (or Retrieve Cached) Key for Salt
+    fn key(&self, salt: [u8; SALT_SIZE]) -> Result<Key, String> {
+        let mut keys = self.keys.lock().expect("cipher lock failed");
+        if let Some(key) = keys.get(&salt) {
+            return Ok(*key);
+        }
+        let mut key = Key::default();
+        Argon2::default()
+            .hash_password_into(&self.secret, &salt, &mut key)
+            .map_err(|err| format!("key derivation failed: {err}"))?;
+        keys.insert(salt, key);
+        Ok(key)
+    }
+    /// Encrypt Data into a Sealed Blob
+    pub fn seal(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        let cipher = ChaCha20Poly1305::new(&self.key(self.salt)?);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let sealed = cipher
+            .encrypt(&nonce, data)
+            .map_err(|_| "encryption failed".to_owned())?;
+        let mut blob = Vec::with_capacity(MAGIC.len() + SALT_SIZE + NONCE_SIZE + sealed.len());
+        blob.extend_from_slice(MAGIC);
+        blob.extend_from_slice(&self.salt);
+        blob.extend_from_slice(&nonce);
+        blob.extend_from_slice(&sealed);
+        Ok(blob)
+    }
+    /// Decrypt Sealed Blob (Fails on Wrong Key or Tampered Data)
+    pub fn open(&self, blob: &[u8]) -> Result<Vec<u8>, String> {
+        let rest = blob
+            .strip_prefix(MAGIC.as_slice())
+            .ok_or("unknown sealed format")?;
+        if rest.len() < SALT_SIZE + NONCE_SIZE {
+            return Err("sealed data is truncated".to_owned());
+        }
+        let (salt, rest) = rest.split_at(SALT_SIZE);
+        let (nonce, sealed) = rest.split_at(NONCE_SIZE);
+        let salt: [u8; SALT_SIZE] = salt.try_into().expect("invalid salt size");
+        let cipher = ChaCha20Poly1305::new(&self.key(salt)?);
+        cipher
+            .decrypt(Nonce::from_slice(nonce), sealed)
+            .map_err(|_| "decryption failed (wrong key?)".to_owned())
+    }
+    /// Replace Record Entry with its Sealed Form (Dropping Plaintext Previews)
+    fn seal_record(&self, mut record: Record) -> Result<Record, String> {
+        let data = serde_json::to_vec(&record.entry).map_err(|e| e.to_string())?;
+        record.entry = Entry::data(&self.seal(&data)?, Some(SEALED_MIME.to_owned()));
+        record.preview = None;
+        Ok(record)
+    }
+    /// Restore Record Entry from its Sealed Form (Plaintext Records Pass Through)
+    fn open_record(&self, mut record: Record) -> Option<Record> {
+        if record.entry.mime.first().map(String::as_str) != Some(SEALED_MIME) {
+            return Some(record);
+        }
+        let entry = self
+            .open(record.entry.as_bytes())
+            .and_then(|data| serde_json::from_slice(&data).map_err(|e| e.to_string()));
+        match entry {
+            Ok(entry) => {
+                record.entry = entry;
+                Some(record)
+            }
+            Err(err) => {
+                log::warn!("skipping sealed record {}: {err}", record.index);
+                None
+            }
+        }
+    }
+}
+
+/// Group Wrapper Encrypting Records before they Reach Storage
+pub struct Encrypted {
+    pub inner: Box<dyn BackendGroup>,
+    pub cipher: Cipher,
+}
+
+impl BackendGroup for Encrypted {
+    fn iter(&self) -> Box<dyn Iterator<Item = Record>> {
+        let cipher = self.cipher.clone();
+        Box::new(self.inner.iter().filter_map(move |r| cipher.open_record(r)))
+    }
+    fn get(&self, index: &usize) -> Option<Record> {
+        self.inner
+            .get(index)
+            .and_then(|r| self.cipher.open_record(r))
+    }
+    fn insert(&mut self, index: usize, record: Record) {
+        match self.cipher.seal_record(record) {
+            Ok(record) => self.inner.insert(index, record),
+            Err(err) => log::error!("refusing to store record {index} unencrypted: {err}"),
+        }
+    }
+    fn delete(&mut self, index: &usize) {
+        self.inner.delete(index)
+    }
+    fn index(&mut self) -> usize {
+        self.inner.index()
+    }
+    fn keys(&self) -> Vec<usize> {
+        self.inner.keys()
+    }
+}
@@ -1,63 +1,281 @@
 //! Backend Storage Manager
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{read_to_string, write};
+use std::path::PathBuf;
+use std::time::Duration;
 
 use crate::backend::CleanCfg;
+use crate::xdg_dirs;
 
-use super::backend::{Backend, BackendGroup};
-use super::config::{BackendConfig, GroupConfig};
+use super::backend::{Backend, BackendGroup, Snapshot};
+use super::config::{
+    BackendConfig, Dedupe, DiskTuning, Expiration, GroupColor, GroupConfig, LargeEntryPolicy,
+    Settings, Storage,
+};
+use super::encryption::{Cipher, Encrypted};
+use super::plugin;
+use super::store_jsonl::Jsonl;
+use super::store_kv::Kv;
+use super::store_null::Null;
+use super::store_redb::Redb;
+
+static STATE_FILE: &'static str = "groups.yaml";
+
+/// Runtime Configuration Overrides Persisted Between Restarts
+type Overrides = HashMap<String, Settings>;
+
+fn state_path() -> Option<PathBuf> {
+    xdg_dirs().ok()?.place_state_file(STATE_FILE).ok()
+}
 
 /// Backend Storage Manager Implementation
 pub struct Manager {
     config: BackendConfig,
+    overrides: Overrides,
     stores: HashMap<String, Box<dyn Backend>>,
+    /// Ciphers of Encrypted Groups (Kept to Reuse Derived Keys)
+    ciphers: HashMap<String, Cipher>,
+    /// Records Deleted by Cleanup not yet Reported
+    evicted: Vec<(String, usize)>,
 }
 
 impl Manager {
     pub fn new(config: BackendConfig) -> Self {
-        Self {
+        plugin::register_bundled();
+        let mut manager = Self {
             config,
+            overrides: Overrides::new(),
             stores: HashMap::new(),
-        }
+            ciphers: HashMap::new(),
+            evicted: vec![],
+        };
+        manager.load_overrides();
+        manager
     }
-    /// Retrieve Configuration Settings for Particular Group
-    fn get_config(&mut self, group: Option<&str>) -> GroupConfig {
-        if let Some(name) = group {
-            if let Some(config) = self.config.get(name) {
-                return config.clone();
+    /// Load and Apply Persisted Runtime Overrides
+    fn load_overrides(&mut self) {
+        let Some(path) = state_path().filter(|p| p.exists()) else {
+            return;
+        };
+        let overrides: Overrides = match read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_yaml::from_str(&s).map_err(|e| e.to_string()))
+        {
+            Ok(overrides) => overrides,
+            Err(err) => {
+                log::error!("failed to load group overrides {path:?}: {err}");
+                return;
+            }
+        };
+        for (group, settings) in overrides {
+            if let Err(err) = self.configure(&group, &settings) {
+                log::error!("invalid override for group {group:?}: {err}");
             }
         }
-        if let Some(config) = self.config.get("default") {
-            return config.clone();
+    }
+    /// Persist Runtime Overrides to State File
+    fn save_overrides(&self) {
+        let Some(path) = state_path() else {
+            log::error!("failed to find state directory for group overrides");
+            return;
+        };
+        let result = serde_yaml::to_string(&self.overrides)
+            .map_err(|e| e.to_string())
+            .and_then(|s| write(&path, s).map_err(|e| e.to_string()));
+        if let Err(err) = result {
+            log::error!("failed to save group overrides {path:?}: {err}");
+        }
+    }
+    /// Wrap Group Storage with Encryption (if Configured for Group)
+    fn encrypt(
+        &mut self,
+        group: Option<&str>,
+        config: &GroupConfig,
+        store: Box<dyn BackendGroup>,
+    ) -> Box<dyn BackendGroup> {
+        let Some(encryption) = &config.encryption else {
+            return store;
+        };
+        let name = group.unwrap_or("default").to_owned();
+        if !self.ciphers.contains_key(&name) {
+            match Cipher::new(encryption) {
+                Ok(cipher) => {
+                    self.ciphers.insert(name.clone(), cipher);
+                }
+                Err(err) => {
+                    // never fall back to writing plaintext
+                    log::error!("encryption unavailable for group {name:?}: {err}");
+                    return Null::new().group(group);
+                }
+            }
         }
-        let name = group.unwrap_or("default");
-        self.config.insert(name.to_owned(), GroupConfig::default());
-        return self
-            .config
-            .get(name)
-            .expect("unable to find backend config")
-            .clone();
+        Box::new(Encrypted {
+            inner: store,
+            cipher: self.ciphers[&name].clone(),
+        })
+    }
+    /// Retrieve Configuration Settings for Particular Group
+    ///
+    /// Unknown groups fall back to the `default` entry (or built-in defaults)
+    /// without being added, so only configured groups are ever listed.
+    fn get_config(&self, group: Option<&str>) -> GroupConfig {
+        group
+            .and_then(|name| self.config.get(name))
+            .or_else(|| self.config.get("default"))
+            .cloned()
+            .unwrap_or_default()
     }
 }
 
 impl Backend for Manager {
     fn groups(&self) -> Vec<String> {
-        self.stores.values().map(|b| b.groups()).flatten().collect()
+        // include configured groups even if nothing is stored in them yet
+        let stored = self.stores.values().flat_map(|b| b.groups());
+        let configured = self.config.keys().cloned();
+        let groups: BTreeSet<String> = stored.chain(configured).collect();
+        groups.into_iter().collect()
     }
     fn group(&mut self, group: Option<&str>) -> Box<dyn BackendGroup> {
         let config = self.get_config(group);
         let storage = config.storage.to_string();
         log::debug!("backend for group {group:?} is {storage:?}");
         if let Some(backend) = self.stores.get_mut(&storage) {
-            let mut group = backend.group(group);
-            group.clean(&CleanCfg::from(&config));
-            return group;
+            let store = backend.group(group);
+            let mut store = self.encrypt(group, &config, store);
+            let name = group.unwrap_or("default");
+            let deleted = store.clean(&CleanCfg::from(&config));
+            self.evicted
+                .extend(deleted.into_iter().map(|index| (name.to_owned(), index)));
+            return store;
         }
         let backend = config.storage.backend();
         self.stores.insert(storage.to_owned(), backend);
-        self.stores
+        let store = self
+            .stores
             .get_mut(&storage)
             .expect("failed to find backend")
-            .group(group)
+            .group(group);
+        self.encrypt(group, &config, store)
+    }
+    fn lifetime(&mut self, group: Option<&str>) -> Option<Duration> {
+        match self.get_config(group).expiration {
+            Expiration::Duration(duration) => Some(duration),
+            _ => None,
+        }
+    }
+    fn preview_length(&mut self, group: Option<&str>) -> Option<usize> {
+        self.get_config(group).preview_length
+    }
+    fn dedupe(&mut self, group: Option<&str>) -> Dedupe {
+        self.get_config(group).dedupe
+    }
+    fn readonly(&mut self, group: Option<&str>) -> bool {
+        self.get_config(group).readonly
+    }
+    fn label(&mut self, group: Option<&str>) -> (Option<String>, Option<GroupColor>) {
+        let config = self.get_config(group);
+        (config.label, config.color)
+    }
+    fn encrypted(&mut self, group: Option<&str>) -> bool {
+        self.get_config(group).encryption.is_some()
+    }
+    fn evicted(&mut self) -> Vec<(String, usize)> {
+        std::mem::take(&mut self.evicted)
+    }
+    fn max_entry_size(&mut self, group: Option<&str>) -> Option<(usize, LargeEntryPolicy)> {
+        let config = self.get_config(group);
+        config
+            .max_entry_size
+            .map(|max| (max, config.large_entry_policy))
+    }
+    fn configure(&mut self, group: &str, settings: &Settings) -> Result<(), String> {
+        let mut config = self.get_config(Some(group));
+        for (key, value) in settings {
+            config.set(key, value)?;
+        }
+        log::info!("updated configuration for group {group:?}: {settings:?}");
+        self.config.insert(group.to_owned(), config);
+        let overrides = self.overrides.entry(group.to_owned()).or_default();
+        overrides.extend(settings.clone());
+        self.save_overrides();
+        Ok(())
+    }
+    fn flush(&mut self) -> Result<(), String> {
+        let errors: Vec<String> = self
+            .stores
+            .values_mut()
+            .filter_map(|store| store.flush().err())
+            .collect();
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors.join("; ")),
+        }
+    }
+    fn snapshot(&mut self) -> Snapshot {
+        self.stores
+            .values_mut()
+            .flat_map(|store| store.snapshot())
+            .collect()
+    }
+    fn restore(&mut self, snapshot: Snapshot) {
+        for (group, records) in snapshot {
+            // records are restored as stored (sealed if the group is encrypted)
+            let storage = self.get_config(Some(&group)).storage.to_string();
+            let _ = self.group(Some(&group));
+            if let Some(store) = self.stores.get_mut(&storage) {
+                store.restore(Snapshot::from([(group, records)]));
+            }
+        }
+    }
+    fn check(&mut self) -> Result<(), String> {
+        let mut tunings: HashMap<&PathBuf, (&str, &DiskTuning)> = HashMap::new();
+        for (name, config) in self.config.iter() {
+            if let Some(encryption) = &config.encryption {
+                Cipher::new(encryption)
+                    .map_err(|err| format!("invalid encryption for group {name:?}: {err}"))?;
+                // spilled files are written in plaintext
+                if config.large_entry_policy == LargeEntryPolicy::SpillToFile {
+                    return Err(format!("encrypted group {name:?} cannot spill-to-file"));
+                }
+            }
+            // a disk store is opened once, so its groups must agree on tuning
+            if let Storage::Disk(path, tuning) = &config.storage {
+                match tunings.get(path) {
+                    Some((other, known)) if *known != tuning => {
+                        return Err(format!(
+                            "groups {other:?} and {name:?} tune disk store {path:?} differently"
+                        ));
+                    }
+                    _ => tunings.insert(path, (name, tuning)),
+                };
+            }
+        }
+        // open configured groups first so an implicit default never sets the tuning
+        let default = self.config.get("default").cloned().unwrap_or_default();
+        let configs: Vec<GroupConfig> = self.config.values().cloned().collect();
+        for config in configs.into_iter().chain(std::iter::once(default)) {
+            let storage = config.storage.to_string();
+            if self.stores.contains_key(&storage) {
+                continue;
+            }
+            let store: Box<dyn Backend> = match &config.storage {
+                Storage::Disk(path, tuning) => Box::new(
+                    Kv::open(path.to_owned(), tuning)
+                        .map_err(|err| format!("failed to open disk store {path:?}: {err}"))?,
+                ),
+                Storage::Redb(path) => Box::new(
+                    Redb::open(path.to_owned())
+                        .map_err(|err| format!("failed to open redb store {path:?}: {err}"))?,
+                ),
+                Storage::Jsonl(path) => Box::new(
+                    Jsonl::open(path.to_owned())
+                        .map_err(|err| format!("failed to open jsonl store {path:?}: {err}"))?,
+                ),
+                _ => continue,
+            };
+            self.stores.insert(storage, store);
+        }
+        Ok(())
     }
 }
@@ -1,11 +1,22 @@
 //! Backend Storage Implementations for Clipboard Daemon
+//!
+//! All storage goes through the [`Backend`]/[`BackendGroup`] traits.
+//! [`Manager`] routes each group to a backend based on its [`GroupConfig`],
+//! and `Storage::Custom` resolves backends registered as plugins.
 
 mod backend;
 mod config;
+mod encryption;
 mod manager;
+mod plugin;
+mod store_jsonl;
 mod store_kv;
 mod store_memory;
+mod store_null;
+mod store_redb;
 
 pub use backend::*;
 pub use config::*;
+pub use encryption::{Cipher, EncryptionConfig};
 pub use manager::Manager;
+pub use plugin::{register_backend, BackendFactory, Options};
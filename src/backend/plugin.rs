@@ -0,0 +1,43 @@
+//! Registration API for Custom Backend Implementations
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{OnceLock, RwLock};
+
+use super::backend::Backend;
+use super::store_null::Null;
+
+/// Custom Backend Options Specified in Configuration
+pub type Options = BTreeMap<String, String>;
+
+/// Factory Function Producing a Backend from its Options
+pub type BackendFactory = fn(&Options) -> Box<dyn Backend>;
+
+static REGISTRY: OnceLock<RwLock<HashMap<String, BackendFactory>>> = OnceLock::new();
+
+#[inline]
+fn registry() -> &'static RwLock<HashMap<String, BackendFactory>> {
+    REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Register Plugins Bundled with WClipD
+pub fn register_bundled() {
+    register_backend("null", |_| Box::new(Null::new()));
+}
+
+/// Register Custom Backend Factory under the Given Name
+pub fn register_backend(name: &str, factory: BackendFactory) {
+    registry()
+        .write()
+        .expect("plugin registry write failed")
+        .insert(name.to_owned(), factory);
+}
+
+/// Spawn Custom Backend using Registered Factory (if Found)
+pub fn custom_backend(name: &str, options: &Options) -> Option<Box<dyn Backend>> {
+    let factory = registry()
+        .read()
+        .expect("plugin registry read failed")
+        .get(name)
+        .copied()?;
+    Some(factory(options))
+}
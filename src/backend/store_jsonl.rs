@@ -0,0 +1,300 @@
+//! Append-Only JSON Lines Backend (One Greppable File per Group)
+//!
+//! Every insert appends the full record and every delete appends a
+//! `{"deleted": index}` marker, so the latest line for an index wins.
+//! New indexes are claimed with a `{"reserved": index}` marker. Writers
+//! hold an exclusive lock on the store's `.lock` file (readers a shared
+//! one) and logs are compacted once they are mostly stale lines.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::{FileExt, MetadataExt};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use super::backend::*;
+
+/// Lock File Guarding every Log within the Store Directory
+static LOCK_FILE: &str = ".lock";
+
+/// Extension of Group Log Files
+static EXTENSION: &str = "jsonl";
+
+/// Logs Shorter than this are Never Compacted
+static COMPACT_MIN_LINES: usize = 64;
+
+/// Log File Name for Group (Escaping Path Separators)
+fn file_name(group: &str) -> String {
+    let name = group.replace('%', "%25").replace('/', "%2F");
+    format!("{name}.{EXTENSION}")
+}
+
+/// Group Name from Log File Stem
+fn group_name(stem: &str) -> String {
+    stem.replace("%2F", "/").replace("%25", "%")
+}
+
+/// Replayed Logs by Path with the File State they were Read at
+type ReplayCache = Arc<Mutex<HashMap<PathBuf, (Option<Stamp>, Replay)>>>;
+
+pub struct Jsonl {
+    dir: PathBuf,
+    cache: ReplayCache,
+}
+
+impl Jsonl {
+    pub fn new(dir: PathBuf) -> Self {
+        Self::open(dir).expect("unable to open jsonl store")
+    }
+    /// Open Store Directory (Reporting Failures Instead of Panicking)
+    pub fn open(dir: PathBuf) -> Result<Self, String> {
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        Ok(Self {
+            dir,
+            cache: ReplayCache::default(),
+        })
+    }
+}
+
+impl Backend for Jsonl {
+    fn groups(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return vec![];
+        };
+        entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == EXTENSION))
+            .filter_map(|p| Some(group_name(p.file_stem()?.to_str()?)))
+            .collect()
+    }
+    fn group(&mut self, group: Group) -> Box<dyn BackendGroup> {
+        let name = group.unwrap_or("default");
+        Box::new(JsonlGroup {
+            path: self.dir.join(file_name(name)),
+            lock: self.dir.join(LOCK_FILE),
+            cache: Arc::clone(&self.cache),
+        })
+    }
+}
+
+/// Line of a Group Log
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Line {
+    Record(Box<Record>),
+    Deleted { deleted: usize },
+    Reserved { reserved: usize },
+}
+
+/// Group Log State after Replaying every Line
+#[derive(Default)]
+struct Replay {
+    records: BTreeMap<usize, Record>,
+    /// Next Unused Index (Deleted Indexes are Never Reused)
+    next: usize,
+    lines: usize,
+}
+
+impl Replay {
+    /// Apply Log Line to the Current Records
+    fn apply(&mut self, line: Line) {
+        self.lines += 1;
+        match line {
+            Line::Record(record) => {
+                self.next = self.next.max(record.index + 1);
+                self.records.insert(record.index, *record);
+            }
+            Line::Deleted { deleted } => {
+                self.next = self.next.max(deleted + 1);
+                self.records.remove(&deleted);
+            }
+            Line::Reserved { reserved } => self.next = self.next.max(reserved + 1),
+        }
+    }
+}
+
+/// Log File State a Cached Replay is Valid for (Compaction Replaces the Inode)
+#[derive(Clone, PartialEq)]
+struct Stamp {
+    len: u64,
+    modified: SystemTime,
+    inode: u64,
+}
+
+impl Stamp {
+    /// Current State of Log File (None if Missing)
+    fn of(path: &Path) -> io::Result<Option<Self>> {
+        let meta = match fs::metadata(path) {
+            Ok(meta) => meta,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        Ok(Some(Self {
+            len: meta.len(),
+            modified: meta.modified()?,
+            inode: meta.ino(),
+        }))
+    }
+}
+
+struct JsonlGroup {
+    path: PathBuf,
+    lock: PathBuf,
+    cache: ReplayCache,
+}
+
+impl JsonlGroup {
+    /// Run Operation while Holding the Store Lock (Shared for Reads)
+    fn locked<T>(&self, exclusive: bool, op: impl FnOnce() -> io::Result<T>) -> T {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&self.lock)
+            .expect("jsonl failed to open lock file");
+        let mode = match exclusive {
+            true => libc::LOCK_EX,
+            false => libc::LOCK_SH,
+        };
+        if unsafe { libc::flock(file.as_raw_fd(), mode) } != 0 {
+            panic!("jsonl lock failed: {}", io::Error::last_os_error());
+        }
+        // the lock is released once the file is dropped
+        op().expect("jsonl store failed")
+    }
+    /// Run Operation on the Current Log State (Replayed only once the File Changed)
+    ///
+    /// Must be called while holding the store lock.
+    fn cached<T>(&self, op: impl FnOnce(&mut Replay) -> io::Result<T>) -> io::Result<T> {
+        let mut cache = self.cache.lock().expect("mutex lock failed");
+        let stamp = Stamp::of(&self.path)?;
+        let fresh = cache
+            .get(&self.path)
+            .is_some_and(|(cached, _)| *cached == stamp);
+        if !fresh {
+            cache.insert(self.path.clone(), (stamp, self.replay()?));
+        }
+        let (stamp, replay) = cache.get_mut(&self.path).expect("missing cached replay");
+        let result = op(replay)?;
+        // writes change the file, so record the state the replay now matches
+        *stamp = Stamp::of(&self.path)?;
+        Ok(result)
+    }
+    /// Replay Log into the Current Records
+    fn replay(&self) -> io::Result<Replay> {
+        let mut replay = Replay::default();
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(replay),
+            Err(err) => return Err(err),
+        };
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // a torn line (crash mid-append) is skipped
+            match serde_json::from_str(&line) {
+                Ok(line) => replay.apply(line),
+                Err(err) => {
+                    replay.lines += 1;
+                    log::warn!("skipping invalid line in {:?}: {err}", self.path)
+                }
+            }
+        }
+        Ok(replay)
+    }
+    /// Rewrite Log with only the Current Records
+    fn compact(&self, replay: &Replay) -> io::Result<()> {
+        let mut data = vec![];
+        for record in replay.records.values() {
+            serde_json::to_writer(&mut data, &Line::Record(Box::new(record.clone())))?;
+            data.push(b'\n');
+        }
+        // keep the sequence when the newest records were deleted
+        let last = replay.records.keys().last().map(|i| i + 1).unwrap_or(0);
+        if replay.next > last {
+            let deleted = replay.next - 1;
+            serde_json::to_writer(&mut data, &Line::Deleted { deleted })?;
+            data.push(b'\n');
+        }
+        let tmp = self.path.with_extension(format!("{EXTENSION}.tmp"));
+        fs::write(&tmp, data)?;
+        fs::rename(tmp, &self.path)
+    }
+    /// Append Line to Log (Compacting once it is Mostly Stale)
+    fn append(&self, line: Line) {
+        self.locked(true, || self.append_locked(line))
+    }
+    /// Append Line while Already Holding the Exclusive Lock
+    fn append_locked(&self, line: Line) -> io::Result<()> {
+        self.cached(|replay| {
+            self.write_line(&line)?;
+            replay.apply(line);
+            if replay.lines > COMPACT_MIN_LINES && replay.lines > replay.records.len() * 2 {
+                self.compact(replay)?;
+                *replay = self.replay()?;
+            }
+            Ok(())
+        })
+    }
+    /// Write Line to the End of the Log
+    fn write_line(&self, line: &Line) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut data = serde_json::to_vec(line)?;
+        data.push(b'\n');
+        // never glue the new line onto a torn one
+        let size = file.metadata()?.len();
+        let mut last = [b'\n'];
+        if size > 0 {
+            file.read_exact_at(&mut last, size - 1)?;
+        }
+        if last[0] != b'\n' {
+            data.insert(0, b'\n');
+        }
+        file.write_all(&data)
+    }
+}
+
+impl BackendGroup for JsonlGroup {
+    fn get(&self, index: &usize) -> Option<Record> {
+        self.locked(false, || self.cached(|r| Ok(r.records.get(index).cloned())))
+    }
+    fn insert(&mut self, index: usize, mut record: Record) {
+        // lines are keyed by the record's own index
+        record.index = index;
+        self.append(Line::Record(Box::new(record)))
+    }
+    fn delete(&mut self, index: &usize) {
+        self.append(Line::Deleted { deleted: *index })
+    }
+    fn iter(&self) -> Box<dyn Iterator<Item = Record>> {
+        let records = self.locked(false, || {
+            self.cached(|r| Ok(r.records.values().cloned().collect::<Vec<_>>()))
+        });
+        Box::new(records.into_iter())
+    }
+    fn keys(&self) -> Vec<usize> {
+        self.locked(false, || {
+            self.cached(|r| Ok(r.records.keys().copied().collect()))
+        })
+    }
+    fn index(&mut self) -> usize {
+        // claim the index so another writer of the store never hands it out too
+        self.locked(true, || {
+            let index = self.cached(|r| Ok(r.next))?;
+            self.append_locked(Line::Reserved { reserved: index })?;
+            Ok(index)
+        })
+    }
+}
@@ -3,16 +3,30 @@
 use std::path::PathBuf;
 
 use super::backend::*;
+use super::config::DiskTuning;
+
+/// Bucket Holding the Next Unused Index of each Group
+static SEQUENCE_BUCKET: &'static str = "__wclipd_sequence";
 
 pub struct Kv {
     store: kv::Store,
 }
 
 impl Kv {
-    pub fn new(path: PathBuf) -> Self {
-        let config = kv::Config::new(path);
-        let store = kv::Store::new(config).expect("unable to spawn kv");
-        Self { store }
+    pub fn new(path: PathBuf, tuning: &DiskTuning) -> Self {
+        Self::open(path, tuning).expect("unable to spawn kv")
+    }
+    /// Open Disk Store (Reporting Failures Instead of Panicking)
+    pub fn open(path: PathBuf, tuning: &DiskTuning) -> Result<Self, kv::Error> {
+        let mut config = kv::Config::new(path).use_compression(tuning.compression);
+        if let Some(capacity) = tuning.cache_capacity {
+            config = config.cache_capacity(capacity);
+        }
+        if let Some(ms) = tuning.flush_every_ms {
+            config = config.flush_every_ms(ms);
+        }
+        let store = kv::Store::new(config)?;
+        Ok(Self { store })
     }
 }
 
@@ -21,20 +35,61 @@ impl Backend for Kv {
         self.store
             .buckets()
             .into_iter()
-            .filter(|g| g != "__sled__default")
+            .filter(|g| g != "__sled__default" && g != SEQUENCE_BUCKET)
             .collect()
     }
+    fn flush(&mut self) -> Result<(), String> {
+        for name in self.store.buckets() {
+            self.store
+                .bucket::<String, String>(Some(&name))
+                .and_then(|bucket| bucket.flush())
+                .map_err(|err| format!("failed to flush bucket {name:?}: {err}"))?;
+        }
+        Ok(())
+    }
     fn group(&mut self, group: Group) -> Box<dyn BackendGroup> {
+        let name = group.unwrap_or("default").to_owned();
         let bucket = self
             .store
-            .bucket(Some(group.unwrap_or("default")))
+            .bucket(Some(&name))
             .expect("kv failed to access bucket");
-        Box::new(KvGroup { bucket })
+        let sequence = self
+            .store
+            .bucket(Some(SEQUENCE_BUCKET))
+            .expect("kv failed to access sequence bucket");
+        Box::new(KvGroup {
+            name,
+            bucket,
+            sequence,
+        })
     }
 }
 
 struct KvGroup<'a> {
+    name: String,
     bucket: kv::Bucket<'a, kv::Integer, kv::Json<Record>>,
+    sequence: kv::Bucket<'a, String, kv::Json<usize>>,
+}
+
+impl<'a> KvGroup<'a> {
+    /// Next Unused Index Persisted for Group
+    fn next_index(&self) -> usize {
+        self.sequence
+            .get(&self.name)
+            .expect("kv sequence read failed")
+            .map(|j| j.0)
+            .unwrap_or(0)
+    }
+    /// Persist Next Unused Index (Never Moves Backwards)
+    fn advance(&self, next: usize) {
+        if next <= self.next_index() {
+            return;
+        }
+        self.sequence
+            .set(&self.name, &kv::Json(next))
+            .expect("kv sequence write failed");
+        self.sequence.flush().expect("kv sequence flush failed");
+    }
 }
 
 impl<'a> BackendGroup for KvGroup<'a> {
@@ -45,6 +100,7 @@ impl<'a> BackendGroup for KvGroup<'a> {
             .map(|j| j.0)
     }
     fn insert(&mut self, index: usize, record: Record) {
+        self.advance(index + 1);
         self.bucket
             .set(&kv::Integer::from(index), &kv::Json(record))
             .expect("kv bucket write failed");
@@ -65,14 +121,27 @@ impl<'a> BackendGroup for KvGroup<'a> {
                 .map(|r: kv::Json<Record>| r.0),
         )
     }
-    fn index(&mut self) -> usize {
+    fn keys(&self) -> Vec<usize> {
         self.bucket
+            .iter()
+            .filter_map(|r| r.ok())
+            .map(|i| i.key().expect("kv bucket index failed"))
+            .map(|i: kv::Integer| usize::from(i))
+            .collect()
+    }
+    fn index(&mut self) -> usize {
+        // stores written before the sequence existed only have their keys
+        let stored = self
+            .bucket
             .iter()
             .filter_map(|r| r.ok())
             .map(|i| i.key().expect("kv bucket index failed"))
             .map(|i: kv::Integer| usize::from(i))
             .max()
             .map(|max| max + 1)
-            .unwrap_or(0)
+            .unwrap_or(0);
+        let index = std::cmp::max(self.next_index(), stored);
+        self.advance(index + 1);
+        index
     }
 }
@@ -1,6 +1,7 @@
 //! Memory Storage for Backend Implementation
 
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 
 use super::backend::*;
@@ -30,18 +31,25 @@ impl<'a> Backend for Memory {
         let group = self.store.get(name).unwrap();
         Box::new((*group).clone())
     }
+    fn snapshot(&mut self) -> Snapshot {
+        self.store
+            .iter()
+            .map(|(name, group)| (name.to_owned(), group.iter().collect()))
+            .collect()
+    }
 }
 
 struct MemoryGroup {
     store: Arc<RwLock<HashMap<usize, Record>>>,
-    last_index: usize,
+    /// Next Unused Index (Shared by All Handles to the Group)
+    next_index: Arc<AtomicUsize>,
 }
 
 impl MemoryGroup {
     fn new() -> Self {
         Self {
             store: Arc::new(RwLock::new(HashMap::new())),
-            last_index: 0,
+            next_index: Arc::new(AtomicUsize::new(0)),
         }
     }
 }
@@ -50,7 +58,7 @@ impl Clone for MemoryGroup {
     fn clone(&self) -> Self {
         Self {
             store: Arc::clone(&self.store),
-            last_index: self.last_index,
+            next_index: Arc::clone(&self.next_index),
         }
     }
 }
@@ -64,6 +72,7 @@ impl BackendGroup for MemoryGroup {
             .map(|r| r.clone())
     }
     fn insert(&mut self, index: usize, record: Record) {
+        self.next_index.fetch_max(index + 1, Ordering::SeqCst);
         self.store
             .write()
             .expect("group lock write failed")
@@ -85,8 +94,10 @@ impl BackendGroup for MemoryGroup {
         )
     }
     fn index(&mut self) -> usize {
-        let index = self.last_index;
-        self.last_index += 1;
-        index
+        self.next_index.fetch_add(1, Ordering::SeqCst)
+    }
+    fn keys(&self) -> Vec<usize> {
+        let store = self.store.read().expect("group lock read failed");
+        store.keys().copied().collect()
     }
 }
@@ -0,0 +1,47 @@
+//! Null Storage Plugin that Discards all Records
+
+use std::collections::HashSet;
+
+use super::backend::*;
+
+/// Example Custom Backend Registered via the Plugin API
+pub struct Null {
+    groups: HashSet<String>,
+}
+
+impl Null {
+    pub fn new() -> Self {
+        Self {
+            groups: HashSet::new(),
+        }
+    }
+}
+
+impl Backend for Null {
+    fn groups(&self) -> Vec<String> {
+        self.groups.iter().cloned().collect()
+    }
+    fn group(&mut self, group: Group) -> Box<dyn BackendGroup> {
+        self.groups.insert(group.unwrap_or("default").to_owned());
+        Box::new(NullGroup { index: 0 })
+    }
+}
+
+struct NullGroup {
+    index: usize,
+}
+
+impl BackendGroup for NullGroup {
+    fn get(&self, _index: &usize) -> Option<Record> {
+        None
+    }
+    fn insert(&mut self, _index: usize, _record: Record) {}
+    fn delete(&mut self, _index: &usize) {}
+    fn iter(&self) -> Box<dyn Iterator<Item = Record>> {
+        Box::new(std::iter::empty())
+    }
+    fn index(&mut self) -> usize {
+        self.index += 1;
+        self.index
+    }
+}
@@ -0,0 +1,171 @@
+//! Redb Single-File Disk Backend Database
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use redb::{Database, ReadableTable, TableDefinition, TableError, TableHandle};
+
+use super::backend::*;
+
+/// Table Holding the Next Unused Index of each Group
+static SEQUENCE_TABLE: TableDefinition<&str, u64> = TableDefinition::new("__wclipd_sequence");
+
+/// Table of JSON Encoded Records by Index for Group
+fn records(name: &str) -> TableDefinition<'_, u64, &'static [u8]> {
+    TableDefinition::new(name)
+}
+
+pub struct Redb {
+    db: Arc<Database>,
+}
+
+impl Redb {
+    pub fn new(path: PathBuf) -> Self {
+        Self::open(path).expect("unable to open redb")
+    }
+    /// Open Database File (Reporting Failures Instead of Panicking)
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let db = Database::create(path).map_err(|e| e.to_string())?;
+        Ok(Self { db: Arc::new(db) })
+    }
+}
+
+impl Backend for Redb {
+    fn groups(&self) -> Vec<String> {
+        let txn = self.db.begin_read().expect("redb read failed");
+        txn.list_tables()
+            .expect("redb failed to list tables")
+            .map(|t| t.name().to_owned())
+            .filter(|name| name != SEQUENCE_TABLE.name())
+            .collect()
+    }
+    fn group(&mut self, group: Group) -> Box<dyn BackendGroup> {
+        Box::new(RedbGroup {
+            name: group.unwrap_or("default").to_owned(),
+            db: Arc::clone(&self.db),
+        })
+    }
+}
+
+struct RedbGroup {
+    name: String,
+    db: Arc<Database>,
+}
+
+impl RedbGroup {
+    /// Decoded Records of Group (Empty until the First Insert)
+    fn records(&self) -> Vec<Record> {
+        let txn = self.db.begin_read().expect("redb read failed");
+        let table = match txn.open_table(records(&self.name)) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return vec![],
+            Err(err) => panic!("redb failed to open table: {err:?}"),
+        };
+        table
+            .iter()
+            .expect("redb table iter failed")
+            .filter_map(|r| r.ok())
+            .filter_map(|(_, v)| serde_json::from_slice(v.value()).ok())
+            .collect()
+    }
+    /// Next Unused Index Persisted for Group
+    fn next_index(&self) -> usize {
+        let txn = self.db.begin_read().expect("redb read failed");
+        let table = match txn.open_table(SEQUENCE_TABLE) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return 0,
+            Err(err) => panic!("redb failed to open sequence: {err:?}"),
+        };
+        table
+            .get(self.name.as_str())
+            .expect("redb sequence read failed")
+            .map(|v| v.value() as usize)
+            .unwrap_or(0)
+    }
+    /// Persist Next Unused Index (Never Moves Backwards)
+    fn advance(&self, next: usize) {
+        if next <= self.next_index() {
+            return;
+        }
+        let txn = self.db.begin_write().expect("redb write failed");
+        {
+            let mut table = txn
+                .open_table(SEQUENCE_TABLE)
+                .expect("redb failed to open sequence");
+            table
+                .insert(self.name.as_str(), next as u64)
+                .expect("redb sequence write failed");
+        }
+        txn.commit().expect("redb sequence commit failed");
+    }
+}
+
+impl BackendGroup for RedbGroup {
+    fn get(&self, index: &usize) -> Option<Record> {
+        let txn = self.db.begin_read().expect("redb read failed");
+        let table = match txn.open_table(records(&self.name)) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return None,
+            Err(err) => panic!("redb failed to open table: {err:?}"),
+        };
+        let value = table.get(*index as u64).expect("redb table read failed")?;
+        serde_json::from_slice(value.value()).ok()
+    }
+    fn insert(&mut self, index: usize, record: Record) {
+        self.advance(index + 1);
+        let data = serde_json::to_vec(&record).expect("record serialization failed");
+        let txn = self.db.begin_write().expect("redb write failed");
+        {
+            let mut table = txn
+                .open_table(records(&self.name))
+                .expect("redb failed to open table");
+            table
+                .insert(index as u64, data.as_slice())
+                .expect("redb table write failed");
+        }
+        txn.commit().expect("redb commit failed");
+    }
+    fn delete(&mut self, index: &usize) {
+        let txn = self.db.begin_write().expect("redb write failed");
+        {
+            let mut table = txn
+                .open_table(records(&self.name))
+                .expect("redb failed to open table");
+            table
+                .remove(*index as u64)
+                .expect("redb table delete failed");
+        }
+        txn.commit().expect("redb commit failed");
+    }
+    fn iter(&self) -> Box<dyn Iterator<Item = Record>> {
+        Box::new(self.records().into_iter())
+    }
+    fn keys(&self) -> Vec<usize> {
+        let txn = self.db.begin_read().expect("redb read failed");
+        let table = match txn.open_table(records(&self.name)) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return vec![],
+            Err(err) => panic!("redb failed to open table: {err:?}"),
+        };
+        table
+            .iter()
+            .expect("redb table iter failed")
+            .filter_map(|r| r.ok())
+            .map(|(k, _)| k.value() as usize)
+            .collect()
+    }
+    fn index(&mut self) -> usize {
+        let stored = self
+            .keys()
+            .into_iter()
+            .max()
+            .map(|max| max + 1)
+            .unwrap_or(0);
+        let index = std::cmp::max(self.next_index(), stored);
+        self.advance(index + 1);
+        index
+    }
+}
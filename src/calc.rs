@@ -0,0 +1,128 @@
+//! Minimal Arithmetic Evaluator for Calculator Picks
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Deepest Nesting of Parentheses, Signs and Powers (Bounds Recursion)
+const MAX_DEPTH: usize = 256;
+
+/// Recursive-Descent Expression Parser
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+        self.chars.peek().copied()
+    }
+    /// expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Option<f64> {
+        let mut value = self.term()?;
+        loop {
+            match self.peek() {
+                Some('+') => value += self.skip().term()?,
+                Some('-') => value -= self.skip().term()?,
+                _ => return Some(value),
+            }
+        }
+    }
+    /// term := factor (('*' | '/' | '%') factor)*
+    fn term(&mut self) -> Option<f64> {
+        let mut value = self.factor()?;
+        loop {
+            match self.peek() {
+                Some('*') => value *= self.skip().factor()?,
+                Some('/') => value /= self.skip().factor()?,
+                Some('%') => value %= self.skip().factor()?,
+                _ => return Some(value),
+            }
+        }
+    }
+    /// factor := unary ('^' factor)?
+    fn factor(&mut self) -> Option<f64> {
+        let base = self.unary()?;
+        match self.peek() {
+            Some('^') => Some(base.powf(self.skip().nested(Self::factor)?)),
+            _ => Some(base),
+        }
+    }
+    /// unary := '-' unary | '(' expr ')' | number
+    fn unary(&mut self) -> Option<f64> {
+        match self.peek()? {
+            '-' => Some(-self.skip().nested(Self::unary)?),
+            '(' => {
+                let value = self.skip().nested(Self::expr)?;
+                (self.peek()? == ')').then(|| self.skip())?;
+                Some(value)
+            }
+            _ => self.number(),
+        }
+    }
+    /// Parse Nested Rule (None once Nesting Exceeds `MAX_DEPTH`)
+    fn nested(&mut self, rule: fn(&mut Self) -> Option<f64>) -> Option<f64> {
+        if self.depth >= MAX_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        let value = rule(self);
+        self.depth -= 1;
+        value
+    }
+    fn number(&mut self) -> Option<f64> {
+        let mut digits = String::new();
+        let numeric = |c: &char| c.is_ascii_digit() || *c == '.' || *c == '_';
+        while let Some(c) = self.chars.next_if(numeric) {
+            if c != '_' {
+                digits.push(c);
+            }
+        }
+        digits.parse().ok()
+    }
+    fn skip(&mut self) -> &mut Self {
+        self.chars.next();
+        self
+    }
+}
+
+/// Evaluate Arithmetic Expression (None if Invalid)
+pub fn eval(expr: &str) -> Option<f64> {
+    let mut parser = Parser {
+        chars: expr.chars().peekable(),
+        depth: 0,
+    };
+    let value = parser.expr()?;
+    match parser.peek() {
+        Some(_) => None,
+        None => value.is_finite().then_some(value),
+    }
+}
+
+/// Check if Text is a Slash-Separated Date (e.g. `10/16/2026`)
+fn is_date(text: &str) -> bool {
+    let parts: Vec<&str> = text.split('/').collect();
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|p| (1..=4).contains(&p.len()) && p.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Evaluate Text if it is a Calculation (`=` Prefixed or Contains Operators)
+///
+/// Unprefixed text only counts when it uses an operator other than a bare
+/// `-` and is not a slashed date, so dates and phone numbers are left alone.
+pub fn calculate(text: &str) -> Option<String> {
+    let text = text.trim();
+    let expr = match text.strip_prefix('=') {
+        Some(expr) => expr,
+        None if is_date(text) => return None,
+        None if text.contains(['+', '*', '/', '%', '^']) || text.contains(" - ") => text,
+        None => return None,
+    };
+    let value = eval(expr)?;
+    Some(match value.fract() == 0.0 && value.abs() < 1e15 {
+        true => format!("{}", value as i64),
+        false => format!("{value}"),
+    })
+}
@@ -1,24 +1,65 @@
 //! Daemon Client Implementation
 
-use std::io::{self, BufRead, BufReader, Write};
+use std::io::{self, Write};
 use std::os::unix::net::UnixStream;
 use std::path::PathBuf;
 
 use thiserror::Error;
 
+use crate::backend::Settings;
 use crate::clipboard::{Entry, Preview};
 use crate::message::*;
+use crate::report::HistoryReport;
+
+/// Largest Response Frame Accepted from the Daemon
+static MAX_RESPONSE: usize = 1 << 30;
 
 #[derive(Debug, Error)]
 pub enum ClientError {
     #[error("Socket Error")]
     SocketError(#[from] io::Error),
-    #[error("Message Error")]
-    MessageError(#[from] serde_json::Error),
+    #[error("Message Encode Error")]
+    EncodeError(#[from] rmp_serde::encode::Error),
+    #[error("Message Decode Error")]
+    DecodeError(#[from] rmp_serde::decode::Error),
     #[error("Unexpected Response")]
     Unexpected(Response),
 }
 
+/// Stream of Events from Subscribed Daemon Connection
+pub struct Events {
+    socket: UnixStream,
+}
+
+impl Iterator for Events {
+    type Item = Result<Event, ClientError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        match read_frame(&mut self.socket, MAX_RESPONSE) {
+            Ok(None) => None,
+            Ok(Some(payload)) => match unframe(&payload) {
+                Ok(Response::Event { event }) => Some(Ok(event)),
+                Ok(response) => Some(Err(ClientError::Unexpected(response))),
+                Err(err) => Some(Err(err.into())),
+            },
+            Err(err) => Some(Err(err.into())),
+        }
+    }
+}
+
+/// Settings of a Copy Request (see `Client::copy_with`)
+#[derive(Debug, Default, Clone)]
+pub struct CopyOptions {
+    pub primary: bool,
+    pub group: Grp,
+    pub index: Idx,
+    /// Replace Record at Index even if Overwrites are Refused
+    pub force: bool,
+    /// Allow Copying into a Group that does not Exist Yet
+    pub create: bool,
+    /// Expected Revision of the Record at Index (Rejected on Mismatch)
+    pub revision: Option<u64>,
+}
+
 /// Client to Clipboard Daemon
 pub struct Client {
     socket: UnixStream,
@@ -34,14 +75,11 @@ impl Client {
 
     pub fn send(&mut self, request: Request) -> Result<Response, ClientError> {
         // write request to socket
-        let mut message = serde_json::to_vec(&request)?;
-        message.push('\n' as u8);
-        self.socket.write(&message)?;
+        self.socket.write_all(&frame(&request)?)?;
         // read response from socket
-        let mut buffer = String::new();
-        let mut reader = BufReader::new(&mut self.socket);
-        let n = reader.read_line(&mut buffer)?;
-        let response = serde_json::from_str(&buffer[..n])?;
+        let payload = read_frame(&mut self.socket, MAX_RESPONSE)?
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        let response = unframe(&payload)?;
         Ok(response)
     }
 
@@ -54,16 +92,40 @@ impl Client {
         Err(ClientError::Unexpected(response))
     }
 
+    /// Convert Connection into Stream of Daemon Events
+    pub fn subscribe(mut self) -> Result<Events, ClientError> {
+        let response = self.send(Request::Subscribe)?;
+        match response {
+            Response::Ok => Ok(Events {
+                socket: self.socket,
+            }),
+            response => Err(ClientError::Unexpected(response)),
+        }
+    }
+
     #[inline]
     pub fn ping(&mut self) -> Result<(), ClientError> {
         self.send_ok(Request::Ping)
     }
 
+    #[inline]
+    pub fn hello(&mut self, readonly: bool, namespace: Option<String>) -> Result<(), ClientError> {
+        self.send_ok(Request::Hello {
+            readonly,
+            namespace,
+        })
+    }
+
     #[inline]
     pub fn stop(&mut self) -> Result<(), ClientError> {
         self.send_ok(Request::Stop)
     }
 
+    #[inline]
+    pub fn trace(&mut self, enabled: bool) -> Result<(), ClientError> {
+        self.send_ok(Request::Trace { enabled })
+    }
+
     #[inline]
     pub fn clear(&mut self) -> Result<(), ClientError> {
         self.send_ok(Request::Clear)
@@ -74,6 +136,25 @@ impl Client {
         self.send_ok(Request::Wipe { wipe, group })
     }
 
+    #[inline]
+    pub fn pin(&mut self, index: usize, pinned: bool, group: Grp) -> Result<(), ClientError> {
+        self.send_ok(Request::Pin {
+            index,
+            pinned,
+            group,
+        })
+    }
+
+    #[inline]
+    pub fn import(&mut self, group: String, entries: Vec<Entry>) -> Result<(), ClientError> {
+        self.send_ok(Request::Import { group, entries })
+    }
+
+    #[inline]
+    pub fn configure(&mut self, group: String, settings: Settings) -> Result<(), ClientError> {
+        self.send_ok(Request::Configure { group, settings })
+    }
+
     #[inline]
     pub fn copy(
         &mut self,
@@ -82,23 +163,140 @@ impl Client {
         group: Grp,
         index: Idx,
     ) -> Result<(), ClientError> {
-        self.send_ok(Request::Copy {
-            entry,
+        let options = CopyOptions {
             primary,
             group,
             index,
+            ..Default::default()
+        };
+        self.copy_with(entry, options)
+    }
+
+    /// Copy Entry with every Setting of the Copy Request
+    pub fn copy_with(&mut self, entry: Entry, options: CopyOptions) -> Result<(), ClientError> {
+        self.send_ok(Request::Copy {
+            entry,
+            primary: options.primary,
+            group: options.group,
+            index: options.index,
+            force: options.force,
+            create: options.create,
+            revision: options.revision,
         })
     }
 
+    /// Copy into Index Replacing any Existing Record (Subject to `index_overwrite`)
+    ///
+    /// When a revision is given the daemon answers with `Response::Conflict`
+    /// if the record was replaced or deleted since that revision.
+    pub fn overwrite(
+        &mut self,
+        entry: Entry,
+        primary: bool,
+        group: Grp,
+        index: usize,
+        revision: Option<u64>,
+    ) -> Result<(), ClientError> {
+        let options = CopyOptions {
+            primary,
+            group,
+            index: Some(index),
+            force: true,
+            revision,
+            ..Default::default()
+        };
+        self.copy_with(entry, options)
+    }
+
     #[inline]
-    pub fn select(&mut self, index: usize, primary: bool, group: Grp) -> Result<(), ClientError> {
+    pub fn select(
+        &mut self,
+        index: usize,
+        primary: bool,
+        group: Grp,
+        no_touch: bool,
+    ) -> Result<(), ClientError> {
         self.send_ok(Request::Select {
             index,
             primary,
             group,
+            no_touch,
+        })
+    }
+
+    pub fn select_match(
+        &mut self,
+        query: String,
+        first: bool,
+        primary: bool,
+        group: Grp,
+        no_touch: bool,
+    ) -> Result<(), ClientError> {
+        self.send_ok(Request::Match {
+            query,
+            first,
+            primary,
+            group,
+            no_touch,
         })
     }
 
+    pub fn status(&mut self) -> Result<Status, ClientError> {
+        let response = self.send(Request::Status)?;
+        if let Response::Status { status } = response {
+            return Ok(status);
+        }
+        Err(ClientError::Unexpected(response))
+    }
+
+    /// Retrieve (Clipboard, Primary) Selection Owners
+    pub fn active(&mut self) -> Result<(Owner, Owner), ClientError> {
+        let response = self.send(Request::Active)?;
+        if let Response::Active {
+            clipboard, primary, ..
+        } = response
+        {
+            return Ok((clipboard, primary));
+        }
+        Err(ClientError::Unexpected(response))
+    }
+
+    /// Retrieve Groups Receiving Clipboard Copies (Live Groups then Terminal Group)
+    pub fn copy_groups(&mut self) -> Result<Vec<String>, ClientError> {
+        let response = self.send(Request::Active)?;
+        if let Response::Active { groups, .. } = response {
+            return Ok(groups);
+        }
+        Err(ClientError::Unexpected(response))
+    }
+
+    /// Aggregate History Statistics across Groups (Default Group if Empty)
+    pub fn report(&mut self, groups: Vec<String>) -> Result<HistoryReport, ClientError> {
+        let response = self.send(Request::Report { groups })?;
+        if let Response::Report { report } = response {
+            return Ok(report);
+        }
+        Err(ClientError::Unexpected(response))
+    }
+
+    /// Search Text Entries across Groups (Default Group if Empty)
+    pub fn search(
+        &mut self,
+        query: String,
+        groups: Vec<String>,
+        length: usize,
+    ) -> Result<Vec<GroupMatches>, ClientError> {
+        let response = self.send(Request::Search {
+            query,
+            groups,
+            length,
+        })?;
+        if let Response::Matches { matches } = response {
+            return Ok(matches);
+        }
+        Err(ClientError::Unexpected(response))
+    }
+
     pub fn groups(&mut self) -> Result<Vec<String>, ClientError> {
         let response = self.send(Request::Groups)?;
         if let Response::Groups { groups } = response {
@@ -107,23 +305,116 @@ impl Client {
         Err(ClientError::Unexpected(response))
     }
 
+    /// Retrieve Display Labels and Colors for Every Group
+    pub fn labels(&mut self) -> Result<Vec<GroupLabel>, ClientError> {
+        let response = self.send(Request::Labels)?;
+        if let Response::Labels { labels } = response {
+            return Ok(labels);
+        }
+        Err(ClientError::Unexpected(response))
+    }
+
+    /// Retrieve Entry Counts, Sizes and Recency for Every Group
+    pub fn group_stats(&mut self) -> Result<Vec<GroupStats>, ClientError> {
+        let response = self.send(Request::GroupStats)?;
+        if let Response::GroupStats { stats } = response {
+            return Ok(stats);
+        }
+        Err(ClientError::Unexpected(response))
+    }
+
     pub fn find(
         &mut self,
         index: Option<usize>,
         group: Grp,
-    ) -> Result<(Entry, usize), ClientError> {
+    ) -> Result<(Entry, usize, String, u64), ClientError> {
         let response = self.send(Request::Find { index, group })?;
-        if let Response::Entry { entry, index } = response {
-            return Ok((entry, index));
+        if let Response::Entry {
+            entry,
+            index,
+            group,
+            revision,
+        } = response
+        {
+            return Ok((*entry, index, group, revision));
         }
         Err(ClientError::Unexpected(response))
     }
 
     pub fn list(&mut self, length: usize, group: Grp) -> Result<Vec<Preview>, ClientError> {
-        let response = self.send(Request::List { length, group })?;
-        if let Response::Previews { previews } = response {
+        self.list_previews(length, group, false)
+    }
+
+    /// List Previews Letting the Group `preview_length` Override Length
+    pub fn list_preferred(
+        &mut self,
+        length: usize,
+        group: Grp,
+    ) -> Result<Vec<Preview>, ClientError> {
+        self.list_previews(length, group, true)
+    }
+
+    fn list_previews(
+        &mut self,
+        length: usize,
+        group: Grp,
+        prefer_group: bool,
+    ) -> Result<Vec<Preview>, ClientError> {
+        let response = self.send(Request::List {
+            length,
+            group,
+            prefer_group,
+            etag: None,
+        })?;
+        if let Response::Previews { previews, .. } = response {
             return Ok(previews);
         }
         Err(ClientError::Unexpected(response))
     }
+
+    /// Rebuild Stored Groups (All Groups if None)
+    pub fn reindex(&mut self, group: Grp) -> Result<Vec<Reindexed>, ClientError> {
+        let response = self.send(Request::Reindex { group })?;
+        if let Response::Reindexed { groups } = response {
+            return Ok(groups);
+        }
+        Err(ClientError::Unexpected(response))
+    }
+
+    /// List Records Changed since Sequence Number (Zero for Full List)
+    pub fn list_since(
+        &mut self,
+        seq: u64,
+        length: usize,
+        group: Grp,
+    ) -> Result<ListChanges, ClientError> {
+        let response = self.send(Request::ListSince { seq, length, group })?;
+        if let Response::Changes { changes } = response {
+            return Ok(changes);
+        }
+        Err(ClientError::Unexpected(response))
+    }
+
+    /// List Previews with their Etag (None if still Matching the Given Etag)
+    pub fn list_changed(
+        &mut self,
+        length: usize,
+        group: Grp,
+        etag: Option<String>,
+    ) -> Result<Option<(Vec<Preview>, String)>, ClientError> {
+        let response = self.send(Request::List {
+            length,
+            group,
+            prefer_group: false,
+            etag,
+        })?;
+        match response {
+            Response::Unchanged { .. } => Ok(None),
+            Response::Previews {
+                previews,
+                etag: Some(etag),
+            } => Ok(Some((previews, etag))),
+            response => Err(ClientError::Unexpected(response)),
+        }
+    }
 }
@@ -1,5 +1,8 @@
 //! Clipboard Objects and Tools
 
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::str::FromStr;
 use std::time::SystemTime;
 
 use serde::{Deserialize, Serialize};
@@ -7,6 +10,8 @@ use wayland_clipboard_listener::ClipBoardListenContext;
 use wayland_clipboard_listener::ClipBoardListenMessage;
 
 use crate::mime::*;
+use crate::secret::PASSWORD_HINT;
+use crate::urilist::{self, URI_LIST};
 
 /// Preview of Existing Clipboard Entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +19,103 @@ pub struct Preview {
     pub index: usize,
     pub preview: String,
     pub last_used: SystemTime,
+    /// Primary Mime-Type of Entry
+    #[serde(default)]
+    pub mime: String,
+    /// Expiration Time (for Duration-Based Group Expiration)
+    #[serde(default)]
+    pub expires: Option<SystemTime>,
+    /// Free-Text Note Attached to Entry
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Selection Entry was Captured From
+    #[serde(default)]
+    pub selection: Selection,
+    /// Hostname of the Machine the Entry was Captured On
+    #[serde(default)]
+    pub host: Option<String>,
+    /// Entry is Pinned (Survives Expiration and Clears)
+    #[serde(default)]
+    pub pinned: bool,
+    /// Masked Preview for Entries Tagged or Detected as Secrets
+    #[serde(default)]
+    pub masked: Option<String>,
+}
+
+impl Preview {
+    /// Preview Text (Masked for Secrets unless Revealed)
+    pub fn display(&self, reveal: bool) -> &str {
+        match (reveal, &self.masked) {
+            (false, Some(masked)) => masked,
+            _ => &self.preview,
+        }
+    }
+}
+
+/// Optional Columns Shown when Listing Previews
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PreviewColumn {
+    Expires,
+    Selection,
+    Host,
+}
+
+impl FromStr for PreviewColumn {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "expires" => Ok(Self::Expires),
+            "selection" => Ok(Self::Selection),
+            "host" => Ok(Self::Host),
+            _ => Err(format!("invalid column: {s:?}")),
+        }
+    }
+}
+
+/// Wayland Selection an Entry Belongs To
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Selection {
+    #[default]
+    Clipboard,
+    Primary,
+}
+
+impl Selection {
+    /// Check if Selection is the Regular Clipboard
+    #[inline]
+    pub fn is_clipboard(&self) -> bool {
+        *self == Self::Clipboard
+    }
+}
+
+impl From<bool> for Selection {
+    fn from(primary: bool) -> Self {
+        match primary {
+            true => Self::Primary,
+            false => Self::Clipboard,
+        }
+    }
+}
+
+impl FromStr for Selection {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clipboard" => Ok(Self::Clipboard),
+            "primary" => Ok(Self::Primary),
+            _ => Err(format!("invalid selection: {s:?}")),
+        }
+    }
+}
+
+impl Display for Selection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Clipboard => write!(f, "clipboard"),
+            Self::Primary => write!(f, "primary"),
+        }
+    }
 }
 
 /// DataTypes for Clipboard Entry
@@ -61,11 +163,90 @@ impl ClipBody {
     }
 }
 
+/// Content Statistics for a Clipboard Entry
+#[derive(Debug, Clone)]
+pub struct Stats {
+    pub bytes: usize,
+    pub chars: Option<usize>,
+    pub words: Option<usize>,
+    pub lines: Option<usize>,
+    pub entropy: f64,
+}
+
+/// Calculate Shannon-Entropy in Bits per Byte
+fn entropy(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let mut counts = [0usize; 256];
+    for byte in data {
+        counts[*byte as usize] += 1;
+    }
+    let total = data.len() as f64;
+    counts
+        .iter()
+        .filter(|c| **c > 0)
+        .map(|c| {
+            let p = *c as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Shell Session an Entry was Copied From
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Source {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub term: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tty: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+}
+
+impl Source {
+    /// Describe the Session of the Current Process
+    pub fn current() -> Self {
+        // stdin is often a pipe when copying, so check every standard stream
+        let tty = [0, 1, 2].into_iter().find_map(|fd| {
+            let path = std::fs::read_link(format!("/proc/self/fd/{fd}")).ok()?;
+            let path = path.to_string_lossy().into_owned();
+            (path.starts_with("/dev/pts/") || path.starts_with("/dev/tty")).then_some(path)
+        });
+        Self {
+            cwd: std::env::current_dir()
+                .ok()
+                .map(|p| p.to_string_lossy().into_owned()),
+            term: std::env::var("TERM").ok(),
+            tty,
+            hostname: Some(crate::hostname()),
+        }
+    }
+}
+
 /// Single Record Stored in Clipboard History
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Entry {
     pub mime: Vec<String>,
     pub body: ClipBody,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Selection::is_clipboard")]
+    pub selection: Selection,
+    /// Legacy Charset the Text was Converted From on Capture
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub charset: Option<String>,
+    /// Shell Session of Copies made with `copy --source`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    /// Entry was Tagged as a Secret with `copy --secret`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub secret: bool,
+    /// MimeTypes Offered by the Source Application (Live Captures Only)
+    #[serde(skip)]
+    pub offered: Vec<String>,
 }
 
 /// calculate text-mimes
@@ -112,6 +293,12 @@ impl Entry {
         Self {
             mime: text_mimes(mime),
             body: ClipBody::Text(content),
+            note: None,
+            selection: Selection::Clipboard,
+            charset: None,
+            source: None,
+            secret: false,
+            offered: vec![],
         }
     }
     /// Generate new Data Clipboard Entry
@@ -127,6 +314,58 @@ impl Entry {
         Self {
             mime: mimes,
             body: ClipBody::Data(content.to_vec()),
+            note: None,
+            selection: Selection::Clipboard,
+            charset: None,
+            source: None,
+            secret: false,
+            offered: vec![],
+        }
+    }
+    /// Attach Free-Text Note to Entry
+    pub fn with_note(mut self, note: Option<String>) -> Self {
+        self.note = note;
+        self
+    }
+    /// Tag Entry as a Secret (Masked in Previews)
+    pub fn with_secret(mut self, secret: bool) -> Self {
+        self.secret = secret;
+        self
+    }
+    /// Attach Shell Session the Entry was Copied From
+    pub fn with_source(mut self, source: Option<Source>) -> Self {
+        self.source = source;
+        self
+    }
+    /// Tag Entry with the Selection it Belongs To
+    pub fn with_selection(mut self, selection: Selection) -> Self {
+        self.selection = selection;
+        self
+    }
+    /// Target Offering Text in the Legacy Charset it was Captured From
+    pub fn charset_target(&self) -> Option<String> {
+        let charset = self.charset.as_ref().filter(|_| self.is_text())?;
+        Some(format!("text/plain;charset={charset}"))
+    }
+    /// Offer Captured Legacy Charset as an Alternate Target
+    pub fn with_charset_target(mut self) -> Self {
+        if let Some(target) = self.charset_target() {
+            if !self.mime.contains(&target) {
+                self.mime.push(target);
+            }
+        }
+        self
+    }
+    /// Bytes Served for Target (Text is Re-Encoded for its Charset Target)
+    pub fn target_bytes(&self, mime: &str) -> Cow<'_, [u8]> {
+        let target = self.charset_target().filter(|t| t == mime);
+        let encoded = match (&self.body, self.charset.as_deref(), target) {
+            (ClipBody::Text(text), Some(charset), Some(_)) => encode_charset(text, charset),
+            _ => None,
+        };
+        match encoded {
+            Some(bytes) => Cow::Owned(bytes),
+            None => Cow::Borrowed(self.as_bytes()),
         }
     }
     /// Check if Clipboard Body is Empty
@@ -156,34 +395,112 @@ impl Entry {
             .map(|s| s.to_owned())
             .unwrap_or_else(|| "N/A".to_owned())
     }
-    /// Generate Content Preview
-    pub fn preview(&self, max_width: usize) -> String {
+    /// Decode Contents as Text (Latin-1 Detected, Invalid Bytes Replaced)
+    pub fn to_text(&self) -> String {
+        match &self.body {
+            ClipBody::Text(text) => text.to_owned(),
+            ClipBody::Data(data) => decode_text(data, &self.mime),
+        }
+    }
+    /// Calculate Content Statistics (Text Counts Only Apply to Text)
+    pub fn stats(&self) -> Stats {
+        let bytes = self.as_bytes();
+        let text = self.is_text().then(|| self.to_text());
+        Stats {
+            bytes: bytes.len(),
+            chars: text.as_ref().map(|t| t.chars().count()),
+            words: text.as_ref().map(|t| t.split_whitespace().count()),
+            lines: text.as_ref().map(|t| t.lines().count()),
+            entropy: entropy(bytes),
+        }
+    }
+    /// Generate Whitespace-Normalized Preview Text (Untruncated)
+    fn preview_text(&self) -> String {
         let mut s = match &self.body {
+            _ if urilist::is_uri_list(&self.mime) => urilist::names(&self.to_text()).join(" "),
             ClipBody::Text(text) => text.to_owned(),
             ClipBody::Data(data) => preview_data(data, &self.mime),
         };
         if s.chars().all(char::is_whitespace) {
             s = format!("{s:?}");
         }
-        let mut s = s
-            .trim()
-            .split_whitespace()
+        s.split_whitespace()
             .filter(|s| !s.is_empty())
             .collect::<Vec<&str>>()
-            .join(" ");
-        if s.len() > max_width {
-            let max = std::cmp::max(max_width, 3);
-            s.truncate(max - 3);
-            s = format!("{s}...");
-        }
-        s
+            .join(" ")
+    }
+    /// Generate Content Preview
+    pub fn preview(&self, max_width: usize) -> String {
+        truncate_preview(self.preview_text(), max_width)
+    }
+    /// Generate Preview Cache Holding up to `size` Bytes
+    pub fn preview_cache(&self, size: usize) -> PreviewCache {
+        let mut text = self.preview_text();
+        let cut = text.len() > size;
+        text.truncate(floor_char_boundary(&text, size));
+        PreviewCache { text, cut }
+    }
+}
+
+/// Largest Char Boundary at or Below Index
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    match index >= s.len() {
+        true => s.len(),
+        false => (0..=index)
+            .rev()
+            .find(|i| s.is_char_boundary(*i))
+            .unwrap_or(0),
+    }
+}
+
+/// Truncate Normalized Preview Text to Maximum Width
+fn truncate_preview(mut s: String, max_width: usize) -> String {
+    if s.len() > max_width {
+        let max = std::cmp::max(max_width, 3);
+        s.truncate(floor_char_boundary(&s, max - 3));
+        s = format!("{s}...");
+    }
+    s
+}
+
+/// Normalized Preview Text Computed Once when a Record is Stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewCache {
+    text: String,
+    /// Text was Cut Short of the Full Preview
+    cut: bool,
+}
+
+impl PreviewCache {
+    /// Render Preview from Cache (None if Cache is too Short for Width)
+    pub fn render(&self, max_width: usize) -> Option<String> {
+        (!self.cut || max_width < self.text.len())
+            .then(|| truncate_preview(self.text.clone(), max_width))
     }
 }
 
 impl From<ClipBoardListenMessage> for Entry {
     fn from(value: ClipBoardListenMessage) -> Self {
-        let mime = if value.mime_types.iter().all(|m| is_text(m)) {
-            text_mimes(None)
+        let text = value.mime_types.iter().all(|m| is_text(m));
+        // convert text offered only in a legacy charset into utf-8
+        let legacy = legacy_charset(&value.mime_types)
+            .filter(|_| text)
+            .and_then(|m| charset(m))
+            .map(str::to_owned);
+        let (body, charset) = match (ClipBody::from(value.context), legacy) {
+            (ClipBody::Data(data), Some(label)) => match decode_charset(&data, &label) {
+                Some(text) => (ClipBody::Text(text), Some(label)),
+                None => (ClipBody::Data(data), None),
+            },
+            (body, _) => (body, None),
+        };
+        // password managers flag copied secrets with a hint target
+        let secret = value.mime_types.iter().any(|m| m == PASSWORD_HINT);
+        let offered = value.mime_types.clone();
+        let mime = if text {
+            // keep uri-list so copied files are still recognized
+            let uris = urilist::is_uri_list(&value.mime_types);
+            text_mimes(uris.then(|| URI_LIST.to_owned()))
         } else if value.mime_types.iter().any(|m| is_image(m)) {
             image_mimes(None)
         } else {
@@ -191,25 +508,64 @@ impl From<ClipBoardListenMessage> for Entry {
         };
         Self {
             mime,
-            body: ClipBody::from(value.context),
+            body,
+            note: None,
+            selection: Selection::Clipboard,
+            charset,
+            source: None,
+            secret,
+            offered,
         }
     }
 }
 
+/// Base64 Text for Human-Readable Formats (JSON) and Raw Bytes Otherwise
 mod base64_serial {
+    use std::fmt;
+
     use base64::prelude::{Engine as _, BASE64_STANDARD};
-    use serde::{Deserialize, Serialize};
+    use serde::de::{Error, SeqAccess, Visitor};
+    use serde::Serialize;
     use serde::{Deserializer, Serializer};
 
     pub fn serialize<S: Serializer>(v: &Vec<u8>, s: S) -> Result<S::Ok, S::Error> {
+        if !s.is_human_readable() {
+            return s.serialize_bytes(v);
+        }
         let b64 = BASE64_STANDARD.encode(v);
         String::serialize(&b64, s)
     }
 
+    /// Accepts Base64 Text or Raw Bytes
+    ///
+    /// Internally tagged enums buffer their fields and always claim to be
+    /// human-readable, so the representation is chosen by what is found.
+    struct BodyVisitor;
+
+    impl<'de> Visitor<'de> for BodyVisitor {
+        type Value = Vec<u8>;
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("base64 string or bytes")
+        }
+        fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+            BASE64_STANDARD.decode(v.as_bytes()).map_err(E::custom)
+        }
+        fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            Ok(v.to_vec())
+        }
+        fn visit_byte_buf<E: Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+            Ok(v)
+        }
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut data = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+            while let Some(byte) = seq.next_element()? {
+                data.push(byte);
+            }
+            Ok(data)
+        }
+    }
+
     pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
-        let b64 = String::deserialize(d)?;
-        BASE64_STANDARD
-            .decode(b64.as_bytes())
-            .map_err(|e| serde::de::Error::custom(e))
+        d.deserialize_any(BodyVisitor)
     }
 }
@@ -0,0 +1,410 @@
+//! FUSE Filesystem Exposing Clipboard History
+//!
+//! Groups are presented as directories and entries as files named
+//! `{index}.{ext}`. Files written into a group directory are copied
+//! into that group once closed, and removing a file deletes the entry.
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use fuser::{
+    FileAttr, FileType, Filesystem, MountOption, ReplyAttr, ReplyCreate, ReplyData, ReplyDirectory,
+    ReplyEmpty, ReplyEntry, ReplyOpen, ReplyWrite, Request, TimeOrNow,
+};
+
+use crate::client::{Client, CopyOptions};
+use crate::clipboard::{Entry, Preview};
+use crate::message::Wipe;
+use crate::mime::guess_mime_path;
+
+/// Attribute/Content Cache Lifetime
+const TTL: Duration = Duration::from_secs(1);
+
+/// Inode of Mount Root
+const ROOT: u64 = 1;
+
+/// Filesystem Node Kinds
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Node {
+    Group(String),
+    Entry(String, usize),
+    Upload(String, String),
+}
+
+/// File Being Written into a Group
+struct Upload {
+    ino: u64,
+    group: String,
+    name: String,
+    data: Vec<u8>,
+}
+
+/// Guess File Extension from Mime-Type
+fn extension(mime: &str) -> &str {
+    match mime.split(';').next().unwrap_or(mime) {
+        "text/plain" | "TEXT" | "STRING" | "UTF8_STRING" => "txt",
+        "image/jpeg" => "jpg",
+        "image/svg+xml" => "svg",
+        mime => mime
+            .split('/')
+            .nth(1)
+            .map(|s| s.split('+').next().unwrap_or(s))
+            .unwrap_or("bin"),
+    }
+}
+
+/// Filename for Entry Preview
+fn filename(preview: &Preview) -> String {
+    format!("{}.{}", preview.index, extension(&preview.mime))
+}
+
+/// Clipboard History Filesystem backed by Daemon Client
+pub struct ClipFs {
+    client: Client,
+    uid: u32,
+    gid: u32,
+    next_ino: u64,
+    next_fh: u64,
+    inodes: HashMap<Node, u64>,
+    nodes: HashMap<u64, Node>,
+    cache: HashMap<u64, (Instant, Vec<u8>)>,
+    uploads: HashMap<u64, Upload>,
+}
+
+impl ClipFs {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            uid: unsafe { libc::getuid() },
+            gid: unsafe { libc::getgid() },
+            next_ino: ROOT + 1,
+            next_fh: 1,
+            inodes: HashMap::new(),
+            nodes: HashMap::new(),
+            cache: HashMap::new(),
+            uploads: HashMap::new(),
+        }
+    }
+    /// Retrieve or Allocate Inode for Node
+    fn ino(&mut self, node: Node) -> u64 {
+        if let Some(ino) = self.inodes.get(&node) {
+            return *ino;
+        }
+        let ino = self.next_ino;
+        self.next_ino += 1;
+        self.inodes.insert(node.clone(), ino);
+        self.nodes.insert(ino, node);
+        ino
+    }
+    /// Build File Attributes for Inode
+    fn attr(&self, ino: u64, kind: FileType, size: u64) -> FileAttr {
+        let now = SystemTime::now();
+        let perm = match kind {
+            FileType::Directory => 0o755,
+            _ => 0o644,
+        };
+        FileAttr {
+            ino,
+            size,
+            blocks: size.div_ceil(512),
+            atime: now,
+            mtime: now,
+            ctime: now,
+            crtime: UNIX_EPOCH,
+            kind,
+            perm,
+            nlink: 1,
+            uid: self.uid,
+            gid: self.gid,
+            rdev: 0,
+            blksize: 512,
+            flags: 0,
+        }
+    }
+    /// List Available Groups
+    fn groups(&mut self) -> Vec<String> {
+        self.client.groups().unwrap_or_default()
+    }
+    /// List (Inode, Filename) of Entries within Group
+    fn entries(&mut self, group: &str) -> Vec<(u64, String)> {
+        let previews = self.client.list(0, Some(group.to_owned()));
+        previews
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| {
+                let name = filename(&p);
+                (self.ino(Node::Entry(group.to_owned(), p.index)), name)
+            })
+            .collect()
+    }
+    /// Retrieve Content of Entry Inode (Cached Briefly)
+    fn content(&mut self, ino: u64) -> Option<Vec<u8>> {
+        if let Some((fetched, data)) = self.cache.get(&ino) {
+            if fetched.elapsed() < TTL {
+                return Some(data.clone());
+            }
+        }
+        let Some(Node::Entry(group, index)) = self.nodes.get(&ino).cloned() else {
+            return None;
+        };
+        let (entry, ..) = self.client.find(Some(index), Some(group)).ok()?;
+        let data = entry.as_bytes().to_vec();
+        self.cache.insert(ino, (Instant::now(), data.clone()));
+        Some(data)
+    }
+    /// Calculate Attributes for Inode (if it Exists)
+    fn getattr_ino(&mut self, ino: u64) -> Option<FileAttr> {
+        if ino == ROOT {
+            return Some(self.attr(ino, FileType::Directory, 0));
+        }
+        match self.nodes.get(&ino)? {
+            Node::Group(_) => Some(self.attr(ino, FileType::Directory, 0)),
+            Node::Entry(..) => {
+                let size = self.content(ino)?.len() as u64;
+                Some(self.attr(ino, FileType::RegularFile, size))
+            }
+            Node::Upload(..) => {
+                let upload = self.uploads.values().find(|u| u.ino == ino)?;
+                let size = upload.data.len() as u64;
+                Some(self.attr(ino, FileType::RegularFile, size))
+            }
+        }
+    }
+}
+
+impl Filesystem for ClipFs {
+    fn lookup(&mut self, _req: &Request<'_>, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        let name = name.to_string_lossy().to_string();
+        let ino = match (parent, self.nodes.get(&parent).cloned()) {
+            (ROOT, _) => match self.groups().contains(&name) {
+                true => Some(self.ino(Node::Group(name))),
+                false => None,
+            },
+            (_, Some(Node::Group(group))) => {
+                let upload = Node::Upload(group.clone(), name.clone());
+                match self.inodes.get(&upload) {
+                    Some(ino) if self.uploads.values().any(|u| u.ino == *ino) => Some(*ino),
+                    _ => self
+                        .entries(&group)
+                        .into_iter()
+                        .find(|(_, n)| n == &name)
+                        .map(|(ino, _)| ino),
+                }
+            }
+            _ => None,
+        };
+        match ino.and_then(|ino| self.getattr_ino(ino)) {
+            Some(attr) => reply.entry(&TTL, &attr, 0),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn getattr(&mut self, _req: &Request<'_>, ino: u64, _fh: Option<u64>, reply: ReplyAttr) {
+        match self.getattr_ino(ino) {
+            Some(attr) => reply.attr(&TTL, &attr),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn setattr(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _mode: Option<u32>,
+        _uid: Option<u32>,
+        _gid: Option<u32>,
+        size: Option<u64>,
+        _atime: Option<TimeOrNow>,
+        _mtime: Option<TimeOrNow>,
+        _ctime: Option<SystemTime>,
+        _fh: Option<u64>,
+        _crtime: Option<SystemTime>,
+        _chgtime: Option<SystemTime>,
+        _bkuptime: Option<SystemTime>,
+        _flags: Option<u32>,
+        reply: ReplyAttr,
+    ) {
+        // only pending uploads may be truncated
+        if let Some(size) = size {
+            match self.uploads.values_mut().find(|u| u.ino == ino) {
+                Some(upload) => upload.data.resize(size as usize, 0),
+                None => return reply.error(libc::EACCES),
+            }
+        }
+        match self.getattr_ino(ino) {
+            Some(attr) => reply.attr(&TTL, &attr),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn unlink(&mut self, _req: &Request<'_>, parent: u64, name: &OsStr, reply: ReplyEmpty) {
+        let Some(Node::Group(group)) = self.nodes.get(&parent).cloned() else {
+            return reply.error(libc::ENOENT);
+        };
+        let name = name.to_string_lossy();
+        let index = name.split('.').next().and_then(|i| i.parse().ok());
+        let Some(index) = index else {
+            return reply.error(libc::ENOENT);
+        };
+        match self.client.wipe(Wipe::Single { index }, Some(group)) {
+            Ok(()) => reply.ok(),
+            Err(_) => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn open(&mut self, _req: &Request<'_>, _ino: u64, flags: i32, reply: ReplyOpen) {
+        // existing entries are read-only. new entries are added by creating files
+        match flags & libc::O_ACCMODE {
+            libc::O_RDONLY => reply.opened(0, 0),
+            _ => reply.error(libc::EACCES),
+        }
+    }
+
+    fn read(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        size: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyData,
+    ) {
+        let Some(data) = self.content(ino) else {
+            return reply.error(libc::ENOENT);
+        };
+        let start = (offset as usize).min(data.len());
+        let end = (start + size as usize).min(data.len());
+        reply.data(&data[start..end]);
+    }
+
+    fn write(
+        &mut self,
+        _req: &Request<'_>,
+        _ino: u64,
+        fh: u64,
+        offset: i64,
+        data: &[u8],
+        _write_flags: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyWrite,
+    ) {
+        let Some(upload) = self.uploads.get_mut(&fh) else {
+            return reply.error(libc::EBADF);
+        };
+        let offset = offset as usize;
+        if upload.data.len() < offset + data.len() {
+            upload.data.resize(offset + data.len(), 0);
+        }
+        upload.data[offset..offset + data.len()].copy_from_slice(data);
+        reply.written(data.len() as u32);
+    }
+
+    fn release(
+        &mut self,
+        _req: &Request<'_>,
+        _ino: u64,
+        fh: u64,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        _flush: bool,
+        reply: ReplyEmpty,
+    ) {
+        let Some(upload) = self.uploads.remove(&fh) else {
+            return reply.ok();
+        };
+        if upload.data.is_empty() {
+            return reply.ok();
+        }
+        let mime = guess_mime_path(&PathBuf::from(&upload.name));
+        let entry = Entry::data(&upload.data, Some(mime));
+        let options = CopyOptions {
+            group: Some(upload.group),
+            create: true,
+            ..Default::default()
+        };
+        match self.client.copy_with(entry, options) {
+            Ok(()) => reply.ok(),
+            Err(err) => {
+                log::error!("failed to copy {:?}: {err:?}", upload.name);
+                reply.error(libc::EIO)
+            }
+        }
+    }
+
+    fn readdir(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        mut reply: ReplyDirectory,
+    ) {
+        let children: Vec<(u64, FileType, String)> = match self.nodes.get(&ino).cloned() {
+            _ if ino == ROOT => self
+                .groups()
+                .into_iter()
+                .map(|g| (self.ino(Node::Group(g.clone())), FileType::Directory, g))
+                .collect(),
+            Some(Node::Group(group)) => self
+                .entries(&group)
+                .into_iter()
+                .map(|(ino, name)| (ino, FileType::RegularFile, name))
+                .collect(),
+            _ => return reply.error(libc::ENOTDIR),
+        };
+        let entries = [
+            (ino, FileType::Directory, ".".to_owned()),
+            (ROOT, FileType::Directory, "..".to_owned()),
+        ];
+        let all = entries.into_iter().chain(children);
+        for (i, (ino, kind, name)) in all.enumerate().skip(offset as usize) {
+            if reply.add(ino, (i + 1) as i64, kind, name) {
+                break;
+            }
+        }
+        reply.ok();
+    }
+
+    fn create(
+        &mut self,
+        _req: &Request<'_>,
+        parent: u64,
+        name: &OsStr,
+        _mode: u32,
+        _umask: u32,
+        _flags: i32,
+        reply: ReplyCreate,
+    ) {
+        let Some(Node::Group(group)) = self.nodes.get(&parent).cloned() else {
+            return reply.error(libc::EACCES);
+        };
+        let name = name.to_string_lossy().to_string();
+        let ino = self.ino(Node::Upload(group.clone(), name.clone()));
+        let fh = self.next_fh;
+        self.next_fh += 1;
+        let upload = Upload {
+            ino,
+            group,
+            name,
+            data: vec![],
+        };
+        self.uploads.insert(fh, upload);
+        let attr = self.attr(ino, FileType::RegularFile, 0);
+        reply.created(&TTL, &attr, 0, fh, 0);
+    }
+}
+
+/// Mount Clipboard History Filesystem (Blocks until Unmounted)
+pub fn mount(client: Client, path: &Path) -> io::Result<()> {
+    let options = [
+        MountOption::FSName("wclipd".to_owned()),
+        MountOption::DefaultPermissions,
+    ];
+    fuser::mount2(ClipFs::new(client), path, &options)
+}
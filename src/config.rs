@@ -1,45 +1,344 @@
 //! Configuration for WClipD
+use std::collections::HashMap;
 use std::str::FromStr;
 
 use serde::{de::Error, Deserialize};
 
-use crate::backend::{BackendConfig, Expiration, Storage};
+use crate::backend::{BackendConfig, Dedupe, Expiration, GroupColor, LargeEntryPolicy};
+use crate::clipboard::Entry;
+use crate::http::HttpConfig;
 use crate::message::Grp;
+use crate::mime;
+use crate::prompt::PromptTrimmer;
+use crate::secret::PASSWORD_HINT;
+use crate::sync::SyncConfig;
 use crate::table::{Align, Style};
+use crate::urilist::UriListConfig;
+use crate::urlclean::UrlCleaner;
 
 fn _true() -> bool {
     true
 }
 
+fn _max_request() -> usize {
+    64 * 1024 * 1024
+}
+
+fn _rate_limit() -> u32 {
+    200
+}
+
+fn _slow_request() -> u64 {
+    250
+}
+
+fn _copy_threads() -> usize {
+    16
+}
+
+fn _echo_window() -> u64 {
+    2000
+}
+
+fn _list_cache() -> Option<usize> {
+    Some(1000)
+}
+
+fn _deny_mimes() -> Vec<String> {
+    vec![PASSWORD_HINT.to_owned()]
+}
+
+fn _trash() -> String {
+    "trash".to_owned()
+}
+
+/// File Mode Creation Mask (Parsed as Octal)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Umask(pub u32);
+
+impl FromStr for Umask {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u32::from_str_radix(s.trim_start_matches("0o"), 8)
+            .map(Self)
+            .map_err(|_| format!("invalid umask: {s:?}"))
+    }
+}
+
+impl Default for Umask {
+    fn default() -> Self {
+        Self(0o027)
+    }
+}
+
+fn _workdir() -> String {
+    "/".to_owned()
+}
+
+/// Settings Applied when Forking the Daemon into the Background
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackgroundConfig {
+    #[serde(default = "_workdir")]
+    pub workdir: String,
+    #[serde(default)]
+    pub umask: Umask,
+    #[serde(default)]
+    pub log_file: Option<String>,
+    #[serde(default)]
+    pub pid_file: Option<String>,
+}
+
+impl Default for BackgroundConfig {
+    fn default() -> Self {
+        Self {
+            workdir: _workdir(),
+            umask: Umask::default(),
+            log_file: None,
+            pid_file: None,
+        }
+    }
+}
+
+/// Dedicated Socket Granted to a Sandboxed Application
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AppConfig {
+    /// Socket Path (Defaults to `apps/<app>.sock` beside the Daemon Socket)
+    #[serde(default)]
+    pub socket: Option<String>,
+    /// Group Namespace (Defaults to the App Name)
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub readonly: bool,
+    /// Requests per Second Shared by All App Connections
+    #[serde(default)]
+    pub max_requests_per_sec: Option<u32>,
+}
+
+/// Size-Based Routing of Live Entries between Groups
+///
+/// Small text entries go to `small_backend` (e.g. memory storage) while
+/// larger or binary entries go to `large_backend` (e.g. disk storage).
+#[derive(Debug, Clone, Deserialize)]
+pub struct SizeRouting {
+    /// Largest Text Entry (in Bytes) Routed to the Small Group
+    pub max_small_size: usize,
+    pub small_backend: String,
+    pub large_backend: String,
+}
+
+impl SizeRouting {
+    /// Select Group Name for Live Entry
+    pub fn route(&self, entry: &Entry) -> &str {
+        match entry.is_text() && entry.as_bytes().len() <= self.max_small_size {
+            true => &self.small_backend,
+            false => &self.large_backend,
+        }
+    }
+}
+
+/// Handling of Copies into an Index that is Already Used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverwriteMode {
+    /// Replace the Existing Record
+    Replace,
+    /// Refuse to Replace the Existing Record unless Forced
+    #[default]
+    Refuse,
+    /// Move the Existing Record into the Trash Group First
+    Trash,
+}
+
+/// Reaction to Other Clipboard Managers Running beside the Daemon
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TakeoverPolicy {
+    /// Log a Warning and Continue
+    #[default]
+    Warn,
+    /// Refuse to Start
+    Refuse,
+    /// Re-Offer every Capture Immediately to Keep Selection Ownership
+    Claim,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DaemonConfig {
     #[serde(skip)]
     pub kill: bool,
+    #[serde(default)]
+    pub background: BackgroundConfig,
+    #[serde(default)]
+    pub headless: bool,
     #[serde(default = "_true")]
     pub capture_live: bool,
     #[serde(default)]
-    pub recopy_live: bool,
+    pub capture_primary: bool,
+    #[serde(default, deserialize_with = "de_recopy")]
+    pub recopy_live: Option<RecopyConfig>,
+    #[serde(default)]
+    pub dedupe_live: bool,
+    #[serde(default)]
+    pub other_managers: TakeoverPolicy,
+    #[serde(default)]
+    pub notify_errors: bool,
+    #[serde(default)]
+    pub gpaste_dbus: bool,
+    #[serde(default)]
+    pub http: Option<HttpConfig>,
+    #[serde(default)]
+    pub sync: Option<SyncConfig>,
+    #[serde(default)]
+    pub clean_urls: UrlCleaner,
+    #[serde(default)]
+    pub trim_prompts: PromptTrimmer,
+    /// Never Store Live Entries Offering these MimeTypes (Exact or `prefix/*`)
+    #[serde(default = "_deny_mimes")]
+    pub deny_mimes: Vec<String>,
+    /// Never Store Live Entries Copied while these Apps are Focused (Case-Insensitive)
+    #[serde(default)]
+    pub ignore_apps: Vec<String>,
+    #[serde(default)]
+    pub uri_list: UriListConfig,
+    #[serde(default)]
+    pub readonly_socket: Option<String>,
+    /// Save Memory Groups here on Shutdown and Reload them on Startup
+    #[serde(default)]
+    pub memory_snapshot: Option<String>,
+    #[serde(default)]
+    pub apps: HashMap<String, AppConfig>,
+    #[serde(default = "_max_request")]
+    pub max_request_size: usize,
+    #[serde(default = "_rate_limit")]
+    pub max_requests_per_sec: u32,
+    #[serde(default = "_slow_request")]
+    pub slow_request_ms: u64,
+    #[serde(default = "_copy_threads")]
+    pub max_copy_threads: usize,
+    /// Largest Entry (in Bytes) Offered on the Primary Selection
+    #[serde(default)]
+    pub max_primary_size: Option<usize>,
+    #[serde(default = "_echo_window")]
+    pub echo_window_ms: u64,
+    /// Cache Previews of Groups with at least this many Records (None Disables)
+    #[serde(default = "_list_cache")]
+    pub list_cache_threshold: Option<usize>,
     #[serde(default)]
     pub backends: BackendConfig,
     #[serde(default)]
+    pub aliases: HashMap<String, String>,
+    #[serde(default)]
+    pub index_overwrite: OverwriteMode,
+    #[serde(default = "_trash")]
+    pub trash_backend: String,
+    #[serde(default)]
     pub term_backend: Grp,
     #[serde(default)]
     pub live_backend: Grp,
+    #[serde(default)]
+    pub primary_backend: Grp,
+    /// Route Live Entries by Size instead of using `live_backend`
+    #[serde(default)]
+    pub size_routing: Option<SizeRouting>,
 }
 
 impl Default for DaemonConfig {
     fn default() -> Self {
         Self {
             kill: false,
+            background: BackgroundConfig::default(),
+            headless: false,
             capture_live: true,
-            recopy_live: true,
+            capture_primary: false,
+            recopy_live: Some(RecopyConfig::default()),
+            dedupe_live: false,
+            other_managers: TakeoverPolicy::default(),
+            notify_errors: false,
+            gpaste_dbus: false,
+            http: None,
+            sync: None,
+            clean_urls: UrlCleaner::default(),
+            trim_prompts: PromptTrimmer::default(),
+            deny_mimes: _deny_mimes(),
+            ignore_apps: vec![],
+            uri_list: UriListConfig::default(),
+            readonly_socket: None,
+            memory_snapshot: None,
+            apps: HashMap::new(),
+            max_request_size: _max_request(),
+            max_requests_per_sec: _rate_limit(),
+            slow_request_ms: _slow_request(),
+            max_copy_threads: _copy_threads(),
+            max_primary_size: None,
+            echo_window_ms: _echo_window(),
+            list_cache_threshold: _list_cache(),
             backends: BackendConfig::new(),
+            aliases: HashMap::new(),
+            index_overwrite: OverwriteMode::default(),
+            trash_backend: _trash(),
             term_backend: None,
             live_backend: None,
+            primary_backend: None,
+            size_routing: None,
         }
     }
 }
 
+/// When Captured Entries are Re-Offered by the Daemon
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecopyMode {
+    /// Re-Offer Immediately after Capture
+    #[default]
+    Always,
+    /// Re-Offer once the Selection is Cleared (Owner Exited)
+    OnExit,
+}
+
+/// Conditions for Re-Copying Live Entries
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RecopyConfig {
+    pub mode: RecopyMode,
+    pub mimes: Vec<String>,
+    pub max_size: Option<usize>,
+}
+
+impl RecopyConfig {
+    /// Check if Captured Entry Qualifies for Re-Copy
+    pub fn allows(&self, entry: &Entry) -> bool {
+        let mimes = self.mimes.is_empty()
+            || self
+                .mimes
+                .iter()
+                .any(|pattern| entry.mime.iter().any(|mime| mime::matches(pattern, mime)));
+        let size = self
+            .max_size
+            .map(|max| entry.as_bytes().len() <= max)
+            .unwrap_or(true);
+        mimes && size
+    }
+}
+
+/// Recopy Specified as Toggle or Conditions
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RecopySpec {
+    Toggle(bool),
+    Conditions(RecopyConfig),
+}
+
+fn de_recopy<'de, D>(deserializer: D) -> Result<Option<RecopyConfig>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match RecopySpec::deserialize(deserializer)? {
+        RecopySpec::Toggle(enabled) => enabled.then(RecopyConfig::default),
+        RecopySpec::Conditions(config) => Some(config),
+    })
+}
+
 #[inline]
 fn _align() -> Align {
     Align::Right
@@ -72,6 +371,61 @@ fn _preview() -> usize {
     60
 }
 
+/// When to Append a Trailing Newline on Paste
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Newline {
+    Always,
+    Never,
+    #[default]
+    Tty,
+}
+
+impl FromStr for Newline {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            "tty" => Ok(Self::Tty),
+            _ => Err(format!("invalid newline mode: {s:?}")),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CopyConfig {
+    /// Record cwd, $TERM, tty and Hostname with every CLI Copy
+    #[serde(default)]
+    pub record_source: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PasteConfig {
+    #[serde(default)]
+    pub newline: Newline,
+}
+
+fn _menu() -> String {
+    "fuzzel --dmenu".to_owned()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PickConfig {
+    #[serde(default = "_menu")]
+    pub menu: String,
+    #[serde(default = "_true")]
+    pub calculator: bool,
+}
+
+impl Default for PickConfig {
+    fn default() -> Self {
+        Self {
+            menu: _menu(),
+            calculator: true,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ListConfig {
     #[serde(default)]
@@ -92,13 +446,53 @@ impl Default for ListConfig {
     }
 }
 
+fn _level() -> String {
+    "info".to_owned()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogConfig {
+    #[serde(default = "_level")]
+    pub level: String,
+    #[serde(default)]
+    pub filters: HashMap<String, String>,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: _level(),
+            filters: HashMap::new(),
+        }
+    }
+}
+
+impl LogConfig {
+    /// Render Settings as `env_logger` Filter Directives
+    pub fn directives(&self) -> String {
+        let mut directives = vec![self.level.to_owned()];
+        directives.extend(self.filters.iter().map(|(m, l)| format!("{m}={l}")));
+        directives.join(",")
+    }
+}
+
 #[derive(Debug, Default, Deserialize)]
 pub struct Config {
     #[serde(default)]
     pub socket: Option<String>,
     #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub log: LogConfig,
+    #[serde(default)]
     pub list: ListConfig,
     #[serde(default)]
+    pub copy: CopyConfig,
+    #[serde(default)]
+    pub paste: PasteConfig,
+    #[serde(default)]
+    pub pick: PickConfig,
+    #[serde(default)]
     pub daemon: DaemonConfig,
 }
 
@@ -119,5 +513,9 @@ macro_rules! de_fromstr {
 // implement `Deserialize` using `FromStr`
 de_fromstr!(Style);
 de_fromstr!(Align);
-de_fromstr!(Storage);
 de_fromstr!(Expiration);
+de_fromstr!(Dedupe);
+de_fromstr!(LargeEntryPolicy);
+de_fromstr!(GroupColor);
+de_fromstr!(Newline);
+de_fromstr!(Umask);
@@ -1,31 +1,141 @@
 ///! Clipboard Daemon Implementation
-use std::fs::remove_file;
-use std::io::{BufRead, BufReader, Write};
-use std::os::unix::net::{UnixListener, UnixStream};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fs::{remove_file, File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::path::PathBuf;
-use std::sync::{Arc, Barrier, RwLock};
-use std::thread;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
 
 use thiserror::Error;
-use wayland_clipboard_listener::WlClipboardCopyStream;
-use wayland_clipboard_listener::{WlClipboardListenerError, WlClipboardPasteStream, WlListenType};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::unix::OwnedWriteHalf;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, watch, Mutex, Notify};
+use tokio::task::{self, JoinHandle as TaskHandle, JoinSet};
+use wayland_clipboard_listener::WlClipboardListenerError;
 
-use crate::backend::{Backend, BackendGroup, Manager, Record};
+use crate::backend::{
+    Backend, BackendGroup, GroupConfig, LargeEntryPolicy, Manager, Record, Snapshot,
+};
 use crate::client::Client;
-use crate::clipboard::Entry;
-use crate::config::DaemonConfig;
+use crate::clipboard::{ClipBody, Entry, Preview, Selection};
+use crate::config::{
+    AppConfig, DaemonConfig, OverwriteMode, RecopyConfig, RecopyMode, SizeRouting, TakeoverPolicy,
+};
+use crate::focus;
+use crate::fuzzy;
+use crate::gpaste;
+use crate::http::{self, HttpConfig};
 use crate::message::*;
+use crate::mime;
+use crate::notify::notify_error;
+use crate::prompt::PromptTrimmer;
+use crate::provider::{ClipboardProvider, InMemory, Listener, Wayland};
+use crate::report::HistoryReport;
+use crate::script::Hook;
+use crate::secret;
+use crate::spill;
+use crate::sync::{record_id, SyncConfig, SyncDir};
+use crate::urilist::UriListConfig;
+use crate::urlclean::UrlCleaner;
 
-fn copy(entry: Entry, primary: bool) -> Result<(), DaemonError> {
-    let mut stream = WlClipboardCopyStream::init()?;
-    thread::spawn(move || {
-        let mimes = entry.mime.iter().map(|s| s.as_str()).collect();
-        let context = entry.body.as_bytes().to_vec();
-        stream
-            .copy_to_clipboard(context, mimes, primary)
-            .expect("clipboard copy failed");
-    });
-    Ok(())
+/// Initial and Maximum Delay between Watcher Restarts
+static WATCH_BACKOFF_MIN: Duration = Duration::from_secs(1);
+static WATCH_BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+/// Consecutive Listener Errors before the Watcher is Restarted
+static WATCH_MAX_ERRORS: usize = 5;
+
+/// Live Captures Logged Individually per Window before Coalescing
+static CAPTURE_LOG_BURST: usize = 10;
+static CAPTURE_LOG_WINDOW: Duration = Duration::from_secs(10);
+
+/// Process Names of Known Wayland Clipboard Managers
+static CLIPBOARD_MANAGERS: &[&str] = &[
+    "clipcat",
+    "clipman",
+    "clipse",
+    "cliphist",
+    "copyq",
+    "gpaste-daemon",
+    "klipper",
+    "wl-clip-persist",
+];
+
+/// Time Open Connections are Given to Finish on Shutdown
+static SHUTDOWN_GRACE: Duration = Duration::from_secs(1);
+
+/// Interval between Deleting Spilled Files no Record References
+static SPILL_SWEEP_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Reserved Copy-Serving Slot Released when Task Ends
+struct CopySlot(Arc<AtomicUsize>);
+
+impl Drop for CopySlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Bounded Pool of Blocking Tasks Serving Copied Clipboard Data
+#[derive(Clone)]
+struct CopyPool {
+    provider: Arc<dyn ClipboardProvider>,
+    max: usize,
+    max_primary: Option<usize>,
+    active: Arc<AtomicUsize>,
+    served: Arc<AtomicU64>,
+    rejected: Arc<AtomicU64>,
+}
+
+impl CopyPool {
+    fn new(max: usize, max_primary: Option<usize>) -> Self {
+        Self {
+            provider: Arc::new(Wayland),
+            max,
+            max_primary,
+            active: Arc::new(AtomicUsize::new(0)),
+            served: Arc::new(AtomicU64::new(0)),
+            rejected: Arc::new(AtomicU64::new(0)),
+        }
+    }
+    /// Serve Entry on Clipboard (Rejected when Pool is Exhausted)
+    ///
+    /// Returns false when the entry is too large to offer on primary.
+    fn copy(&self, entry: Entry, primary: bool) -> Result<bool, DaemonError> {
+        let size = entry.as_bytes().len();
+        if let Some(max) = self.max_primary.filter(|max| primary && size > *max) {
+            log::info!("not offering {size} byte entry on primary (max {max} bytes)");
+            return Ok(false);
+        }
+        let slot = CopySlot(Arc::clone(&self.active));
+        if self.active.fetch_add(1, Ordering::SeqCst) >= self.max {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(DaemonError::CopyLimit(self.max));
+        }
+        let offer = self.provider.offer()?;
+        let served = Arc::clone(&self.served);
+        task::spawn_blocking(move || {
+            let _slot = slot;
+            // compositor restarts end the copy. the watcher re-offers it
+            match offer.serve(entry, primary) {
+                Ok(()) => {
+                    served.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(err) => log::warn!("clipboard copy ended: {err:?}"),
+            }
+        });
+        Ok(true)
+    }
 }
 
 #[derive(Debug, Error)]
@@ -36,64 +146,941 @@ pub enum DaemonError {
     SocketError(#[from] std::io::Error),
     #[error("Message Error")]
     MessageError(#[from] serde_json::Error),
+    #[error("Message Encode Error")]
+    EncodeError(#[from] rmp_serde::encode::Error),
+    #[error("Message Decode Error")]
+    DecodeError(#[from] rmp_serde::decode::Error),
     #[error("Clipboard Error")]
     ClipboardError(#[from] WlClipboardListenerError),
+    #[error("Too Many Active Clipboard Copies (max {0})")]
+    CopyLimit(usize),
+    #[error("Startup Check Failed ({0})")]
+    HealthCheck(String),
+    #[error("Request Task Failed")]
+    TaskError(#[from] task::JoinError),
+}
+
+/// Find Clipboard Managers Run by the Current User as `(name, pid)`
+///
+/// `wl-paste --watch` pipelines (used by cliphist and clipman) are
+/// reported as well since they capture every selection.
+pub fn other_managers() -> Vec<(String, u32)> {
+    use std::os::unix::fs::MetadataExt;
+    let Ok(procs) = std::fs::read_dir("/proc") else {
+        return vec![];
+    };
+    let uid = unsafe { libc::geteuid() };
+    let mut found = BTreeSet::new();
+    for proc in procs.filter_map(Result::ok) {
+        let name = proc.file_name();
+        let Some(pid) = name.to_str().and_then(|p| p.parse::<u32>().ok()) else {
+            continue;
+        };
+        let owned = proc.metadata().map(|m| m.uid() == uid).unwrap_or(false);
+        if pid == std::process::id() || !owned {
+            continue;
+        }
+        let path = proc.path();
+        let comm = std::fs::read_to_string(path.join("comm")).unwrap_or_default();
+        let comm = comm.trim();
+        let cmdline = std::fs::read(path.join("cmdline")).unwrap_or_default();
+        let args: Vec<_> = cmdline
+            .split(|b| *b == 0)
+            .map(String::from_utf8_lossy)
+            .collect();
+        let watching = comm == "wl-paste" && args.iter().any(|a| a == "--watch" || a == "-w");
+        if CLIPBOARD_MANAGERS.contains(&comm) || watching {
+            found.insert((comm.to_owned(), pid));
+        }
+    }
+    found.into_iter().collect()
+}
+
+/// Single-Instance Lock and PID File Held for the Daemon Lifetime
+struct InstanceLock {
+    _file: File,
+    pid_path: PathBuf,
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        let _ = remove_file(&self.pid_path);
+    }
+}
+
+/// Per-Connection Request Rate Limiter
+struct RateLimit {
+    max: u32,
+    count: u32,
+    window: Instant,
+}
+
+impl RateLimit {
+    fn new(max: u32) -> Self {
+        Self {
+            max,
+            count: 0,
+            window: Instant::now(),
+        }
+    }
+    /// Wait Until Another Request is Permitted (Zero Disables Limit)
+    async fn wait(&mut self) {
+        let second = Duration::from_secs(1);
+        if self.max == 0 {
+            return;
+        }
+        if self.window.elapsed() >= second {
+            self.window = Instant::now();
+            self.count = 0;
+        }
+        self.count += 1;
+        if self.count > self.max {
+            log::warn!("client exceeded {} requests/sec. throttling", self.max);
+            tokio::time::sleep(second.saturating_sub(self.window.elapsed())).await;
+            self.window = Instant::now();
+            self.count = 1;
+        }
+    }
+}
+
+/// Coalesces Capture Logs when an App Sets the Clipboard in a Tight Loop
+struct CaptureLog {
+    window: Instant,
+    count: usize,
+    groups: BTreeSet<String>,
+}
+
+impl CaptureLog {
+    fn new() -> Self {
+        Self {
+            window: Instant::now(),
+            count: 0,
+            groups: BTreeSet::new(),
+        }
+    }
+    /// Summarize Suppressed Captures once the Window has Passed
+    fn flush(&mut self) {
+        if self.window.elapsed() < CAPTURE_LOG_WINDOW {
+            return;
+        }
+        if self.count > CAPTURE_LOG_BURST {
+            let groups: Vec<_> = self.groups.iter().map(String::as_str).collect();
+            log::info!(
+                "captured {} live entries in {}s (groups={})",
+                self.count,
+                self.window.elapsed().as_secs(),
+                groups.join(",")
+            );
+        }
+        *self = Self::new();
+    }
+    /// Record Capture and Check if it should be Logged Individually
+    fn record(&mut self, group: &str) -> bool {
+        self.flush();
+        self.count += 1;
+        self.groups.insert(group.to_owned());
+        if self.count == CAPTURE_LOG_BURST + 1 {
+            log::info!("live captures are flooding. coalescing logs");
+        }
+        self.count <= CAPTURE_LOG_BURST
+    }
+}
+
+/// Cached Previews of a Large Group (Served while Refreshing)
+struct ListCache {
+    previews: Vec<Preview>,
+    etag: String,
+    refreshing: bool,
+}
+
+/// Cached Previews by Group Name and Preview Length
+type ListCaches = HashMap<(String, usize), ListCache>;
+
+/// Lock File with `flock` (False if Non-Blocking and Already Locked)
+fn flock(file: &File, mode: libc::c_int) -> std::io::Result<bool> {
+    if unsafe { libc::flock(file.as_raw_fd(), mode) } == 0 {
+        return Ok(true);
+    }
+    let err = std::io::Error::last_os_error();
+    match err.kind() == std::io::ErrorKind::WouldBlock {
+        true => Ok(false),
+        false => Err(err),
+    }
+}
+
+/// Etag Identifying a List of Previews
+fn etag(previews: &[Preview]) -> String {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_vec(previews)
+        .expect("preview serialization failed")
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Read Next Request Payload and its Size (Oversized Frames are Left Unread)
+async fn read_request<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    framed: bool,
+    max: usize,
+) -> std::io::Result<Option<(Vec<u8>, usize)>> {
+    if !framed {
+        let mut buffer = vec![];
+        let n = reader
+            .take(max as u64 + 1)
+            .read_until(b'\n', &mut buffer)
+            .await?;
+        return Ok((n > 0).then_some((buffer, n)));
+    }
+    let mut header = [0u8; FRAME_HEADER];
+    match reader.read_exact(&mut header).await {
+        Ok(_) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+    let size = u32::from_be_bytes(header) as usize;
+    if size > max {
+        return Ok(Some((vec![], size)));
+    }
+    let mut payload = vec![0; size];
+    reader.read_exact(&mut payload).await?;
+    Ok(Some((payload, size)))
+}
+
+/// Encode Response as a Frame or a (Legacy) JSON Line
+fn encode(response: &Response, framed: bool) -> Result<Vec<u8>, DaemonError> {
+    if framed {
+        return Ok(frame(response)?);
+    }
+    let mut content = serde_json::to_vec(response)?;
+    content.push(b'\n');
+    Ok(content)
+}
+
+/// Number of Record Changes Kept for Incremental Listing
+static JOURNAL_SIZE: usize = 4096;
+
+/// Change to a Group Record
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Change {
+    Added(usize),
+    Touched(usize),
+    Removed(usize),
+    /// Group Cleared or Reconfigured (Clients must Reload)
+    Reset,
+}
+
+/// Bounded Log of Record Changes by Sequence Number
+struct Journal {
+    seq: u64,
+    changes: VecDeque<(u64, String, Change)>,
+}
+
+impl Journal {
+    /// Start Sequence at the Current Time so Numbers Keep Growing across Restarts
+    fn new() -> Self {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        Self {
+            seq: now.as_micros() as u64,
+            changes: VecDeque::new(),
+        }
+    }
+    /// Record Change and Return its Sequence Number
+    fn record(&mut self, group: &str, change: Change) -> u64 {
+        self.seq += 1;
+        if self.changes.len() >= JOURNAL_SIZE {
+            self.changes.pop_front();
+        }
+        self.changes.push_back((self.seq, group.to_owned(), change));
+        self.seq
+    }
+    /// Collect (Added, Touched, Removed) Indexes since Sequence (None if not Covered)
+    fn since(&self, seq: u64, group: &str) -> Option<[BTreeSet<usize>; 3]> {
+        let oldest = self
+            .changes
+            .front()
+            .map(|(s, _, _)| *s)
+            .unwrap_or(self.seq + 1);
+        if seq > self.seq || seq + 1 < oldest {
+            return None;
+        }
+        let [mut added, mut touched, mut removed] = [(); 3].map(|_| BTreeSet::new());
+        let changes = self
+            .changes
+            .iter()
+            .filter(|(s, name, _)| *s > seq && name == group);
+        for (_, _, change) in changes {
+            match *change {
+                Change::Added(index) => {
+                    touched.remove(&index);
+                    removed.remove(&index);
+                    added.insert(index);
+                }
+                Change::Touched(index) if !added.contains(&index) => {
+                    touched.insert(index);
+                }
+                Change::Touched(_) => {}
+                Change::Removed(index) => {
+                    added.remove(&index);
+                    touched.remove(&index);
+                    removed.insert(index);
+                }
+                Change::Reset => return None,
+            }
+        }
+        Some([added, touched, removed])
+    }
 }
 
-/// Shared Internal State between Threads
+/// Shared Internal State between Tasks
 struct Shared {
-    pub ignore: Option<Entry>,
+    pub ignore: Option<(u64, Instant)>,
+    pub echo_window: Duration,
+    pub dedupe: bool,
     pub backend: Box<dyn Backend>,
     pub term_group: Grp,
     pub live_group: Grp,
+    pub primary_group: Grp,
+    pub trash_group: Grp,
+    pub routing: Option<SizeRouting>,
+    pub overwrite: OverwriteMode,
+    capture_log: CaptureLog,
+    pub clipboard_owner: Owner,
+    pub primary_owner: Owner,
+    pub health: Vec<Diagnostic>,
+    pub captures: Vec<(String, GroupConfig)>,
+    pub hooks: HashMap<String, Hook>,
+    pub cleaner: UrlCleaner,
+    pub prompts: PromptTrimmer,
+    pub deny_mimes: Vec<String>,
+    pub uri_list: UriListConfig,
+    pub aliases: HashMap<String, String>,
 }
 
 impl Shared {
-    pub fn new(cfg: DaemonConfig) -> Self {
+    pub fn new(cfg: DaemonConfig, backend: Box<dyn Backend>) -> Self {
+        let hooks = cfg
+            .backends
+            .iter()
+            .filter_map(|(name, group)| Some((name, group.script.as_ref()?)))
+            .filter_map(|(name, script)| {
+                let path = PathBuf::from(shellexpand::tilde(&script.to_string_lossy()).as_ref());
+                match Hook::load(&path) {
+                    Ok(hook) => Some((name.to_owned(), hook)),
+                    Err(err) => {
+                        log::error!("failed to load script {path:?}: {err}");
+                        None
+                    }
+                }
+            })
+            .collect();
+        let mut captures: Vec<_> = cfg
+            .backends
+            .iter()
+            .filter(|(_, group)| !group.capture.is_empty())
+            .map(|(name, group)| (name.to_owned(), group.clone()))
+            .collect();
+        captures.sort_by(|a, b| a.0.cmp(&b.0));
+        let alias = |name: String| cfg.aliases.get(&name).cloned().unwrap_or(name);
         Self {
+            captures,
+            hooks,
+            cleaner: cfg.clean_urls,
+            prompts: cfg.trim_prompts,
+            deny_mimes: cfg.deny_mimes,
+            uri_list: cfg.uri_list,
             ignore: None,
-            backend: Box::new(Manager::new(cfg.backends)),
-            term_group: cfg.term_backend,
-            live_group: cfg.live_backend,
+            echo_window: Duration::from_millis(cfg.echo_window_ms),
+            dedupe: cfg.dedupe_live,
+            backend,
+            term_group: cfg.term_backend.map(alias),
+            live_group: cfg.live_backend.clone().map(alias),
+            primary_group: cfg.primary_backend.or(cfg.live_backend).map(alias),
+            trash_group: Some(alias(cfg.trash_backend)),
+            routing: cfg.size_routing.map(|r| SizeRouting {
+                small_backend: alias(r.small_backend),
+                large_backend: alias(r.large_backend),
+                ..r
+            }),
+            overwrite: cfg.index_overwrite,
+            capture_log: CaptureLog::new(),
+            clipboard_owner: Owner::Unknown,
+            primary_owner: Owner::Unknown,
+            health: vec![],
+            aliases: cfg.aliases,
+        }
+    }
+    /// Resolve Group Alias into Full Group Name
+    pub fn alias(&self, name: &mut String) {
+        if let Some(group) = self.aliases.get(name.as_str()) {
+            *name = group.to_owned();
+        }
+    }
+    /// Record Current Owner of the Selection (True if it Changed)
+    pub fn set_owner(&mut self, selection: Selection, owner: Owner) -> bool {
+        let current = match selection {
+            Selection::Clipboard => &mut self.clipboard_owner,
+            Selection::Primary => &mut self.primary_owner,
+        };
+        let changed = *current != owner;
+        *current = owner;
+        changed
+    }
+    /// Content Hash of Entry used for Echo Suppression
+    fn digest(entry: &Entry) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        entry.mime.hash(&mut hasher);
+        entry.as_bytes().hash(&mut hasher);
+        hasher.finish()
+    }
+    /// Suppress Watcher Echo of Entry Copied by the Daemon
+    pub fn ignore(&mut self, entry: &Entry) {
+        self.ignore = Some((Self::digest(entry), Instant::now()));
+    }
+    /// Check if Entry is an Echo of a Recent Daemon Copy
+    pub fn is_echo(&self, entry: &Entry) -> bool {
+        self.ignore
+            .map(|(hash, at)| at.elapsed() < self.echo_window && hash == Self::digest(entry))
+            .unwrap_or(false)
+    }
+    /// Find Record Matching Entry in any Other Writable Group
+    pub fn existing(&mut self, entry: &Entry, targets: &[Grp]) -> Option<(String, usize)> {
+        let targets: Vec<&str> = targets
+            .iter()
+            .map(|g| g.as_deref().unwrap_or("default"))
+            .collect();
+        for name in self.backend.groups() {
+            if targets.contains(&name.as_str()) || self.backend.readonly(Some(&name)) {
+                continue;
+            }
+            let dedupe = self.backend.dedupe(Some(&name));
+            if let Some(index) = self.backend.group(Some(&name)).exists(entry, dedupe) {
+                return Some((name, index));
+            }
+        }
+        None
+    }
+    /// Determine Groups Capturing Live Entry (Falls Back to Size Routing or Live Group)
+    pub fn capture_groups(&self, entry: &Entry) -> Vec<Grp> {
+        if !entry.selection.is_clipboard() {
+            return vec![self.primary_group.clone()];
+        }
+        let groups: Vec<Grp> = self
+            .captures
+            .iter()
+            .filter(|(_, group)| group.captures(&entry.mime))
+            .map(|(name, _)| Some(name.to_owned()))
+            .collect();
+        if !groups.is_empty() {
+            return groups;
+        }
+        match &self.routing {
+            Some(routing) => vec![Some(routing.route(entry).to_owned())],
+            None => vec![self.live_group.clone()],
+        }
+    }
+    /// Groups Receiving Clipboard Entries no Capture Group Claims (Small Group First)
+    pub fn live_groups(&self) -> Vec<Grp> {
+        match &self.routing {
+            Some(routing) => vec![
+                Some(routing.small_backend.clone()),
+                Some(routing.large_backend.clone()),
+            ],
+            None => vec![self.live_group.clone()],
+        }
+    }
+    /// Check if Live Entry Offers a Denied MimeType (e.g. Password Manager Hint)
+    pub fn is_denied(&self, entry: &Entry) -> bool {
+        entry
+            .offered
+            .iter()
+            .chain(entry.mime.iter())
+            .any(|m| self.deny_mimes.iter().any(|p| mime::matches(p, m)))
+    }
+    /// Apply Group `max_entry_size` to Entry before Storing it (None if Skipped)
+    pub fn limit_size(&mut self, group: Option<&str>, entry: Entry) -> Option<Entry> {
+        let Some((max, policy)) = self.backend.max_entry_size(group) else {
+            return Some(entry);
+        };
+        let size = entry.as_bytes().len();
+        if size <= max {
+            return Some(entry);
+        }
+        let name = group.unwrap_or("default");
+        match policy {
+            LargeEntryPolicy::Store => Some(entry),
+            LargeEntryPolicy::Skip => {
+                log::info!("skipped {size} byte entry over max_entry_size of group {name:?}");
+                None
+            }
+            // spilled files are plaintext, so encrypted groups never spill
+            LargeEntryPolicy::SpillToFile if self.backend.encrypted(group) => {
+                log::warn!("skipped {size} byte entry of encrypted group {name:?} (cannot spill)");
+                None
+            }
+            LargeEntryPolicy::SpillToFile => match spill::spill(name, &entry) {
+                Ok(entry) => Some(entry),
+                Err(err) => {
+                    log::error!("failed to spill large entry of group {name:?}: {err}");
+                    None
+                }
+            },
+        }
+    }
+    /// Check if Entry is too Large to be Stored in Group at All
+    pub fn is_skipped(&mut self, group: Option<&str>, entry: &Entry) -> bool {
+        let Some((max, policy)) = self.backend.max_entry_size(group) else {
+            return false;
+        };
+        let spills = policy == LargeEntryPolicy::SpillToFile && !self.backend.encrypted(group);
+        entry.as_bytes().len() > max && policy != LargeEntryPolicy::Store && !spills
+    }
+    /// Strip Tracking Parameters if Entry is a URL
+    pub fn clean_url(&self, mut entry: Entry) -> Entry {
+        if let ClipBody::Text(text) = &entry.body {
+            if let Some(url) = self.cleaner.clean(text).filter(|url| url != text.trim()) {
+                log::debug!("stripped tracking parameters from url");
+                entry.body = ClipBody::Text(url);
+            }
+        }
+        entry
+    }
+    /// Strip Shell Prompts if Entry is a Copied Command-Line Snippet
+    pub fn trim_prompt(&self, mut entry: Entry) -> Entry {
+        if let ClipBody::Text(text) = &entry.body {
+            if let Some(command) = self.prompts.trim(text).filter(|c| c != text) {
+                log::debug!("stripped shell prompt from snippet");
+                entry.body = ClipBody::Text(command);
+            }
+        }
+        entry
+    }
+    /// Apply Overwrite Policy before Replacing the Record at Index
+    pub fn overwrite(&mut self, group: Grp, index: usize, force: bool) -> Result<(), String> {
+        let Some(record) = self.group(group.clone()).get(&index) else {
+            return Ok(());
+        };
+        let name = group.unwrap_or_else(|| "default".to_owned());
+        match self.overwrite {
+            OverwriteMode::Replace => Ok(()),
+            OverwriteMode::Refuse if force => Ok(()),
+            OverwriteMode::Refuse => Err(format!(
+                "Index {index} is in use in Group {name:?} (use --force to replace)"
+            )),
+            OverwriteMode::Trash if self.trash_group.as_deref() == Some(name.as_str()) => Ok(()),
+            OverwriteMode::Trash => {
+                let trash = self.trash_group.clone();
+                let dedupe = self.backend.dedupe(trash.as_deref());
+                let moved = self.group(trash).push(record.entry, dedupe);
+                log::info!("moved replaced entry (group={name} index={index}) to trash ({moved})");
+                Ok(())
+            }
+        }
+    }
+    /// Run Capture Hook Configured for Group (if Any)
+    pub fn hook(&self, entry: Entry, group: Grp) -> Option<(Entry, Grp)> {
+        let name = group.as_deref().unwrap_or("default");
+        match self.hooks.get(name) {
+            Some(hook) => hook.process(entry, group),
+            None => Some((entry, group)),
         }
     }
     #[inline]
     pub fn group(&mut self, group: Grp) -> Box<dyn BackendGroup> {
         self.backend.group(group.as_deref())
     }
+    /// Build Group Previews with their Expiration Times
+    pub fn previews(&mut self, group: Grp, length: usize) -> Vec<Preview> {
+        let previews = self.group(group.clone()).preview(length);
+        self.expiring(group, previews)
+    }
+    /// Set Expiration Times of Unpinned Previews
+    pub fn expiring(&mut self, group: Grp, mut previews: Vec<Preview>) -> Vec<Preview> {
+        if let Some(lifetime) = self.backend.lifetime(group.as_deref()) {
+            for preview in previews.iter_mut().filter(|p| !p.pinned) {
+                preview.expires = Some(preview.last_used + lifetime);
+            }
+        }
+        previews
+    }
+}
+
+/// Handle to Daemon Running in a Background Thread
+pub struct DaemonHandle {
+    stop: Arc<Notify>,
+    events: broadcast::Sender<Event>,
+    thread: JoinHandle<Result<(), DaemonError>>,
+}
+
+impl DaemonHandle {
+    /// Request Daemon Shutdown
+    pub fn stop(&self) {
+        self.stop.notify_one();
+    }
+    /// Subscribe to Daemon Events
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.events.subscribe()
+    }
+    /// Wait for Daemon to Exit
+    pub fn join(self) -> Result<(), DaemonError> {
+        self.thread.join().expect("daemon thread panicked")
+    }
+}
+
+/// Restrictions Applied to Every Connection on a Socket
+#[derive(Clone, Default)]
+struct Access {
+    readonly: bool,
+    namespace: Option<String>,
+    /// Limiter Shared by All Connections (Per-Connection if None)
+    limit: Option<Arc<Mutex<RateLimit>>>,
+}
+
+impl Access {
+    fn readonly() -> Self {
+        Self {
+            readonly: true,
+            ..Default::default()
+        }
+    }
+    /// Access for Sandboxed App Socket (Confined to its Namespace)
+    fn app(name: &str, cfg: &AppConfig, rate_limit: u32) -> Self {
+        let limit = cfg.max_requests_per_sec.unwrap_or(rate_limit);
+        Self {
+            readonly: cfg.readonly,
+            namespace: Some(cfg.namespace.clone().unwrap_or_else(|| name.to_owned())),
+            limit: Some(Arc::new(Mutex::new(RateLimit::new(limit)))),
+        }
+    }
 }
 
 /// Clipboard Daemon Implementation
 pub struct Daemon {
     kill: bool,
+    headless: bool,
     live: bool,
-    recopy: bool,
+    primary: bool,
+    recopy: Option<RecopyConfig>,
+    takeover: TakeoverPolicy,
+    ignore_apps: Vec<String>,
+    notify: bool,
+    gpaste: bool,
+    pool: CopyPool,
+    restarts: Arc<AtomicU64>,
+    cancel: Arc<watch::Sender<bool>>,
+    http: Option<HttpConfig>,
+    sync: Option<SyncConfig>,
+    max_request: usize,
+    rate_limit: u32,
+    slow_request: Duration,
+    trace: Arc<AtomicBool>,
     addr: PathBuf,
+    readonly_addr: Option<PathBuf>,
+    snapshot: Option<PathBuf>,
+    apps: Vec<(PathBuf, Access)>,
+    started: SystemTime,
     shared: Arc<RwLock<Shared>>,
-    start_wg: Arc<Barrier>,
-    stop_wg: Arc<Barrier>,
+    list_threshold: Option<usize>,
+    lists: Arc<RwLock<ListCaches>>,
+    journal: Arc<RwLock<Journal>>,
+    stop: Arc<Notify>,
+    events: broadcast::Sender<Event>,
 }
 
 impl Daemon {
     /// Spawn New Clipboard Daemon
     pub fn new(path: PathBuf, cfg: DaemonConfig) -> Result<Self, DaemonError> {
-        let waiting = cfg.capture_live.then_some(3).unwrap_or(2);
+        let backend = Box::new(Manager::new(cfg.backends.clone()));
+        Self::with_backend(path, cfg, backend)
+    }
+
+    /// Replace Clipboard Provider Implementation (Wayland by Default)
+    pub fn with_provider(mut self, provider: Arc<dyn ClipboardProvider>) -> Self {
+        self.pool.provider = provider;
+        self
+    }
+
+    /// Spawn New Clipboard Daemon using the Specified Storage Backend
+    pub fn with_backend(
+        path: PathBuf,
+        cfg: DaemonConfig,
+        backend: Box<dyn Backend>,
+    ) -> Result<Self, DaemonError> {
+        let (events, _) = broadcast::channel(64);
+        let expand = |p: &str| PathBuf::from(shellexpand::tilde(p).to_string());
+        let apps_dir = path.with_file_name("apps");
+        let apps = cfg
+            .apps
+            .iter()
+            .map(|(name, app)| {
+                let addr = match app.socket.as_deref() {
+                    Some(socket) => expand(socket),
+                    None => apps_dir.join(format!("{name}.sock")),
+                };
+                (addr, Access::app(name, app, cfg.max_requests_per_sec))
+            })
+            .collect();
+        // headless daemons keep the selection in memory and never capture
+        let headless = cfg.headless;
+        let mut pool = CopyPool::new(cfg.max_copy_threads, cfg.max_primary_size);
+        if headless {
+            pool.provider = Arc::new(InMemory::new());
+        }
         Ok(Self {
             kill: cfg.kill,
-            live: cfg.capture_live,
-            recopy: cfg.recopy_live,
+            headless,
+            live: cfg.capture_live && !headless,
+            primary: cfg.capture_primary && !headless,
+            recopy: cfg.recopy_live.clone().filter(|_| !headless),
+            takeover: cfg.other_managers,
+            ignore_apps: cfg.ignore_apps.iter().map(|a| a.to_lowercase()).collect(),
+            notify: cfg.notify_errors,
+            gpaste: cfg.gpaste_dbus,
+            pool,
+            restarts: Arc::new(AtomicU64::new(0)),
+            cancel: Arc::new(watch::channel(false).0),
+            http: cfg.http.clone(),
+            sync: cfg.sync.clone(),
+            max_request: cfg.max_request_size,
+            rate_limit: cfg.max_requests_per_sec,
+            slow_request: Duration::from_millis(cfg.slow_request_ms),
+            trace: Arc::new(AtomicBool::new(false)),
             addr: path,
-            shared: Arc::new(RwLock::new(Shared::new(cfg))),
-            start_wg: Arc::new(Barrier::new(waiting)),
-            stop_wg: Arc::new(Barrier::new(2)),
+            readonly_addr: cfg.readonly_socket.as_deref().map(expand),
+            snapshot: cfg.memory_snapshot.as_deref().map(expand),
+            apps,
+            started: SystemTime::now(),
+            list_threshold: cfg.list_cache_threshold,
+            lists: Arc::new(RwLock::new(HashMap::new())),
+            journal: Arc::new(RwLock::new(Journal::new())),
+            shared: Arc::new(RwLock::new(Shared::new(cfg, backend))),
+            stop: Arc::new(Notify::new()),
+            events,
         })
     }
 
+    /// Collect Daemon Status Details
+    pub fn status(&self) -> Status {
+        Status {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            protocol: PROTOCOL_VERSION,
+            pid: std::process::id(),
+            started: self.started,
+            capture_live: self.live,
+            headless: self.headless,
+            copies_active: self.pool.active.load(Ordering::Relaxed),
+            copies_served: self.pool.served.load(Ordering::Relaxed),
+            copies_rejected: self.pool.rejected.load(Ordering::Relaxed),
+            watcher_restarts: self.restarts.load(Ordering::Relaxed),
+            health: self
+                .shared
+                .read()
+                .expect("rwlock read failed")
+                .health
+                .clone(),
+        }
+    }
+
+    /// Verify Runtime Requirements before Spawning Services
+    ///
+    /// Missing data-control support disables live capture, while an
+    /// unwritable runtime directory or unopenable store is fatal. Headless
+    /// daemons skip the compositor check entirely.
+    fn health_check(&mut self) -> Result<(), DaemonError> {
+        let runtime = self
+            .addr
+            .parent()
+            .ok_or_else(|| "socket path has no parent directory".to_owned())
+            .and_then(|dir| {
+                let probe = dir.join(".wclipd-check");
+                std::fs::write(&probe, b"")
+                    .and_then(|_| remove_file(&probe))
+                    .map_err(|err| format!("runtime dir {dir:?} not writable: {err}"))
+            });
+        let control = match self.headless {
+            true => Ok(()),
+            false => self
+                .pool
+                .provider
+                .listen(Selection::Clipboard)
+                .map(|_| ())
+                .map_err(|err| {
+                    format!("compositor lacks data-control protocol support ({err:?}). live capture disabled")
+                }),
+        };
+        let managers = match self.headless {
+            true => Ok(()),
+            false => self.check_managers(),
+        };
+        let mut shared = self.shared.write().expect("rwlock write failed");
+        let store = shared.backend.check();
+        shared.health = vec![
+            Diagnostic::new("runtime_dir", runtime.clone()),
+            Diagnostic::new("storage", store.clone()),
+        ];
+        if !self.headless {
+            shared
+                .health
+                .insert(1, Diagnostic::new("data_control", control.clone()));
+        }
+        if let Err(err) = control {
+            self.report_error(err);
+            self.live = false;
+            self.primary = false;
+        }
+        let fatal = match self.takeover {
+            TakeoverPolicy::Refuse => managers.clone(),
+            _ => Ok(()),
+        };
+        if !self.headless {
+            // conflicts are kept as details of a passing check unless fatal
+            shared.health.push(Diagnostic {
+                ok: fatal.is_ok(),
+                ..Diagnostic::new("clipboard_managers", managers)
+            });
+        }
+        runtime
+            .and(store)
+            .and(fatal)
+            .map_err(DaemonError::HealthCheck)
+    }
+
+    /// Detect Other Clipboard Managers and Apply the Takeover Policy
+    fn check_managers(&mut self) -> Result<(), String> {
+        let managers: Vec<_> = other_managers()
+            .into_iter()
+            .map(|(name, pid)| format!("{name} (pid {pid})"))
+            .collect();
+        if managers.is_empty() {
+            return Ok(());
+        }
+        let found = format!("other clipboard managers running: {}", managers.join(", "));
+        match self.takeover {
+            TakeoverPolicy::Warn => log::warn!("{found}. entries may be captured twice"),
+            TakeoverPolicy::Refuse => {}
+            TakeoverPolicy::Claim => {
+                log::warn!("{found}. claiming selection ownership");
+                self.recopy = Some(RecopyConfig::default()).filter(|_| self.live || self.primary);
+            }
+        }
+        Err(found)
+    }
+
+    /// Acquire Single-Instance Lock and Write PID File
+    fn lock_instance(&self) -> Result<InstanceLock, DaemonError> {
+        let lock_path = self.addr.with_extension("lock");
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)?;
+        if !flock(&file, libc::LOCK_EX | libc::LOCK_NB)? {
+            if !self.kill {
+                return Err(DaemonError::AlreadyRunning);
+            }
+            log::warn!("daemon already running. killing it");
+            if let Err(err) = Client::new(self.addr.clone()).and_then(|mut c| c.stop()) {
+                log::warn!("failed to stop existing daemon: {err:?}");
+            }
+            flock(&file, libc::LOCK_EX)?;
+        }
+        let pid_path = self.addr.with_extension("pid");
+        std::fs::write(&pid_path, std::process::id().to_string())?;
+        Ok(InstanceLock {
+            _file: file,
+            pid_path,
+        })
+    }
+
+    /// Subscribe to Daemon Events
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.events.subscribe()
+    }
+
+    /// Publish Event to Subscribers (Ignored if None are Listening)
+    fn publish(&self, event: Event) {
+        match &event {
+            Event::Copied { group, index, .. } => self.changed(group, Change::Added(*index)),
+            Event::Deleted {
+                group,
+                index: Some(index),
+            } => self.changed(group, Change::Removed(*index)),
+            Event::Deleted { group, index: None } => self.changed(group, Change::Reset),
+            Event::Owned { .. } | Event::Stopped => {}
+        }
+        let _ = self.events.send(event);
+    }
+
+    /// Publish Copy of an Existing Record (Journaled as a Touch)
+    fn publish_touched(&self, event: Event) {
+        if let Event::Copied { group, index, .. } = &event {
+            self.changed(group, Change::Touched(*index));
+        }
+        let _ = self.events.send(event);
+    }
+
+    /// Publish Deletion of Records Removed by Group Cleanup
+    fn publish_evicted(&self, shared: &mut Shared) {
+        for (group, index) in shared.backend.evicted() {
+            log::debug!("cleaned up entry (group={group} index={index})");
+            self.publish(Event::Deleted {
+                group,
+                index: Some(index),
+            });
+        }
+    }
+
+    /// Journal Group Change and Drop its Cached Previews
+    fn changed(&self, group: &str, change: Change) {
+        self.journal
+            .write()
+            .expect("rwlock write failed")
+            .record(group, change);
+        let mut lists = self.lists.write().expect("rwlock write failed");
+        lists.retain(|(name, _), _| name != group);
+    }
+
+    /// Rebuild Cached Group Previews in a Background Task
+    fn refresh_list(&self, group: Grp, length: usize) {
+        let daemon = self.clone();
+        task::spawn_blocking(move || {
+            let name = group.clone().unwrap_or_else(|| "default".to_owned());
+            let previews = daemon
+                .shared
+                .write()
+                .expect("rwlock write failed")
+                .previews(group, length);
+            let etag = etag(&previews);
+            let mut lists = daemon.lists.write().expect("rwlock write failed");
+            // skip caches invalidated (or replaced) while refreshing
+            if let Some(cache) = lists.get_mut(&(name, length)).filter(|c| c.refreshing) {
+                *cache = ListCache {
+                    previews,
+                    etag,
+                    refreshing: false,
+                };
+            }
+        });
+    }
+
+    /// Log Recoverable Error and Raise Notification (if Enabled)
+    fn report_error(&self, message: String) {
+        log::error!("{message}");
+        if self.notify {
+            notify_error("wclipd daemon error", &message);
+        }
+    }
+
     /// Clear Active Clipboard
     pub fn clear(&self) -> Result<(), DaemonError> {
         let entry = Entry::text("".to_string(), None);
-        copy(entry.clone(), true)?;
-        copy(entry, false)
+        self.pool.copy(entry.clone(), true)?;
+        self.pool.copy(entry, false)?;
+        let mut shared = self.shared.write().expect("rwlock write failed");
+        self.set_owner(&mut shared, Selection::Clipboard, Owner::Unknown);
+        self.set_owner(&mut shared, Selection::Primary, Owner::Unknown);
+        Ok(())
     }
 
     /// Add Entry To Clipboard with Following Settings
@@ -105,35 +1092,234 @@ impl Daemon {
         index: Idx,
     ) -> Result<(), DaemonError> {
         // update ignore tracking for live-updates to avoid double-copy
+        let entry = entry.with_selection(Selection::from(primary));
         let mut shared = self.shared.write().expect("rwlock write failed");
-        shared.ignore = Some(entry.clone());
+        shared.ignore(&entry);
         // add entry to specified group
         let mime = entry.mime();
         let name = group.or(shared.term_group.clone());
+        let Some(stored) = shared.limit_size(name.as_deref(), entry.clone()) else {
+            // oversized entries are still copied, just never stored
+            self.pool.copy(entry, primary)?;
+            return Ok(());
+        };
+        let dedupe = shared.backend.dedupe(name.as_deref());
         let mut group = shared.group(name.clone());
+        let touched = index.is_none() && group.exists(&stored, dedupe).is_some();
         let index = match index {
             Some(idx) => {
-                group.insert(idx, Record::new(idx, entry.clone()));
+                let existing = group.get(&idx);
+                // start from the current time so a deleted and rewritten index never
+                // repeats a revision an editor may still hold
+                let now = SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_micros() as u64;
+                let revision = existing.as_ref().map_or(now, |r| now.max(r.revision + 1));
+                let record = Record {
+                    revision,
+                    pinned: existing.is_some_and(|r| r.pinned),
+                    ..Record::new(idx, stored)
+                };
+                group.insert(idx, record);
                 idx
             }
-            None => group.push(entry.clone()),
+            None => group.push(stored, dedupe),
         };
         // add to live clipboard
-        copy(entry, primary)?;
+        let selection = entry.selection;
+        let offered = self.pool.copy(entry, primary)?;
         // log entry
         let name = name.unwrap_or_else(|| "default".to_owned());
+        if offered {
+            let owner = Owner::Daemon {
+                group: name.clone(),
+                index,
+            };
+            self.set_owner(&mut shared, selection, owner);
+        }
         log::info!("copied term entry (group={name} index={index}) {mime:?}");
+        let event = Event::Copied {
+            group: name,
+            index,
+            mime,
+            live: false,
+        };
+        match touched {
+            true => self.publish_touched(event),
+            false => self.publish(event),
+        }
+        Ok(())
+    }
+
+    /// Resolve Group Aliases within Request
+    fn resolve_aliases(&self, mut message: Request) -> Request {
+        let shared = self.shared.read().expect("rwlock read failed");
+        match &mut message {
+            Request::Copy { group, .. }
+            | Request::Select { group, .. }
+            | Request::Match { group, .. }
+            | Request::List { group, .. }
+            | Request::ListSince { group, .. }
+            | Request::Find { group, .. }
+            | Request::Wipe { group, .. }
+            | Request::Pin { group, .. }
+            | Request::Reindex { group } => {
+                if let Some(name) = group.as_mut() {
+                    shared.alias(name);
+                }
+            }
+            Request::Configure { group, .. } | Request::Import { group, .. } => shared.alias(group),
+            Request::Report { groups } | Request::Search { groups, .. } => {
+                groups.iter_mut().for_each(|g| shared.alias(g))
+            }
+            _ => {}
+        }
+        message
+    }
+
+    /// Restrict Request to Client Namespace Groups (after Resolving Aliases)
+    fn scope_request(&self, request: Request, namespace: &str) -> Option<Request> {
+        let request = self.resolve_aliases(request);
+        let shared = self.shared.read().expect("rwlock read failed");
+        let default = shared.term_group.as_deref().unwrap_or("default");
+        request.scoped(namespace, default)
+    }
+
+    /// Focused App if Live Captures from it are Ignored (Only Queried when Configured)
+    fn ignored_app(&self) -> Option<String> {
+        if self.ignore_apps.is_empty() {
+            return None;
+        }
+        focus::focused_app().filter(|app| self.ignore_apps.contains(&app.to_lowercase()))
+    }
+
+    /// Copy Entry to Live Clipboard without Updating History
+    fn peek(&mut self, entry: Entry, primary: bool, owner: Owner) -> Result<(), DaemonError> {
+        let mut shared = self.shared.write().expect("rwlock write failed");
+        shared.ignore(&entry);
+        log::info!("copied term entry without touch {:?}", entry.mime());
+        if self.pool.copy(entry, primary)? {
+            self.set_owner(&mut shared, Selection::from(primary), owner);
+        }
         Ok(())
     }
 
+    /// Record Selection Owner and Notify Subscribers when it Changes
+    fn set_owner(&self, shared: &mut Shared, selection: Selection, owner: Owner) {
+        if shared.set_owner(selection, owner.clone()) {
+            self.publish(Event::Owned { selection, owner });
+        }
+    }
+
+    /// Reject Modifications to Read-Only Groups
+    fn guard_readonly(&self, mut message: Request) -> Result<Request, Response> {
+        let mut shared = self.shared.write().expect("rwlock write failed");
+        let group = match &message {
+            Request::Copy { group, .. }
+            | Request::Select { group, .. }
+            | Request::Wipe { group, .. }
+            | Request::Pin { group, .. } => group.clone().or(shared.term_group.clone()),
+            _ => return Ok(message),
+        };
+        if !shared.backend.readonly(group.as_deref()) {
+            return Ok(message);
+        }
+        // selecting from a read-only group must not modify it
+        if let Request::Select { no_touch, .. } = &mut message {
+            *no_touch = true;
+            return Ok(message);
+        }
+        let name = group.unwrap_or_else(|| "default".to_owned());
+        Err(Response::error(format!("Group {name:?} is Read-Only")))
+    }
+
+    /// Reject Requests Referencing Groups that do not Exist (Suggesting Typo Fixes)
+    fn guard_group(&self, message: &Request) -> Result<(), Response> {
+        let group = match message {
+            Request::Copy { create: true, .. } => return Ok(()),
+            Request::Copy { group, .. }
+            | Request::Select { group, .. }
+            | Request::Match { group, .. }
+            | Request::List { group, .. }
+            | Request::ListSince { group, .. }
+            | Request::Find { group, .. }
+            | Request::Wipe { group, .. }
+            | Request::Pin { group, .. }
+            | Request::Reindex { group } => group.as_deref(),
+            _ => return Ok(()),
+        };
+        let Some(group) = group else {
+            return Ok(());
+        };
+        let shared = self.shared.write().expect("rwlock write failed");
+        let (namespace, name) = match group.split_once(NAMESPACE_SEP) {
+            Some((namespace, name)) => (Some(namespace), name),
+            None => (None, group),
+        };
+        // groups the daemon writes to are always valid (even before first use)
+        let mut known: Vec<String> = [
+            &shared.term_group,
+            &shared.live_group,
+            &shared.primary_group,
+            &shared.trash_group,
+        ]
+        .into_iter()
+        .filter_map(|g| g.clone())
+        .chain(std::iter::once("default".to_owned()))
+        .chain(
+            shared
+                .routing
+                .iter()
+                .flat_map(|r| [r.small_backend.clone(), r.large_backend.clone()]),
+        )
+        .collect();
+        if known.iter().any(|g| g == name) {
+            return Ok(());
+        }
+        let groups = shared.backend.groups();
+        if groups.iter().any(|g| g == group) {
+            return Ok(());
+        }
+        known.extend(groups.into_iter().filter_map(|g| {
+            match (namespace, g.split_once(NAMESPACE_SEP)) {
+                (Some(ns), Some((gns, name))) if ns == gns => Some(name.to_owned()),
+                (None, None) => Some(g),
+                _ => None,
+            }
+        }));
+        let error = match fuzzy::suggest(name, known.iter().map(String::as_str)) {
+            Some(similar) => format!("Group {name:?} does not exist; did you mean {similar:?}?"),
+            None => format!("Group {name:?} does not exist"),
+        };
+        Err(Response::error(error))
+    }
+
     /// Process Incoming Request for Daemon
     pub fn process_request(&mut self, message: Request) -> Result<Response, DaemonError> {
+        let message = self.resolve_aliases(message);
+        if let Err(response) = self.guard_group(&message) {
+            return Ok(response);
+        }
+        let message = match self.guard_readonly(message) {
+            Ok(message) => message,
+            Err(response) => return Ok(response),
+        };
         Ok(match message {
             Request::Ping => Response::Ok,
+            Request::Hello { .. } => Response::Ok,
+            Request::Trace { enabled } => {
+                log::info!("request tracing enabled={enabled}");
+                self.trace.store(enabled, Ordering::Relaxed);
+                Response::Ok
+            }
             Request::Stop => {
-                self.stop_wg.wait();
+                self.stop.notify_one();
                 Response::Ok
             }
+            Request::Status => Response::Status {
+                status: self.status(),
+            },
             Request::Clear => {
                 self.clear()?;
                 Response::Ok
@@ -143,21 +1329,63 @@ impl Daemon {
                 primary,
                 group,
                 index,
+                force,
+                revision,
+                ..
             } => {
-                self.copy(entry, primary, group, index)?;
-                Response::Ok
-            }
+                if let Some(index) = index {
+                    let mut shared = self.shared.write().expect("rwlock write failed");
+                    let group = group.clone().or(shared.term_group.clone());
+                    // reject stale writes from editors holding an older revision
+                    let current = shared.group(group.clone()).get(&index).map(|r| r.revision);
+                    // never replace (or trash) the record with an entry that is not stored
+                    if shared.is_skipped(group.as_deref(), &entry) {
+                        let name = group.unwrap_or_else(|| "default".to_owned());
+                        return Ok(Response::error(format!(
+                            "Entry is over the max_entry_size of Group {name:?}"
+                        )));
+                    }
+                    if revision.is_some_and(|expected| current != Some(expected)) {
+                        let name = group.unwrap_or_else(|| "default".to_owned());
+                        log::warn!("rejected stale write (group={name} index={index})");
+                        return Ok(Response::Conflict {
+                            index,
+                            group: name,
+                            revision: current,
+                        });
+                    }
+                    if let Err(err) = shared.overwrite(group, index, force) {
+                        return Ok(Response::error(err));
+                    }
+                }
+                self.copy(entry, primary, group, index)?;
+                Response::Ok
+            }
             Request::Select {
                 index,
                 primary,
                 group,
+                no_touch,
             } => {
-                let record = {
+                let (record, name) = {
                     let mut shared = self.shared.write().expect("rwlock write failed");
                     let group = group.clone().or(shared.term_group.clone());
-                    shared.group(group).select(Some(index))
+                    let name = group.clone().unwrap_or_else(|| "default".to_owned());
+                    let record = match no_touch {
+                        true => shared.group(group).find(Some(index)),
+                        false => shared.group(group).select(Some(index)),
+                    };
+                    (record, name)
                 };
                 match record {
+                    Some(record) if no_touch => {
+                        let owner = Owner::Daemon {
+                            group: name,
+                            index: record.index,
+                        };
+                        self.peek(record.entry, primary, owner)?;
+                        Response::Ok
+                    }
                     Some(record) => {
                         self.copy(record.entry, primary, group, None)?;
                         Response::Ok
@@ -165,170 +1393,1149 @@ impl Daemon {
                     None => Response::error(format!("No Such Index {index:?})")),
                 }
             }
+            Request::Match {
+                query,
+                first,
+                primary,
+                group,
+                no_touch,
+            } => {
+                let mut matches: Vec<(i64, Record)> = {
+                    let mut shared = self.shared.write().expect("rwlock write failed");
+                    let name = group.clone().or(shared.term_group.clone());
+                    shared
+                        .group(name)
+                        .iter()
+                        .filter_map(|r| Some((fuzzy::score(&query, &r.cached_preview())?, r)))
+                        .collect()
+                };
+                // best score first, most recently used breaks ties
+                matches.sort_by(|(a, ra), (b, rb)| b.cmp(a).then(rb.last_used.cmp(&ra.last_used)));
+                let ambiguous = matches.len() > 1 && matches[0].0 == matches[1].0;
+                match matches.first() {
+                    None => Response::error(format!("No Match for {query:?}")),
+                    Some(_) if ambiguous && !first => {
+                        let count = matches.iter().filter(|(s, _)| *s == matches[0].0).count();
+                        Response::error(format!("Ambiguous Match for {query:?} ({count} entries)"))
+                    }
+                    Some((_, record)) => self.process_request(Request::Select {
+                        index: record.index,
+                        primary,
+                        group,
+                        no_touch,
+                    })?,
+                }
+            }
             Request::Groups => {
                 let shared = self.shared.write().expect("rwlock read failed");
                 let groups = shared.backend.groups();
                 Response::Groups { groups }
             }
-            Request::List { length, group } => {
+            Request::GroupStats => {
+                let mut shared = self.shared.write().expect("rwlock write failed");
+                let groups = shared.backend.groups();
+                let stats = groups
+                    .into_iter()
+                    .map(|name| {
+                        let records: Vec<_> = shared.group(Some(name.clone())).iter().collect();
+                        let (label, color) = shared.backend.label(Some(&name));
+                        GroupStats {
+                            group: name,
+                            entries: records.len(),
+                            bytes: records.iter().map(|r| r.entry.as_bytes().len()).sum(),
+                            latest: records.iter().map(|r| r.last_used).max(),
+                            label,
+                            color,
+                        }
+                    })
+                    .collect();
+                Response::GroupStats { stats }
+            }
+            Request::Labels => {
+                let mut shared = self.shared.write().expect("rwlock write failed");
+                let groups = shared.backend.groups();
+                let labels = groups
+                    .into_iter()
+                    .map(|group| {
+                        let (label, color) = shared.backend.label(Some(&group));
+                        GroupLabel {
+                            group,
+                            label,
+                            color,
+                        }
+                    })
+                    .collect();
+                Response::Labels { labels }
+            }
+            Request::List {
+                length,
+                group,
+                prefer_group,
+                etag: known,
+            } => {
+                let mut shared = self.shared.write().expect("rwlock read failed");
+                let group = group.or(shared.term_group.clone());
+                let length = match prefer_group {
+                    true => shared
+                        .backend
+                        .preview_length(group.as_deref())
+                        .unwrap_or(length),
+                    false => length,
+                };
+                let key = (
+                    group.clone().unwrap_or_else(|| "default".to_owned()),
+                    length,
+                );
+                let mut lists = self.lists.write().expect("rwlock write failed");
+                // serve cached previews of large groups while refreshing them
+                let (previews, tag) = match lists.get_mut(&key) {
+                    Some(cache) => {
+                        if !cache.refreshing {
+                            cache.refreshing = true;
+                            self.refresh_list(group, length);
+                        }
+                        (cache.previews.clone(), cache.etag.clone())
+                    }
+                    None => {
+                        let previews = shared.previews(group, length);
+                        let tag = etag(&previews);
+                        if self.list_threshold.is_some_and(|n| previews.len() >= n) {
+                            let cache = ListCache {
+                                previews: previews.clone(),
+                                etag: tag.clone(),
+                                refreshing: false,
+                            };
+                            lists.insert(key, cache);
+                        }
+                        (previews, tag)
+                    }
+                };
+                match known.is_some_and(|known| known == tag) {
+                    true => Response::Unchanged { etag: tag },
+                    false => Response::Previews {
+                        previews,
+                        etag: Some(tag),
+                    },
+                }
+            }
+            Request::ListSince { seq, length, group } => {
                 let mut shared = self.shared.write().expect("rwlock read failed");
                 let group = group.or(shared.term_group.clone());
-                let previews = shared.group(group.clone()).preview(length);
-                Response::Previews { previews }
+                let name = group.clone().unwrap_or_else(|| "default".to_owned());
+                // journal records cleaned up when opening the group before reading it
+                let store = shared.group(group.clone());
+                self.publish_evicted(&mut shared);
+                let journal = self.journal.read().expect("rwlock read failed");
+                let changes = match journal.since(seq, &name) {
+                    Some([added, touched, removed]) => {
+                        let added = store.preview_at(&Vec::from_iter(added), length);
+                        let touched = store.preview_at(&Vec::from_iter(touched), length);
+                        ListChanges {
+                            seq: journal.seq,
+                            reset: false,
+                            added: shared.expiring(group.clone(), added),
+                            touched: shared.expiring(group, touched),
+                            removed: removed.into_iter().collect(),
+                        }
+                    }
+                    None => ListChanges {
+                        seq: journal.seq,
+                        reset: true,
+                        added: shared.previews(group, length),
+                        ..Default::default()
+                    },
+                };
+                Response::Changes { changes }
             }
             Request::Find { index, group } => {
                 let mut shared = self.shared.write().expect("rwlock read failed");
                 let group = group.or(shared.term_group.clone());
+                let name = group.clone().unwrap_or_else(|| "default".to_owned());
                 match shared.group(group).find(index) {
                     Some(record) => Response::Entry {
-                        entry: record.entry,
+                        entry: Box::new(record.entry),
                         index: record.index,
+                        group: name,
+                        revision: record.revision,
                     },
                     None => Response::error(format!("No Such Index {index:?})")),
                 }
             }
+            Request::Active => {
+                let shared = self.shared.read().expect("rwlock read failed");
+                let default = || "default".to_owned();
+                let mut groups: Vec<String> = Vec::new();
+                let mut names = shared.live_groups();
+                names.push(shared.term_group.clone());
+                for name in names.into_iter().map(|g| g.unwrap_or_else(default)) {
+                    if !groups.contains(&name) {
+                        groups.push(name);
+                    }
+                }
+                Response::Active {
+                    clipboard: shared.clipboard_owner.clone(),
+                    primary: shared.primary_owner.clone(),
+                    groups,
+                }
+            }
+            Request::Report { groups } => {
+                let mut shared = self.shared.write().expect("rwlock write failed");
+                let groups = match groups.is_empty() {
+                    true => vec![shared.term_group.clone()],
+                    false => groups.into_iter().map(Some).collect(),
+                };
+                let records: Vec<(String, Record)> = groups
+                    .into_iter()
+                    .flat_map(|group| {
+                        let name = group.clone().unwrap_or_else(|| "default".to_owned());
+                        let records: Vec<_> = shared.group(group).iter().collect();
+                        records.into_iter().map(move |r| (name.clone(), r))
+                    })
+                    .collect();
+                let report = HistoryReport::build(records.iter().map(|(g, r)| (g.as_str(), r)));
+                Response::Report { report }
+            }
+            Request::Search {
+                query,
+                groups,
+                length,
+            } => {
+                let mut shared = self.shared.write().expect("rwlock write failed");
+                let groups = match groups.is_empty() {
+                    true => vec![shared.term_group.clone()],
+                    false => groups.into_iter().map(Some).collect(),
+                };
+                let mut matches = vec![];
+                for group in groups {
+                    let mut previews = shared.group(group.clone()).search(&query, length);
+                    if previews.is_empty() {
+                        continue;
+                    }
+                    if let Some(lifetime) = shared.backend.lifetime(group.as_deref()) {
+                        for preview in previews.iter_mut().filter(|p| !p.pinned) {
+                            preview.expires = Some(preview.last_used + lifetime);
+                        }
+                    }
+                    let group = group.unwrap_or_else(|| "default".to_owned());
+                    matches.push(GroupMatches { group, previews });
+                }
+                Response::Matches { matches }
+            }
+            Request::Subscribe => {
+                Response::error("Subscribe Only Supported on Socket Connections".to_owned())
+            }
+            Request::Import { group, entries } => {
+                let mut shared = self.shared.write().expect("rwlock write failed");
+                let settings = [
+                    ("readonly", "true"),
+                    ("expiration", "never"),
+                    ("max_entries", "none"),
+                ];
+                let settings = settings
+                    .into_iter()
+                    .map(|(k, v)| (k.to_owned(), v.to_owned()))
+                    .collect();
+                if let Err(err) = shared.backend.configure(&group, &settings) {
+                    return Ok(Response::error(err));
+                }
+                let count = entries.len();
+                let dedupe = shared.backend.dedupe(Some(&group));
+                let mut store = shared.group(Some(group.clone()));
+                for entry in entries {
+                    let index = store.push(entry, dedupe);
+                    self.changed(&group, Change::Added(index));
+                }
+                log::info!("imported {count} entries into group {group:?}");
+                Response::Ok
+            }
+            Request::Reindex { group } => {
+                let mut shared = self.shared.write().expect("rwlock write failed");
+                let names = match group {
+                    Some(name) => vec![name],
+                    None => shared.backend.groups(),
+                };
+                let groups = names
+                    .into_iter()
+                    .map(|name| {
+                        let (records, repaired) = shared.group(Some(name.clone())).reindex();
+                        log::info!(
+                            "reindexed group {name:?} ({records} records, {repaired} repaired)"
+                        );
+                        self.changed(&name, Change::Reset);
+                        Reindexed {
+                            group: name,
+                            records,
+                            repaired,
+                        }
+                    })
+                    .collect();
+                Response::Reindexed { groups }
+            }
+            Request::Configure { group, settings } => {
+                let mut shared = self.shared.write().expect("rwlock write failed");
+                match shared.backend.configure(&group, &settings) {
+                    Ok(()) => {
+                        // expiration and preview settings may change every record
+                        self.changed(&group, Change::Reset);
+                        Response::Ok
+                    }
+                    Err(err) => Response::error(err),
+                }
+            }
+            Request::Pin {
+                index,
+                pinned,
+                group,
+            } => {
+                let mut shared = self.shared.write().expect("rwlock write failed");
+                let name = group.or(shared.term_group.clone());
+                let mut group = shared.group(name.clone());
+                let name = name.unwrap_or_else(|| "default".to_owned());
+                match group.pin(index, pinned) {
+                    true => {
+                        log::info!("set pinned={pinned} (group={name} index={index})");
+                        self.changed(&name, Change::Touched(index));
+                        Response::Ok
+                    }
+                    false => Response::error(format!("No Such Index {index:?}")),
+                }
+            }
             Request::Wipe { wipe, group } => {
                 let mut shared = self.shared.write().expect("rwlock write failed");
-                let group = group.or(shared.term_group.clone());
-                let mut group = shared.group(group);
+                let name = group.or(shared.term_group.clone());
+                let mut group = shared.group(name.clone());
+                let name = name.unwrap_or_else(|| "default".to_owned());
                 match wipe {
                     Wipe::All => {
                         group.clear();
+                        self.publish(Event::Deleted {
+                            group: name,
+                            index: None,
+                        });
                         Response::Ok
                     }
                     Wipe::Single { index } => match group.find(Some(index)) {
                         Some(_) => {
                             group.delete(&index);
+                            self.publish(Event::Deleted {
+                                group: name,
+                                index: Some(index),
+                            });
                             Response::Ok
                         }
                         None => Response::error(format!("No Such Index {index:?})")),
                     },
+                    Wipe::Many { ranges } if ranges.len() > MAX_WIPE_RANGES => {
+                        Response::error(format!("Wipe Exceeds {MAX_WIPE_RANGES} Ranges"))
+                    }
+                    wipe => {
+                        let indexes: Vec<usize> = group
+                            .iter()
+                            .filter(|r| wipe.selects(r.index, &r.entry))
+                            .map(|r| r.index)
+                            .collect();
+                        if indexes.is_empty() {
+                            return Ok(Response::error("No Matching Entries".to_owned()));
+                        }
+                        log::info!("deleting {} entries from group {name:?}", indexes.len());
+                        for index in indexes {
+                            group.delete(&index);
+                            self.publish(Event::Deleted {
+                                group: name.clone(),
+                                index: Some(index),
+                            });
+                        }
+                        Response::Ok
+                    }
                 }
             }
         })
     }
 
+    /// Process Request and Record Handling Time
+    fn process_traced(&mut self, request: Request) -> Result<Response, DaemonError> {
+        let kind = request.kind();
+        let start = Instant::now();
+        let response = self.process_request(request);
+        self.publish_evicted(&mut self.shared.write().expect("rwlock write failed"));
+        let elapsed = start.elapsed();
+        let ok = response
+            .as_ref()
+            .map(|r| !matches!(r, Response::Error { .. }));
+        let ok = ok.unwrap_or(false);
+        if elapsed >= self.slow_request {
+            log::warn!("slow request={kind} ok={ok} elapsed={elapsed:?}");
+        } else if self.trace.load(Ordering::Relaxed) {
+            log::info!("trace request={kind} ok={ok} elapsed={elapsed:?}");
+        } else {
+            log::debug!("request={kind} ok={ok} elapsed={elapsed:?}");
+        }
+        response
+    }
+
+    /// Confine Request to the Client Namespace (if Any) and Process it
+    fn process_scoped(
+        &mut self,
+        request: Request,
+        namespace: Option<&str>,
+    ) -> Result<Response, DaemonError> {
+        let Some(ns) = namespace else {
+            return self.process_traced(request);
+        };
+        match self.scope_request(request, ns) {
+            Some(request) => Ok(self.process_traced(request)?.scoped(ns)),
+            None => Ok(Response::error(
+                "Request Not Permitted on Namespaced Connection".to_owned(),
+            )),
+        }
+    }
+
+    /// Process Request on the Blocking Pool so Backend I/O never Stalls the Runtime
+    pub async fn process_blocking(&self, request: Request) -> Result<Response, DaemonError> {
+        let mut daemon = self.clone();
+        task::spawn_blocking(move || daemon.process_traced(request)).await?
+    }
+
     /// Process Socket Connection
-    fn process_conn(&mut self, mut stream: UnixStream) -> Result<(), DaemonError> {
+    async fn process_conn(
+        &mut self,
+        stream: UnixStream,
+        access: Access,
+    ) -> Result<(), DaemonError> {
+        let mut readonly = access.readonly;
+        let mut namespace = access.namespace.clone();
+        let mut limit = RateLimit::new(self.rate_limit);
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        // line-delimited json always opens with `{` while a frame under
+        // `max_request` never does, so older clients keep working
+        let framed = match reader.fill_buf().await?.first() {
+            Some(byte) => *byte != b'{',
+            None => return Ok(()),
+        };
         loop {
             // read and parse request from client
-            let mut buffer = String::new();
-            let mut reader = BufReader::new(&mut stream);
-            let n = reader.read_line(&mut buffer)?;
-            if n == 0 {
+            let Some((buffer, n)) = read_request(&mut reader, framed, self.max_request).await?
+            else {
                 break;
-            }
-            let request = serde_json::from_str(&buffer[..n])?;
+            };
+            let response = match n > self.max_request {
+                true => Response::error(format!("Request Exceeds {} Bytes", self.max_request)),
+                false => {
+                    match access.limit.as_ref() {
+                        Some(shared) => shared.lock().await.wait().await,
+                        None => limit.wait().await,
+                    }
+                    let request: Request = match framed {
+                        true => unframe(&buffer)?,
+                        false => serde_json::from_slice(&buffer)?,
+                    };
+                    match request {
+                        Request::Hello {
+                            readonly: false, ..
+                        } if readonly => Response::error("Connection is Read-Only".to_owned()),
+                        Request::Hello {
+                            namespace: Some(ns),
+                            ..
+                        } if !valid_namespace(&ns) => {
+                            Response::error(format!("Invalid Namespace {ns:?}"))
+                        }
+                        Request::Hello {
+                            namespace: Some(ns),
+                            ..
+                        } if namespace.as_ref().is_some_and(|n| *n != ns) => {
+                            Response::error("Connection Namespace cannot be Changed".to_owned())
+                        }
+                        Request::Hello {
+                            readonly: ro,
+                            namespace: ns,
+                        } => {
+                            readonly |= ro;
+                            namespace = namespace.or(ns);
+                            Response::Ok
+                        }
+                        request if readonly && !request.is_readonly() => Response::error(
+                            "Request Not Permitted on Read-Only Connection".to_owned(),
+                        ),
+                        Request::Subscribe => {
+                            let events = self.subscribe();
+                            writer.write_all(&encode(&Response::Ok, framed)?).await?;
+                            return self.stream_events(writer, events, namespace, framed).await;
+                        }
+                        request => {
+                            let mut daemon = self.clone();
+                            let namespace = namespace.clone();
+                            task::spawn_blocking(move || {
+                                daemon.process_scoped(request, namespace.as_deref())
+                            })
+                            .await??
+                        }
+                    }
+                }
+            };
             // generate, pack, and send response to client
-            let response = self.process_request(request)?;
-            let mut content = serde_json::to_vec(&response)?;
-            content.push('\n' as u8);
-            stream.write(&content)?;
+            writer.write_all(&encode(&response, framed)?).await?;
+            // drop clients sending oversized requests
+            if n > self.max_request {
+                log::warn!("client request exceeded {} bytes", self.max_request);
+                break;
+            }
         }
         Ok(())
     }
 
-    /// Listen for Incoming Server Requests Forever
-    fn server(&mut self) {
-        log::debug!("listening for socket messages");
-        // cleanup any remnants of dead daemon/socket
-        if self.addr.exists() {
-            // halt if existing daemon is already running
-            if let Ok(mut client) = Client::new(self.addr.clone()) {
-                if client.ping().is_ok() {
-                    match self.kill {
-                        true => {
-                            log::warn!("daemon already running. killing it");
-                            let _ = client.stop().expect("failed to kill daemon");
-                        }
-                        false => {
-                            self.start_wg.wait();
-                            log::error!("daemon already running! exiting");
-                            self.stop_wg.wait();
-                            return;
-                        }
-                    };
-                };
+    /// Forward Daemon Events to Subscribed Client until Disconnect
+    async fn stream_events(
+        &self,
+        mut writer: OwnedWriteHalf,
+        mut events: broadcast::Receiver<Event>,
+        namespace: Option<String>,
+        framed: bool,
+    ) -> Result<(), DaemonError> {
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(n)) => {
+                    log::warn!("subscriber lagged behind by {n} events");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let event = match namespace.as_deref() {
+                Some(ns) => match event.scoped(ns) {
+                    Some(event) => event,
+                    None => continue,
+                },
+                None => event,
             };
+            let stopped = matches!(event, Event::Stopped);
+            let content = encode(&Response::Event { event }, framed)?;
+            if writer.write_all(&content).await.is_err() || stopped {
+                break;
+            }
         }
-        let _ = remove_file(&self.addr);
-        // spawn new socket server
-        self.start_wg.wait();
-        let listener = UnixListener::bind(&self.addr).expect("failed to open socket listener");
-        for stream in listener.incoming() {
-            let result = match stream {
-                Ok(stream) => self.process_conn(stream),
+        Ok(())
+    }
+
+    /// Bind Socket Listener (Cleaning Remnants of Dead Daemon/Socket)
+    fn bind(&self, addr: &PathBuf) -> Result<UnixListener, DaemonError> {
+        let _ = remove_file(addr);
+        Ok(UnixListener::bind(addr)?)
+    }
+
+    /// Listen for Incoming Server Requests Forever
+    async fn server(self, listener: UnixListener, access: Access) {
+        let (readonly, namespace) = (access.readonly, access.namespace.as_deref());
+        log::debug!("listening for socket messages (readonly={readonly} namespace={namespace:?})");
+        let mut conns = JoinSet::new();
+        loop {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = self.stopped() => break,
+            };
+            let stream = match accepted {
+                Ok((stream, _)) => stream,
                 Err(err) => {
                     log::error!("connection error: {err:?}");
                     continue;
                 }
             };
-            if let Err(err) = result {
-                log::error!("stream error: {err:?}");
-            }
+            let mut daemon = self.clone();
+            let access = access.clone();
+            conns.spawn(async move {
+                if let Err(err) = daemon.process_conn(stream, access).await {
+                    daemon.report_error(format!("stream error: {err:?}"));
+                }
+            });
         }
+        // stop accepting and give open connections a moment to finish
+        drop(listener);
+        let drain = async { while conns.join_next().await.is_some() {} };
+        if tokio::time::timeout(SHUTDOWN_GRACE, drain).await.is_err() {
+            log::debug!("closing {} lingering connections", conns.len());
+        }
+        conns.shutdown().await;
     }
 
     /// Watch for Clipboard Updates and Save Non-Empty Copies
-    fn watch_clipboard(&mut self) {
-        log::debug!("watching clipboard for activity");
-        let mut stream = WlClipboardPasteStream::init(WlListenType::ListenOnCopy)
-            .expect("failed to open clipboard listener");
-        self.start_wg.wait();
-        for message in stream.paste_stream().flatten() {
-            // collect clipboard entry object
-            let Some(msg) = message else { continue };
-            let entry = Entry::from(msg);
+    fn watch_clipboard(&mut self, selection: Selection, reconnect: bool) {
+        log::debug!("watching {selection} selection for activity");
+        let mut stream = match self.pool.provider.listen(selection) {
+            Ok(stream) => stream,
+            Err(err) => {
+                self.report_error(format!("failed to open clipboard listener: {err:?}"));
+                return;
+            }
+        };
+        if reconnect {
+            self.reoffer(stream.as_mut(), selection);
+        }
+        // entry waiting to be re-offered once its owner exits
+        let mut pending: Option<(Entry, Owner)> = None;
+        let mut errors = 0;
+        while let Some(message) = stream.next() {
+            // collect clipboard entry object (giving up on a broken listener)
+            let message = match message {
+                Ok(message) => message,
+                Err(err) => {
+                    self.report_error(format!("clipboard listener error: {err:?}"));
+                    errors += 1;
+                    if errors >= WATCH_MAX_ERRORS {
+                        return;
+                    }
+                    continue;
+                }
+            };
+            errors = 0;
+            // re-offer pending entry when the selection is cleared
+            let entry = message.map(|entry| entry.with_selection(selection));
+            let Some(entry) = entry.filter(|e| !e.is_empty()) else {
+                match pending.take() {
+                    Some((entry, owner)) => {
+                        log::debug!("selection owner exited. re-offering captured entry");
+                        self.recopy(entry, owner);
+                    }
+                    None => {
+                        let mut shared = self.shared.write().expect("rwlock write failed");
+                        self.set_owner(&mut shared, selection, Owner::Unknown);
+                    }
+                }
+                continue;
+            };
             // determine if entry should be ignored
+            let app = self.ignored_app();
             let mut shared = self.shared.write().expect("rwlock write failed");
-            let group = shared.live_group.clone();
-            if entry.is_empty() || shared.ignore.as_ref().map(|i| i == &entry).unwrap_or(false) {
+            if shared.is_echo(&entry) {
                 continue;
             }
-            // copy into manager
-            let mime = entry.mime();
-            let name = group.clone().unwrap_or_else(|| "default".to_owned());
-            let index = shared.group(group).push(entry.clone());
-            log::info!("copied live entry (group={name} index={index}) {mime:?}");
-            // recopy clipboard if enabled
-            shared.ignore = Some(entry.clone());
-            if self.recopy {
-                if let Err(err) = copy(entry, false) {
-                    log::error!("failed to re-copy clipboard: {err:?}");
-                };
+            let mimes = entry.mime.clone();
+            self.set_owner(&mut shared, selection, Owner::External { mimes });
+            // secrets stay with their owner (never stored or re-offered)
+            if shared.is_denied(&entry) {
+                log::debug!("skipped entry offering a denied mimetype");
+                pending = None;
+                continue;
+            }
+            if let Some(app) = app {
+                log::debug!("skipped entry copied from ignored app {app:?}");
+                pending = None;
+                continue;
+            }
+            // touch existing record elsewhere or store in every capturing group
+            let entry = shared.clean_url(entry);
+            let entry = shared.trim_prompt(entry);
+            let entry = shared.uri_list.resolve(entry);
+            let targets = shared.capture_groups(&entry);
+            let existing = shared
+                .dedupe
+                .then(|| shared.existing(&entry, &targets))
+                .flatten();
+            let stored: Vec<(Entry, Owner)> = match existing {
+                Some((name, index)) => {
+                    shared.group(Some(name.clone())).touch(index);
+                    match shared.capture_log.record(&name) {
+                        true => log::info!("touched existing entry (group={name} index={index})"),
+                        false => log::debug!("touched existing entry (group={name} index={index})"),
+                    }
+                    self.publish_touched(Event::Copied {
+                        group: name.clone(),
+                        index,
+                        mime: entry.mime(),
+                        live: true,
+                    });
+                    vec![(entry, Owner::Daemon { group: name, index })]
+                }
+                None => targets
+                    .into_iter()
+                    .filter_map(|group| self.store_live(&mut shared, entry.clone(), group))
+                    .collect(),
+            };
+            self.publish_evicted(&mut shared);
+            // recopy clipboard if enabled and entry meets conditions
+            pending = None;
+            let Some((entry, owner)) = stored.into_iter().next() else {
+                continue;
+            };
+            shared.ignore(&entry);
+            drop(shared);
+            match self.recopy.as_ref().filter(|r| r.allows(&entry)) {
+                Some(RecopyConfig {
+                    mode: RecopyMode::OnExit,
+                    ..
+                }) => pending = Some((entry, owner)),
+                Some(_) => self.recopy(entry, owner),
+                None => {}
             }
         }
     }
 
-    /// Listen for Incoming Events and Send Responses
-    pub fn run(&mut self) -> Result<(), DaemonError> {
-        // spawn threads
+    /// Run Clipboard Watcher and Restart it with Backoff whenever it Dies
+    fn supervise_watcher(&mut self, selection: Selection) {
+        let mut backoff = WATCH_BACKOFF_MIN;
+        let mut reconnect = false;
+        loop {
+            let started = Instant::now();
+            let mut watcher = self.clone();
+            let watch = || watcher.watch_clipboard(selection, reconnect);
+            if catch_unwind(AssertUnwindSafe(watch)).is_err() {
+                self.report_error(format!("{selection} watcher panicked"));
+            }
+            if self.is_stopped() {
+                return;
+            }
+            // reset backoff after the watcher ran healthy for a while
+            if started.elapsed() > WATCH_BACKOFF_MAX {
+                backoff = WATCH_BACKOFF_MIN;
+            }
+            log::warn!("{selection} watcher stopped. restarting in {backoff:?}");
+            thread::sleep(backoff);
+            if self.is_stopped() {
+                return;
+            }
+            self.restarts.fetch_add(1, Ordering::Relaxed);
+            reconnect = true;
+            backoff = std::cmp::min(backoff * 2, WATCH_BACKOFF_MAX);
+        }
+    }
+
+    /// Re-Offer Latest Entry if the Selection was Lost (Compositor Restart)
+    fn reoffer(&self, stream: &mut dyn Listener, selection: Selection) {
+        match stream.current() {
+            Ok(Some(entry)) if !entry.is_empty() => return,
+            Ok(_) => {}
+            Err(err) => {
+                log::warn!("failed to read {selection} after reconnect: {err:?}");
+                return;
+            }
+        }
+        let found = {
+            let mut shared = self.shared.write().expect("rwlock write failed");
+            let owner = match selection {
+                Selection::Clipboard => &shared.clipboard_owner,
+                Selection::Primary => &shared.primary_owner,
+            };
+            let (groups, index) = match (selection, owner) {
+                (_, Owner::Daemon { group, index }) => (vec![Some(group.to_owned())], Some(*index)),
+                (Selection::Clipboard, _) => (shared.live_groups(), None),
+                (Selection::Primary, _) => (vec![shared.primary_group.clone()], None),
+            };
+            // the latest entry may have been routed to either size group
+            groups
+                .into_iter()
+                .filter_map(|group| {
+                    let name = group.clone().unwrap_or_else(|| "default".to_owned());
+                    shared.group(group).find(index).map(|record| (record, name))
+                })
+                .max_by_key(|(record, _)| record.last_used)
+        };
+        if let Some((record, group)) = found {
+            log::info!(
+                "re-offering {selection} entry (group={group} index={})",
+                record.index
+            );
+            let owner = Owner::Daemon {
+                group,
+                index: record.index,
+            };
+            self.recopy(record.entry.with_selection(selection), owner);
+        }
+    }
+
+    /// Re-Offer Captured Entry as the Daemon
+    fn recopy(&self, entry: Entry, owner: Owner) {
+        let selection = entry.selection;
+        let mut shared = self.shared.write().expect("rwlock write failed");
+        shared.ignore(&entry);
+        match self.pool.copy(entry, !selection.is_clipboard()) {
+            Ok(true) => self.set_owner(&mut shared, selection, owner),
+            Ok(false) => {}
+            Err(err) => self.report_error(format!("failed to re-copy clipboard: {err:?}")),
+        }
+    }
+
+    /// Process Live Entry with Group Capture Hook and Store
+    fn store_live(&self, shared: &mut Shared, entry: Entry, group: Grp) -> Option<(Entry, Owner)> {
+        let Some((entry, group)) = shared.hook(entry, group) else {
+            log::debug!("live entry rejected by capture script");
+            return None;
+        };
+        // the captured entry is still re-offered when only a reference is stored
+        let stored = shared.limit_size(group.as_deref(), entry.clone())?;
+        let mime = stored.mime();
+        let name = group.clone().unwrap_or_else(|| "default".to_owned());
+        let dedupe = shared.backend.dedupe(group.as_deref());
+        let mut store = shared.group(group);
+        let touched = store.exists(&stored, dedupe).is_some();
+        let index = store.push(stored, dedupe);
+        match shared.capture_log.record(&name) {
+            true => log::info!("copied live entry (group={name} index={index}) {mime:?}"),
+            false => log::debug!("copied live entry (group={name} index={index}) {mime:?}"),
+        }
+        let event = Event::Copied {
+            group: name.clone(),
+            index,
+            mime,
+            live: true,
+        };
+        match touched {
+            true => self.publish_touched(event),
+            false => self.publish(event),
+        }
+        Some((entry, Owner::Daemon { group: name, index }))
+    }
+
+    /// Check if Daemon Shutdown has Started
+    fn is_stopped(&self) -> bool {
+        *self.cancel.borrow()
+    }
+
+    /// Resolve once Daemon Shutdown has Started
+    pub async fn stopped(&self) {
+        let mut cancel = self.cancel.subscribe();
+        let _ = cancel.wait_for(|stopped| *stopped).await;
+    }
+
+    /// Periodically Report Coalesced Capture Logs until Shutdown
+    async fn flush_capture_log(self) {
+        let mut interval = tokio::time::interval(CAPTURE_LOG_WINDOW);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = self.stopped() => return,
+            }
+            self.shared
+                .write()
+                .expect("rwlock write failed")
+                .capture_log
+                .flush();
+        }
+    }
+
+    /// Delete Spilled Files once Records Referencing them are Gone
+    async fn sweep_spilled(self) {
+        let mut interval = tokio::time::interval(SPILL_SWEEP_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = self.stopped() => return,
+            }
+            let daemon = self.clone();
+            let sweep = move || {
+                let mut shared = daemon.shared.write().expect("rwlock write failed");
+                let spilling: Vec<String> = shared
+                    .backend
+                    .groups()
+                    .into_iter()
+                    .filter(|group| {
+                        let limit = shared.backend.max_entry_size(Some(group));
+                        matches!(limit, Some((_, LargeEntryPolicy::SpillToFile)))
+                    })
+                    .collect();
+                spill::sweep(&spilling, || {
+                    shared
+                        .backend
+                        .groups()
+                        .into_iter()
+                        .flat_map(|group| shared.group(Some(group)).iter())
+                        .filter_map(|record| spill::referenced(&record.entry))
+                        .collect()
+                })
+            };
+            match task::spawn_blocking(sweep).await {
+                Ok(Ok(0)) => {}
+                Ok(Ok(deleted)) => log::info!("deleted {deleted} unreferenced spilled entries"),
+                Ok(Err(err)) => log::warn!("failed to sweep spilled entries: {err}"),
+                Err(err) => log::warn!("spilled entry sweep ended abnormally: {err:?}"),
+            }
+        }
+    }
+
+    /// Export New Records into the Sync Directory and Import Foreign Ones
+    fn sync_pass(&self, dir: &SyncDir, cfg: &SyncConfig) -> std::io::Result<(usize, usize)> {
+        let exported = dir.exported()?;
+        let mut records = vec![];
+        {
+            let mut shared = self.shared.write().expect("rwlock write failed");
+            for group in shared.backend.groups() {
+                if !cfg.includes(&group) || shared.backend.readonly(Some(&group)) {
+                    continue;
+                }
+                // sync files are plaintext, so encrypted groups never leave the store
+                if shared.backend.encrypted(Some(&group)) {
+                    continue;
+                }
+                let store = shared.group(Some(group.clone()));
+                let shareable = store.iter().filter(|r| !secret::is_secret(&r.entry));
+                records.extend(shareable.map(|record| (group.clone(), record)));
+            }
+        }
+        let mut known: HashSet<String> = records.iter().map(|(_, r)| record_id(r)).collect();
+        let mut count = (0, 0);
+        for (group, record) in records.iter() {
+            if !exported.contains(&record_id(record)) {
+                dir.export(group, record)?;
+                count.0 += 1;
+            }
+        }
+        if !cfg.import {
+            return Ok(count);
+        }
+        known.extend(dir.imported()?);
+        let mut imported = vec![];
+        for sidecar in dir.foreign()? {
+            if known.contains(&sidecar.id) || !cfg.includes(&sidecar.group) {
+                continue;
+            }
+            let mut record = match dir.load(&sidecar, 0) {
+                Ok(record) => record,
+                Err(err) => {
+                    log::warn!("failed to load synced entry {:?}: {err:?}", sidecar.file);
+                    continue;
+                }
+            };
+            known.insert(sidecar.id.clone());
+            imported.push(sidecar.id);
+            let mut shared = self.shared.write().expect("rwlock write failed");
+            if shared.backend.readonly(Some(&sidecar.group)) {
+                continue;
+            }
+            let dedupe = shared.backend.dedupe(Some(&sidecar.group));
+            let mut store = shared.group(Some(sidecar.group));
+            if store.exists(&record.entry, dedupe).is_none() {
+                record.index = store.index();
+                store.insert(record.index, record);
+                count.1 += 1;
+            }
+        }
+        dir.mark_imported(&imported)?;
+        Ok(count)
+    }
+
+    /// Periodically Sync Records with the Configured Directory until Shutdown
+    async fn sync_directory(self, cfg: SyncConfig) {
+        let dir = SyncDir::new(&cfg);
+        log::info!("syncing entries with {:?} as {:?}", dir.root, dir.machine);
+        let mut interval = tokio::time::interval(cfg.interval());
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = self.stopped() => return,
+            }
+            let (daemon, dir, cfg) = (self.clone(), dir.clone(), cfg.clone());
+            match task::spawn_blocking(move || daemon.sync_pass(&dir, &cfg)).await {
+                Ok(Ok((0, 0))) => {}
+                Ok(Ok((exported, imported))) => {
+                    log::info!("directory sync exported {exported} and imported {imported} entries")
+                }
+                Ok(Err(err)) => self.report_error(format!("directory sync failed: {err:?}")),
+                Err(err) => log::warn!("directory sync ended abnormally: {err:?}"),
+            }
+        }
+    }
+
+    /// Stop Services, Wait for In-Flight Writes and Cleanup Daemon Remnants
+    ///
+    /// Watcher threads block on the compositor and cannot be joined. They
+    /// exit on their next event and are abandoned with the runtime.
+    async fn shutdown(&mut self, services: Vec<TaskHandle<()>>) {
+        self.publish(Event::Stopped);
+        self.cancel.send_replace(true);
+        for service in services {
+            if let Err(err) = service.await {
+                log::warn!("service ended abnormally: {err:?}");
+            }
+        }
+        let mut shared = self.shared.write().expect("rwlock write failed");
+        if let Err(err) = self.save_snapshot(&mut shared) {
+            self.report_error(format!("failed to save memory snapshot: {err:?}"));
+        }
+        if let Err(err) = shared.backend.flush() {
+            self.report_error(format!("failed to flush storage: {err}"));
+        }
+        let apps = self.apps.iter().map(|(addr, _)| addr);
+        for addr in std::iter::once(&self.addr)
+            .chain(self.readonly_addr.iter())
+            .chain(apps)
+        {
+            if let Err(err) = remove_file(addr) {
+                log::warn!("failed to remove socket {addr:?}: {err:?}");
+            }
+        }
+    }
+
+    /// Reload Memory Groups Saved on the Last Shutdown
+    fn restore_snapshot(&self) -> Result<(), DaemonError> {
+        let Some(path) = self.snapshot.as_ref().filter(|p| p.exists()) else {
+            return Ok(());
+        };
+        let snapshot: Snapshot = serde_json::from_slice(&std::fs::read(path)?)?;
+        let mut shared = self.shared.write().expect("rwlock write failed");
+        shared.backend.restore(snapshot);
+        log::info!("restored memory snapshot {path:?}");
+        Ok(())
+    }
+
+    /// Save Memory Groups for the Next Startup (Readable only by the User)
+    fn save_snapshot(&self, shared: &mut Shared) -> Result<(), DaemonError> {
+        let Some(path) = self.snapshot.as_ref() else {
+            return Ok(());
+        };
+        let snapshot = serde_json::to_vec(&shared.backend.snapshot())?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .mode(0o600)
+            .open(path)?;
+        file.write_all(&snapshot)?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    /// Run Daemon Services on the Event Loop until Stopped
+    async fn serve(&mut self) -> Result<(), DaemonError> {
+        // ensure only a single daemon instance is running
+        let _lock = self.lock_instance()?;
+        self.health_check()?;
+        if let Err(err) = self.restore_snapshot() {
+            self.report_error(format!("failed to restore memory snapshot: {err:?}"));
+        }
+        // spawn services
+        let mut sigterm = signal(SignalKind::terminate())?;
+        let mut sigint = signal(SignalKind::interrupt())?;
         if self.live {
             let mut wdaemon = self.clone();
-            thread::spawn(move || wdaemon.watch_clipboard());
+            task::spawn_blocking(move || wdaemon.supervise_watcher(Selection::Clipboard));
+        }
+        if self.primary {
+            let mut wdaemon = self.clone();
+            task::spawn_blocking(move || wdaemon.supervise_watcher(Selection::Primary));
+        }
+        let listener = self.bind(&self.addr)?;
+        let mut services = vec![tokio::spawn(
+            self.clone().server(listener, Access::default()),
+        )];
+        if let Some(addr) = self.readonly_addr.as_ref() {
+            let listener = self.bind(addr)?;
+            services.push(tokio::spawn(
+                self.clone().server(listener, Access::readonly()),
+            ));
+        }
+        for (addr, access) in self.apps.iter() {
+            let namespace = access.namespace.as_deref().unwrap_or_default();
+            if !valid_namespace(namespace) {
+                self.report_error(format!(
+                    "invalid app namespace {namespace:?}. skipping {addr:?}"
+                ));
+                continue;
+            }
+            if let Some(dir) = addr.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            let listener = self.bind(addr)?;
+            services.push(tokio::spawn(self.clone().server(listener, access.clone())));
+        }
+        let _dbus = match self.gpaste {
+            true => {
+                // gpaste only browses text, so size routing uses its small group
+                let history = {
+                    let shared = self.shared.read().expect("rwlock read failed");
+                    shared.live_groups().remove(0)
+                };
+                let history = history.unwrap_or_else(|| "default".to_owned());
+                match gpaste::serve(self.clone(), history).await {
+                    Ok(conn) => Some(conn),
+                    Err(err) => {
+                        self.report_error(format!("failed to serve gpaste dbus: {err:?}"));
+                        None
+                    }
+                }
+            }
+            false => None,
+        };
+        if self.live || self.primary {
+            services.push(tokio::spawn(self.clone().flush_capture_log()));
+        }
+        services.push(tokio::spawn(self.clone().sweep_spilled()));
+        if let Some(config) = self.sync.clone() {
+            services.push(tokio::spawn(self.clone().sync_directory(config)));
+        }
+        if let Some(config) = self.http.clone() {
+            let daemon = self.clone();
+            services.push(tokio::spawn(async move {
+                if let Err(err) = http::serve(daemon.clone(), config).await {
+                    daemon.report_error(format!("http api error: {err:?}"));
+                }
+            }));
         }
-        let mut sdaemon = self.clone();
-        thread::spawn(move || sdaemon.server());
-        // wait for services to start
-        self.start_wg.wait();
         log::info!("daemon running");
         // wait for services to end
-        self.stop_wg.wait();
+        tokio::select! {
+            _ = self.stop.notified() => {}
+            _ = sigterm.recv() => log::info!("received SIGTERM. shutting down"),
+            _ = sigint.recv() => log::info!("received SIGINT. shutting down"),
+        }
+        self.shutdown(services).await;
         log::info!("daemon stopped");
         Ok(())
     }
+
+    /// Listen for Incoming Events and Send Responses
+    pub fn run(&mut self) -> Result<(), DaemonError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let result = runtime.block_on(self.serve());
+        // blocking tasks (clipboard watcher/servers) never finish on their own
+        runtime.shutdown_background();
+        result
+    }
+
+    /// Run Daemon in a Background Thread and Return Control Handle
+    pub fn start(mut self) -> DaemonHandle {
+        let stop = Arc::clone(&self.stop);
+        let events = self.events.clone();
+        let thread = thread::spawn(move || self.run());
+        DaemonHandle {
+            stop,
+            events,
+            thread,
+        }
+    }
 }
 
 impl Clone for Daemon {
     fn clone(&self) -> Self {
         Self {
             kill: self.kill,
+            headless: self.headless,
             live: self.live,
-            recopy: self.recopy,
+            primary: self.primary,
+            recopy: self.recopy.clone(),
+            takeover: self.takeover,
+            ignore_apps: self.ignore_apps.clone(),
+            notify: self.notify,
+            gpaste: self.gpaste,
+            pool: self.pool.clone(),
+            restarts: Arc::clone(&self.restarts),
+            cancel: Arc::clone(&self.cancel),
+            http: self.http.clone(),
+            sync: self.sync.clone(),
+            max_request: self.max_request,
+            rate_limit: self.rate_limit,
+            slow_request: self.slow_request,
+            trace: Arc::clone(&self.trace),
             addr: self.addr.clone(),
+            readonly_addr: self.readonly_addr.clone(),
+            snapshot: self.snapshot.clone(),
+            apps: self.apps.clone(),
+            started: self.started,
             shared: Arc::clone(&self.shared),
-            start_wg: Arc::clone(&self.start_wg),
-            stop_wg: Arc::clone(&self.stop_wg),
+            list_threshold: self.list_threshold,
+            lists: Arc::clone(&self.lists),
+            journal: Arc::clone(&self.journal),
+            stop: Arc::clone(&self.stop),
+            events: self.events.clone(),
         }
     }
 }
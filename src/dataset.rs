@@ -0,0 +1,64 @@
+//! Importable Data-Set Formats (Emoji Lists, Line-per-Entry Files)
+
+use std::str::FromStr;
+
+use crate::clipboard::Entry;
+
+/// Supported Data-Set File Formats
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SetFormat {
+    /// One Entry per Non-Empty Line
+    Lines,
+    /// Unicode `emoji-test.txt` or `<emoji> <name>` Lines
+    Emoji,
+}
+
+impl FromStr for SetFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lines" => Ok(Self::Lines),
+            "emoji" => Ok(Self::Emoji),
+            _ => Err(format!("invalid set format: {s:?}")),
+        }
+    }
+}
+
+/// Parse Single Emoji Line into (Emoji, Name)
+fn parse_emoji(line: &str) -> Option<(&str, &str)> {
+    // emoji-test.txt: `1F600 ; fully-qualified # 😀 E1.0 grinning face`
+    if let Some((spec, comment)) = line.split_once('#') {
+        if let Some((_, status)) = spec.split_once(';') {
+            if status.trim() != "fully-qualified" {
+                return None;
+            }
+            let (emoji, rest) = comment.trim().split_once(' ')?;
+            let name = match rest.split_once(' ') {
+                Some((version, name)) if version.starts_with('E') => name,
+                _ => rest,
+            };
+            return Some((emoji, name.trim()));
+        }
+    }
+    // simple: `😀 grinning face`
+    let (emoji, name) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    Some((emoji, name.trim()))
+}
+
+/// Parse Data-Set Contents into Clipboard Entries
+pub fn parse(format: SetFormat, content: &str) -> Vec<Entry> {
+    let lines = content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'));
+    match format {
+        SetFormat::Lines => lines.map(|l| Entry::text(l.to_owned(), None)).collect(),
+        SetFormat::Emoji => lines
+            .filter_map(parse_emoji)
+            .map(|(emoji, name)| {
+                let note = (!name.is_empty()).then(|| name.to_owned());
+                Entry::text(emoji.to_owned(), None).with_note(note)
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,57 @@
+//! Focused Window Lookup via Compositor IPC (Hyprland and Sway)
+//!
+//! The data-control protocol never reports which client set the
+//! selection, but the copying application is almost always focused.
+
+use std::process::Command;
+
+use serde_json::Value;
+
+/// Run Command and Parse its Output as JSON
+fn json_output(program: &str, args: &[&str]) -> Option<Value> {
+    let output = match Command::new(program).args(args).output() {
+        Ok(output) if output.status.success() => output,
+        Ok(output) => {
+            log::debug!("{program} exited with {}", output.status);
+            return None;
+        }
+        Err(err) => {
+            log::debug!("failed to run {program}: {err:?}");
+            return None;
+        }
+    };
+    serde_json::from_slice(&output.stdout).ok()
+}
+
+/// App Class from `hyprctl activewindow -j` Output
+pub fn hyprland_app(window: &Value) -> Option<String> {
+    let class = window.get("class")?.as_str()?;
+    (!class.is_empty()).then(|| class.to_owned())
+}
+
+/// App ID (or XWayland Class) of the Focused Node in a `swaymsg -t get_tree`
+pub fn sway_app(node: &Value) -> Option<String> {
+    if node.get("focused").and_then(Value::as_bool) == Some(true) {
+        let app = node.get("app_id").and_then(Value::as_str).or_else(|| {
+            node.pointer("/window_properties/class")
+                .and_then(Value::as_str)
+        });
+        return app.map(str::to_owned);
+    }
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node.get(key)?.as_array())
+        .flatten()
+        .find_map(sway_app)
+}
+
+/// Application ID or Class of the Focused Window (None if Unsupported)
+pub fn focused_app() -> Option<String> {
+    if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+        return hyprland_app(&json_output("hyprctl", &["activewindow", "-j"])?);
+    }
+    if std::env::var_os("SWAYSOCK").is_some() {
+        return sway_app(&json_output("swaymsg", &["-t", "get_tree"])?);
+    }
+    None
+}
@@ -0,0 +1,79 @@
+//! Fuzzy Matching for Selecting Entries by Content
+
+/// Score Bonus for Exact Substring Matches
+const SUBSTRING_BONUS: i64 = 1000;
+
+/// Score Text Against Query (Higher is Better, None if No Match)
+///
+/// Exact substrings always outrank scattered subsequence matches.
+/// Subsequence matches are rewarded for consecutive characters and
+/// matches at the start of words.
+pub fn score(query: &str, text: &str) -> Option<i64> {
+    let query = query.to_lowercase();
+    let text = text.to_lowercase();
+    if query.is_empty() {
+        return Some(0);
+    }
+    if let Some(pos) = text.find(&query) {
+        let pos = text[..pos].chars().count() as i64;
+        return Some(SUBSTRING_BONUS * 2 - pos.min(SUBSTRING_BONUS - 1));
+    }
+    let mut score = 0;
+    let mut chars = query.chars().peekable();
+    let mut prev: Option<char> = None;
+    let mut consecutive = false;
+    for c in text.chars() {
+        let Some(&want) = chars.peek() else { break };
+        if c == want {
+            chars.next();
+            score += 1;
+            if consecutive {
+                score += 5;
+            }
+            if prev.map(|p| !p.is_alphanumeric()).unwrap_or(true) {
+                score += 10;
+            }
+            consecutive = true;
+        } else {
+            consecutive = false;
+            score -= 1;
+        }
+        prev = Some(c);
+    }
+    match chars.peek() {
+        Some(_) => None,
+        None => Some(score.min(SUBSTRING_BONUS - 1)),
+    }
+}
+
+/// Edit Distance Counting Adjacent Transpositions as a Single Edit
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![(0..=b.len()).collect::<Vec<usize>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = (a[i - 1] != b[j - 1]) as usize;
+            row[j] = (rows[i - 1][j] + 1)
+                .min(row[j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Suggest Closest Candidate for a Likely Typo (None if Nothing is Close)
+pub fn suggest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = std::cmp::max(1, name.chars().count() / 3);
+    candidates
+        .into_iter()
+        .map(|c| (distance(&name.to_lowercase(), &c.to_lowercase()), c))
+        .filter(|(d, _)| *d <= limit)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
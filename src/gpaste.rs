@@ -0,0 +1,229 @@
+//! GPaste Compatible D-Bus Interface (Subset)
+//!
+//! Lets shell extensions written for GPaste browse and select history
+//! stored by wclipd. Entry uuids are the entry indexes within a group.
+
+use tokio::sync::broadcast::error::RecvError;
+use zbus::object_server::SignalContext;
+use zbus::{fdo, interface, Connection};
+
+use crate::clipboard::Entry;
+use crate::daemon::Daemon;
+use crate::message::{Event, Request, Response, Wipe};
+
+static BUS_NAME: &'static str = "org.gnome.GPaste";
+static OBJECT_PATH: &'static str = "/org/gnome/GPaste";
+
+/// Preview Length Reported for History Listings
+static PREVIEW_LENGTH: usize = 200;
+
+/// GPaste Interface Backed by Daemon Requests
+pub struct GPaste {
+    daemon: Daemon,
+    history: String,
+}
+
+impl GPaste {
+    pub fn new(daemon: Daemon, history: String) -> Self {
+        Self { daemon, history }
+    }
+    /// Process Request against Daemon and Convert Errors
+    async fn request(&self, request: Request) -> fdo::Result<Response> {
+        match self.daemon.process_blocking(request).await {
+            Ok(Response::Error { error }) => Err(fdo::Error::Failed(error)),
+            Ok(response) => Ok(response),
+            Err(err) => Err(fdo::Error::Failed(format!("{err:?}"))),
+        }
+    }
+    /// Parse Entry Uuid into Index
+    fn index(uuid: &str) -> fdo::Result<usize> {
+        uuid.parse()
+            .map_err(|_| fdo::Error::InvalidArgs(format!("invalid uuid: {uuid:?}")))
+    }
+    /// List (Uuid, Preview) Pairs for History (Most Recent First)
+    async fn previews(&self, history: &str) -> fdo::Result<Vec<(String, String)>> {
+        let request = Request::List {
+            length: PREVIEW_LENGTH,
+            group: Some(history.to_owned()),
+            prefer_group: false,
+            etag: None,
+        };
+        let Response::Previews { mut previews, .. } = self.request(request).await? else {
+            return Err(fdo::Error::Failed("unexpected response".to_owned()));
+        };
+        previews.sort_by_key(|p| std::cmp::Reverse(p.last_used));
+        Ok(previews
+            .into_iter()
+            .map(|p| (p.index.to_string(), p.preview))
+            .collect())
+    }
+}
+
+#[interface(name = "org.gnome.GPaste2")]
+impl GPaste {
+    /// Add Text to Current History
+    async fn add(&self, string: String) -> fdo::Result<()> {
+        self.request(Request::Copy {
+            entry: Entry::text(string, None),
+            primary: false,
+            group: Some(self.history.clone()),
+            index: None,
+            force: false,
+            create: true,
+            revision: None,
+        })
+        .await?;
+        Ok(())
+    }
+    /// Delete Entry from Current History
+    async fn delete(&self, uuid: String) -> fdo::Result<()> {
+        let index = Self::index(&uuid)?;
+        self.request(Request::Wipe {
+            wipe: Wipe::Single { index },
+            group: Some(self.history.clone()),
+        })
+        .await?;
+        Ok(())
+    }
+    /// Delete All Entries from History
+    async fn empty_history(&self, name: String) -> fdo::Result<()> {
+        self.request(Request::Wipe {
+            wipe: Wipe::All,
+            group: Some(name),
+        })
+        .await?;
+        Ok(())
+    }
+    /// Retrieve Full Text of Entry
+    async fn get_element(&self, uuid: String) -> fdo::Result<String> {
+        let index = Self::index(&uuid)?;
+        let request = Request::Find {
+            index: Some(index),
+            group: Some(self.history.clone()),
+        };
+        match self.request(request).await? {
+            Response::Entry { entry, .. } => Ok(entry.to_text()),
+            _ => Err(fdo::Error::Failed("unexpected response".to_owned())),
+        }
+    }
+    /// Retrieve (Uuid, Text) of Entry at Position in History
+    async fn get_element_at_index(&self, index: u64) -> fdo::Result<(String, String)> {
+        let previews = self.previews(&self.history).await?;
+        let (uuid, _) = previews
+            .into_iter()
+            .nth(index as usize)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("no element at {index}")))?;
+        let value = self.get_element(uuid.clone()).await?;
+        Ok((uuid, value))
+    }
+    /// List (Uuid, Preview) Pairs for Current History
+    async fn get_history(&self) -> fdo::Result<Vec<(String, String)>> {
+        self.previews(&self.history).await
+    }
+    /// Name of Current History
+    fn get_history_name(&self) -> String {
+        self.history.clone()
+    }
+    /// Number of Entries in History
+    async fn get_history_size(&self, name: String) -> fdo::Result<u64> {
+        Ok(self.previews(&name).await?.len() as u64)
+    }
+    /// List Available Histories
+    async fn list_histories(&self) -> fdo::Result<Vec<String>> {
+        match self.request(Request::Groups).await? {
+            Response::Groups { groups } => Ok(groups),
+            _ => Err(fdo::Error::Failed("unexpected response".to_owned())),
+        }
+    }
+    /// Find Uuids of Entries Containing Query
+    async fn search(&self, query: String) -> fdo::Result<Vec<String>> {
+        let query = query.to_lowercase();
+        Ok(self
+            .previews(&self.history)
+            .await?
+            .into_iter()
+            .filter(|(_, preview)| preview.to_lowercase().contains(&query))
+            .map(|(uuid, _)| uuid)
+            .collect())
+    }
+    /// Copy Entry to Active Clipboard
+    async fn select(&self, uuid: String) -> fdo::Result<()> {
+        let index = Self::index(&uuid)?;
+        self.request(Request::Select {
+            index,
+            primary: false,
+            group: Some(self.history.clone()),
+            no_touch: false,
+        })
+        .await?;
+        Ok(())
+    }
+    /// Switch Current History
+    async fn switch_history(
+        &mut self,
+        name: String,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        self.history = name;
+        Self::switch_history_signal(&ctxt, &self.history).await?;
+        Ok(())
+    }
+
+    #[zbus(property)]
+    fn active(&self) -> bool {
+        true
+    }
+    #[zbus(property)]
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_owned()
+    }
+
+    /// History was Modified
+    #[zbus(signal)]
+    async fn update(
+        ctxt: &SignalContext<'_>,
+        action: &str,
+        target: &str,
+        position: u64,
+    ) -> zbus::Result<()>;
+    /// Current History was Switched
+    #[zbus(signal, name = "SwitchHistory")]
+    async fn switch_history_signal(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+}
+
+/// Serve GPaste Interface on Session Bus and Forward Daemon Events
+pub async fn serve(daemon: Daemon, history: String) -> zbus::Result<Connection> {
+    let mut events = daemon.subscribe();
+    let conn = zbus::connection::Builder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, GPaste::new(daemon, history))?
+        .build()
+        .await?;
+    let iface = conn
+        .object_server()
+        .interface::<_, GPaste>(OBJECT_PATH)
+        .await?;
+    tokio::spawn(async move {
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            let history = iface.get().await.history.clone();
+            let changed = match event {
+                Event::Copied { group, .. } | Event::Deleted { group, .. } => group == history,
+                Event::Owned { .. } => false,
+                Event::Stopped => break,
+            };
+            if !changed {
+                continue;
+            }
+            let ctxt = iface.signal_context();
+            if let Err(err) = GPaste::update(ctxt, "REPLACE", "ALL", 0).await {
+                log::error!("failed to emit gpaste update: {err:?}");
+            }
+        }
+    });
+    Ok(conn)
+}
@@ -0,0 +1,219 @@
+//! Optional HTTP API for Tools without Unix-Socket Support
+
+use std::net::SocketAddr;
+
+use axum::extract::{Path, Query, Request as HttpRequest, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response as HttpResponse};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use tokio::net::TcpListener;
+
+use crate::clipboard::Entry;
+use crate::daemon::Daemon;
+use crate::message::{Grp, Request, Response, Wipe};
+
+fn _listen() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 7464))
+}
+
+/// HTTP Listener Configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct HttpConfig {
+    #[serde(default = "_listen")]
+    pub listen: SocketAddr,
+    pub token: String,
+}
+
+/// Group Selection Query Parameters
+#[derive(Debug, Deserialize)]
+struct GroupQuery {
+    group: Grp,
+    length: Option<usize>,
+}
+
+/// Copy Request Body
+#[derive(Debug, Deserialize)]
+struct CopyBody {
+    text: String,
+    mime: Option<String>,
+    group: Grp,
+    #[serde(default)]
+    primary: bool,
+}
+
+/// Shared Handler State
+#[derive(Clone)]
+struct Api {
+    daemon: Daemon,
+    token: String,
+}
+
+impl Api {
+    /// Process Request against Daemon and Convert into HTTP Response
+    async fn request(&self, request: Request) -> HttpResponse {
+        match self.daemon.process_blocking(request).await {
+            Ok(Response::Ok) => StatusCode::NO_CONTENT.into_response(),
+            Ok(Response::Error { error }) => (error_status(&error), error).into_response(),
+            Ok(Response::Groups { groups }) => Json(groups).into_response(),
+            Ok(Response::GroupStats { stats }) => Json(stats).into_response(),
+            Ok(Response::Labels { labels }) => Json(labels).into_response(),
+            Ok(Response::Previews { previews, .. }) => Json(previews).into_response(),
+            Ok(Response::Unchanged { .. }) => StatusCode::NOT_MODIFIED.into_response(),
+            Ok(Response::Changes { changes }) => Json(changes).into_response(),
+            Ok(Response::Reindexed { groups }) => Json(groups).into_response(),
+            Ok(Response::Entry {
+                entry,
+                index,
+                group,
+                revision,
+            }) => Json(serde_json::json!({
+                "index": index,
+                "group": group,
+                "revision": revision,
+                "entry": entry,
+            }))
+            .into_response(),
+            Ok(Response::Conflict {
+                index,
+                group,
+                revision,
+            }) => (
+                StatusCode::CONFLICT,
+                Json(serde_json::json!({"index": index, "group": group, "revision": revision})),
+            )
+                .into_response(),
+            Ok(Response::Status { status }) => Json(status).into_response(),
+            Ok(Response::Report { report }) => Json(report).into_response(),
+            Ok(Response::Matches { matches }) => Json(matches).into_response(),
+            Ok(Response::Event { event }) => Json(event).into_response(),
+            Ok(Response::Active {
+                clipboard, primary, ..
+            }) => Json(serde_json::json!({"clipboard": clipboard, "primary": primary}))
+                .into_response(),
+            Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{err:?}")).into_response(),
+        }
+    }
+}
+
+/// HTTP Status Matching a Daemon Error Message
+fn error_status(error: &str) -> StatusCode {
+    if error.starts_with("No Such Index") || error.contains("does not exist") {
+        return StatusCode::NOT_FOUND;
+    }
+    match error.contains("Read-Only") {
+        true => StatusCode::FORBIDDEN,
+        false => StatusCode::BAD_REQUEST,
+    }
+}
+
+/// Compare Tokens without Leaking the Matching Prefix Length through Timing
+fn token_matches(given: &str, token: &str) -> bool {
+    let (given, token) = (given.as_bytes(), token.as_bytes());
+    let diff = given
+        .iter()
+        .zip(token)
+        .fold(given.len() ^ token.len(), |acc, (a, b)| {
+            acc | (a ^ b) as usize
+        });
+    std::hint::black_box(diff) == 0
+}
+
+/// Reject Requests without Valid Bearer Token
+async fn authenticate(State(api): State<Api>, request: HttpRequest, next: Next) -> HttpResponse {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    match token.is_some_and(|token| token_matches(token, &api.token)) {
+        true => next.run(request).await,
+        false => StatusCode::UNAUTHORIZED.into_response(),
+    }
+}
+
+async fn groups(State(api): State<Api>) -> HttpResponse {
+    api.request(Request::Groups).await
+}
+
+async fn entries(State(api): State<Api>, Query(query): Query<GroupQuery>) -> HttpResponse {
+    api.request(Request::List {
+        length: query.length.unwrap_or(usize::MAX),
+        group: query.group,
+        prefer_group: false,
+        etag: None,
+    })
+    .await
+}
+
+async fn entry(
+    State(api): State<Api>,
+    Path(index): Path<usize>,
+    Query(query): Query<GroupQuery>,
+) -> HttpResponse {
+    api.request(Request::Find {
+        index: Some(index),
+        group: query.group,
+    })
+    .await
+}
+
+async fn copy(State(api): State<Api>, Json(body): Json<CopyBody>) -> HttpResponse {
+    api.request(Request::Copy {
+        entry: Entry::text(body.text, body.mime),
+        primary: body.primary,
+        group: body.group,
+        index: None,
+        force: false,
+        create: true,
+        revision: None,
+    })
+    .await
+}
+
+async fn remove(
+    State(api): State<Api>,
+    Path(index): Path<usize>,
+    Query(query): Query<GroupQuery>,
+) -> HttpResponse {
+    api.request(Request::Wipe {
+        wipe: Wipe::Single { index },
+        group: query.group,
+    })
+    .await
+}
+
+/// Serve HTTP API until Daemon Exits
+pub async fn serve(daemon: Daemon, config: HttpConfig) -> std::io::Result<()> {
+    if config.token.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "http api requires a non-empty token",
+        ));
+    }
+    if !config.listen.ip().is_loopback() {
+        log::warn!(
+            "http api listening on non-loopback address {}",
+            config.listen
+        );
+    }
+    let stopped = daemon.clone();
+    let api = Api {
+        daemon,
+        token: config.token,
+    };
+    let app = Router::new()
+        .route("/groups", get(groups))
+        .route("/entries", get(entries))
+        .route("/entries/:id", get(entry).delete(remove))
+        .route("/copy", post(copy))
+        .layer(middleware::from_fn_with_state(api.clone(), authenticate))
+        .with_state(api);
+    let listener = TcpListener::bind(config.listen).await?;
+    log::info!("http api listening on {}", config.listen);
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move { stopped.stopped().await })
+        .await
+}
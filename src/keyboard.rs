@@ -0,0 +1,28 @@
+//! Type Text into the Focused Window using Virtual Keyboard Tools
+
+use std::io;
+use std::process::Command;
+
+/// Supported Virtual Keyboard Tools (Tried in Order)
+static TOOLS: &[(&str, &[&str])] = &[("wtype", &["--"]), ("ydotool", &["type", "--"])];
+
+/// Type Text into Focused Surface with First Available Tool
+pub fn type_text(text: &str, tool: Option<&str>) -> io::Result<()> {
+    let tools = TOOLS
+        .iter()
+        .filter(|(name, _)| tool.map(|t| t == *name).unwrap_or(true));
+    for (name, args) in tools {
+        log::debug!("typing {} chars using {name}", text.chars().count());
+        match Command::new(name).args(*args).arg(text).status() {
+            Ok(status) if status.success() => return Ok(()),
+            Ok(status) => return Err(io::Error::other(format!("{name} exited with {status}"))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    let names: Vec<_> = TOOLS.iter().map(|(name, _)| *name).collect();
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("no virtual keyboard tool found (install one of {names:?})"),
+    ))
+}
@@ -0,0 +1,75 @@
+//! Customizable Clipboard Manager for Wayland
+//!
+//! The daemon can be embedded in other programs via [`daemon::Daemon`],
+//! and driven over its socket protocol using [`client::Client`].
+
+pub mod backend;
+pub mod calc;
+pub mod client;
+pub mod clipboard;
+pub mod clipfs;
+pub mod config;
+pub mod daemon;
+pub mod dataset;
+pub mod focus;
+mod fuzzy;
+mod gpaste;
+mod http;
+pub mod keyboard;
+pub mod message;
+pub mod mime;
+mod notify;
+pub mod prompt;
+pub mod provider;
+pub mod report;
+mod script;
+pub mod secret;
+mod spill;
+pub mod sync;
+pub mod table;
+pub mod template;
+pub mod urilist;
+mod urlclean;
+
+use std::sync::RwLock;
+
+pub static XDG_PREFIX: &'static str = "wclipd";
+pub static DEFAULT_SOCK: &'static str = "daemon.sock";
+pub static DEFAULT_CONFIG: &'static str = "config.yaml";
+pub static DEFAULT_DISK_STORE: &'static str = "db";
+pub static DEFAULT_REDB_STORE: &str = "history.redb";
+pub static DEFAULT_JSONL_STORE: &str = "history";
+pub static PROFILE_ENV: &'static str = "WCLIPD_PROFILE";
+pub static NAMESPACE_ENV: &'static str = "WCLIPD_NAMESPACE";
+
+static PROFILE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Select Profile used to Namespace Socket, Config and Storage Paths
+pub fn set_profile(profile: Option<String>) {
+    *PROFILE.write().expect("profile lock") = profile;
+}
+
+/// Currently Selected Profile (None for the Default)
+pub fn profile() -> Option<String> {
+    PROFILE.read().expect("profile lock").clone()
+}
+
+/// Hostname of the Current Machine
+pub fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    let ok = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) } == 0;
+    let len = buf.iter().position(|b| *b == 0).unwrap_or(buf.len());
+    let name = String::from_utf8_lossy(&buf[..len]).into_owned();
+    match ok && !name.is_empty() {
+        true => name,
+        false => "localhost".to_owned(),
+    }
+}
+
+/// XDG Base Directories for the Selected Profile
+pub fn xdg_dirs() -> Result<xdg::BaseDirectories, xdg::BaseDirectoriesError> {
+    match profile() {
+        Some(profile) => xdg::BaseDirectories::with_profile(XDG_PREFIX, profile),
+        None => xdg::BaseDirectories::with_prefix(XDG_PREFIX),
+    }
+}
@@ -1,32 +1,32 @@
-use std::fs::read_to_string;
-use std::io::{self, stdin, stdout, Read, Write};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs::{read_to_string, OpenOptions};
+use std::io::{self, stdin, stdout, IsTerminal, Read, Write};
 use std::path::PathBuf;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use clap::{Args, Parser, Subcommand};
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand};
+use clap_complete::engine::{ArgValueCompleter, CompletionCandidate};
+use clap_complete::env::CompleteEnv;
+use log::LevelFilter;
+use serde::Serialize;
+use terminal_size::Width;
 use thiserror::Error;
 use wayland_clipboard_listener::{WlClipboardListenerError, WlClipboardPasteStream, WlListenType};
 
-mod backend;
-mod client;
-mod clipboard;
-mod config;
-mod daemon;
-mod message;
-mod mime;
-mod table;
-
-use crate::client::{Client, ClientError};
-use crate::clipboard::{ClipBody, Entry};
-use crate::config::Config;
-use crate::daemon::{Daemon, DaemonError};
-use crate::message::Wipe;
-use crate::table::*;
-
-static XDG_PREFIX: &'static str = "wclipd";
-static DEFAULT_SOCK: &'static str = "daemon.sock";
-static DEFAULT_CONFIG: &'static str = "config.yaml";
-static DEFAULT_DISK_STORE: &'static str = "db";
+use wclipd::backend::GroupColor;
+use wclipd::client::{Client, ClientError, CopyOptions};
+use wclipd::clipboard::{ClipBody, Entry, Preview, PreviewColumn, Selection, Source};
+use wclipd::config::{Config, LogConfig, Newline, Umask};
+use wclipd::daemon::{other_managers, Daemon, DaemonError};
+use wclipd::dataset::{self, SetFormat};
+use wclipd::message::{
+    Event, GroupMatches, GroupSort, Owner, Response, Status, Wipe, PROTOCOL_VERSION,
+};
+use wclipd::provider::{ClipboardProvider, Wayland};
+use wclipd::table::*;
+use wclipd::{calc, clipfs, keyboard, mime, prompt, secret, template};
+use wclipd::{xdg_dirs, DEFAULT_CONFIG, DEFAULT_SOCK, NAMESPACE_ENV, PROFILE_ENV};
 
 /// Possible CLI Errors
 #[derive(Debug, Error)]
@@ -35,6 +35,8 @@ pub enum CliError {
     ReadError(#[from] std::io::Error),
     #[error("Invalid Config")]
     ConfigError(#[from] serde_yaml::Error),
+    #[error("Serialize Error")]
+    SerializeError(#[from] serde_json::Error),
     #[error("Client Error")]
     ClientError(#[from] ClientError),
     #[error("Daemon Error")]
@@ -62,9 +64,15 @@ struct CopyArgs {
     /// Specific Index to Copy Into
     #[clap(short, long)]
     index: Option<usize>,
+    /// Replace an existing entry at the index
+    #[arg(long, requires = "index")]
+    force: bool,
     /// Specific Group To Copy Into
     #[clap(short, long)]
     group: Option<String>,
+    /// Create the group if it does not exist yet
+    #[arg(long, requires = "group")]
+    create: bool,
     /// Override the inferred MIME type
     #[arg(short = 't', long = "type")]
     mime: Option<String>,
@@ -74,32 +82,77 @@ struct CopyArgs {
     /// Clear Clipboard rather than copy anything
     #[arg(short, long, default_value_t = false)]
     clear: bool,
+    /// Attach a free-text note to the entry
+    #[arg(long)]
+    note: Option<String>,
+    /// Record the shell session (cwd, $TERM, tty, hostname) with the entry
+    #[arg(long)]
+    source: bool,
+    /// Tag the entry as a secret (masked when listed)
+    #[arg(long)]
+    secret: bool,
 }
 
 /// Arguments for Select Command
 #[derive(Debug, Clone, Args)]
 struct SelectArgs {
     /// Clipboard entry index within manager
-    entry_num: usize,
+    #[clap(required_unless_present = "query", conflicts_with = "query")]
+    #[arg(add = ArgValueCompleter::new(complete_indexes))]
+    entry_num: Option<usize>,
+    /// Recopy entry best fuzzy-matching the query instead of an index
+    #[clap(short = 'm', long = "match")]
+    query: Option<String>,
+    /// Pick the best match when the query is ambiguous
+    #[clap(long, requires = "query")]
+    first: bool,
     /// Copy to primary-selection
     #[arg(short, long, default_value_t = false)]
     primary: bool,
     /// Group to Select from
     #[clap(short, long)]
     group: Option<String>,
+    /// Copy without promoting the entry in recency ordering
+    #[arg(long, default_value_t = false)]
+    no_touch: bool,
 }
 
 /// Arguments for Paste Command
 #[derive(Debug, Clone, Args)]
 struct PasteArgs {
     /// Clipboard entry index within manager
+    #[arg(add = ArgValueCompleter::new(complete_indexes))]
     entry_num: Option<usize>,
     /// Do not append a newline character
-    #[arg(short, long)]
+    #[arg(short, long, conflicts_with = "newline")]
     no_newline: bool,
+    /// Always append a newline character (even when piped)
+    #[arg(long)]
+    newline: bool,
+    /// Strip exactly one trailing newline from the content
+    #[arg(long)]
+    chomp: bool,
+    /// Write exact entry bytes only (no newline, refuses binary to a terminal)
+    #[arg(long, conflicts_with_all = ["newline", "chomp"])]
+    raw: bool,
+    /// Allow writing binary content to a terminal in raw mode
+    #[arg(long, requires = "raw")]
+    force: bool,
+    /// Fill `{{placeholder}}` values in snippet (name=value)
+    #[arg(long, value_parser = parse_setting)]
+    fill: Vec<(String, String)>,
+    /// Prompt for unfilled `{{placeholder}}` values
+    #[arg(long)]
+    prompt: bool,
+    /// Join continued, wrapped or bulleted lines into a single line
+    #[arg(long, conflicts_with = "raw")]
+    join: bool,
     /// Instead of pasting, list offered types
     #[arg(short, long)]
     list_types: bool,
+    /// Encode text into a charset (`original` restores the captured one)
+    #[arg(long, conflicts_with = "raw", value_parser = parse_charset)]
+    charset: Option<String>,
     /// Paste from active clipboard instead of manager
     #[arg(short, long)]
     active: bool,
@@ -111,10 +164,19 @@ struct PasteArgs {
     group: Option<String>,
 }
 
+/// Parse Charset Label Known to `encoding_rs` (or `original`)
+fn parse_charset(s: &str) -> Result<String, String> {
+    match s == "original" || mime::encode_charset("", s).is_some() {
+        true => Ok(s.to_owned()),
+        false => Err(format!("unknown charset: {s:?}")),
+    }
+}
+
 /// Arguments for Select Command
 #[derive(Debug, Clone, Args)]
 struct EditArgs {
     /// Clipboard entry index within manager
+    #[arg(add = ArgValueCompleter::new(complete_indexes))]
     entry_num: Option<usize>,
     /// Copy to primary-selection after edit
     #[arg(short, long, default_value_t = false)]
@@ -124,12 +186,267 @@ struct EditArgs {
     group: Option<String>,
 }
 
+/// Arguments for Type Command
+#[derive(Debug, Clone, Args)]
+struct TypeArgs {
+    /// Clipboard entry index within manager
+    #[arg(add = ArgValueCompleter::new(complete_indexes))]
+    entry_num: Option<usize>,
+    /// Group to Type from
+    #[clap(short, long)]
+    group: Option<String>,
+    /// Virtual keyboard tool to use (wtype or ydotool)
+    #[clap(long, value_parser = ["wtype", "ydotool"])]
+    tool: Option<String>,
+}
+
+/// Arguments for Current Command
+#[derive(Debug, Clone, Args)]
+struct CurrentArgs {
+    /// Output format ({group}, {index}, {owner}, {mime}, {bytes}, {preview} or {preview:N})
+    #[clap(short = 'F', long, default_value = "{preview}")]
+    format: String,
+    /// Keep running and print a line whenever the clipboard changes
+    #[clap(short, long)]
+    follow: bool,
+    /// Show secrets instead of masking them
+    #[clap(long)]
+    reveal: bool,
+}
+
+/// Arguments for Watch Command
+#[derive(Debug, Clone, Args)]
+struct WatchArgs {
+    /// Only print entries added to these groups
+    #[clap(short, long)]
+    group: Vec<String>,
+    /// Only print entries captured from the live clipboard
+    #[clap(short, long)]
+    live: bool,
+    /// Print a one-line preview (`group<TAB>index<TAB>preview`) of each entry
+    #[clap(short, long, conflicts_with = "json")]
+    preview: bool,
+    /// Print each entry as a JSON object on its own line
+    #[clap(short, long)]
+    json: bool,
+    /// Separate entries with a NUL byte instead of a newline
+    #[clap(short = 'z', long)]
+    null: bool,
+    /// Show secrets instead of masking them in previews
+    #[clap(long)]
+    reveal: bool,
+}
+
+/// Render Entry using Format String Placeholders
+fn render_format(
+    format: &str,
+    owner: &Owner,
+    group: &str,
+    index: usize,
+    entry: &Entry,
+    length: usize,
+    reveal: bool,
+) -> String {
+    let mut out = String::new();
+    let mut rest = format;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}').map(|e| start + e) else {
+            break;
+        };
+        let (key, width) = match rest[start + 1..end].split_once(':') {
+            Some((key, width)) => (key, width.parse().ok()),
+            None => (&rest[start + 1..end], None),
+        };
+        match key {
+            "group" => out.push_str(group),
+            "index" => out.push_str(&index.to_string()),
+            "owner" => out.push_str(owner.name()),
+            "mime" => out.push_str(&entry.mime()),
+            "bytes" => out.push_str(&entry.as_bytes().len().to_string()),
+            "preview" if !reveal && secret::is_secret(entry) => {
+                out.push_str(&secret::mask(&entry.to_text()))
+            }
+            "preview" => out.push_str(&entry.preview(width.unwrap_or(length))),
+            _ => out.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Arguments for Import-Set Command
+#[derive(Debug, Clone, Args)]
+struct ImportSetArgs {
+    /// Data-set file to import
+    path: PathBuf,
+    /// File format (lines or emoji)
+    #[clap(short, long, default_value = "lines")]
+    format: SetFormat,
+    /// Read-only group to import into (defaults to the file name)
+    #[clap(short, long)]
+    group: Option<String>,
+}
+
+/// Arguments for Pick Command
+#[derive(Debug, Clone, Args)]
+struct PickArgs {
+    /// Group to pick from
+    #[clap(short, long)]
+    group: Option<String>,
+    /// Menu command to pick with (reads choices from stdin)
+    #[clap(short, long)]
+    menu: Option<String>,
+    /// Copy to primary-selection
+    #[arg(short, long, default_value_t = false)]
+    primary: bool,
+    /// Do not evaluate math expressions (or `=` prefixed picks)
+    #[arg(long, default_value_t = false)]
+    no_calc: bool,
+    /// Show secrets in the menu instead of masking them
+    #[arg(long)]
+    reveal: bool,
+}
+
+/// Arguments for Mount Command
+#[derive(Debug, Clone, Args)]
+struct MountArgs {
+    /// Directory to mount clipboard history on
+    path: PathBuf,
+}
+
+/// Arguments for Check Command
+#[derive(Debug, Clone, Args)]
+struct CheckArgs {
+    /// Print daemon status details as JSON
+    #[clap(short, long)]
+    json: bool,
+}
+
+/// Daemon Status Reported by Check (Doubles as Exit-Code)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum DaemonState {
+    Running = 0,
+    NotRunning = 1,
+    Stale = 2,
+    Mismatch = 3,
+}
+
+/// Group Management Subcommands
+#[derive(Debug, Clone, Subcommand)]
+enum GroupCommand {
+    /// Override group settings at runtime (e.g. `expiration=2d`)
+    Set {
+        /// Group to configure
+        name: String,
+        /// Settings as key=value pairs
+        #[clap(required = true, value_parser = parse_setting)]
+        settings: Vec<(String, String)>,
+    },
+}
+
+/// Store Maintenance Subcommands
+#[derive(Debug, Clone, Subcommand)]
+enum MaintenanceCommand {
+    /// Rebuild sequence counters and preview caches from stored records
+    Reindex {
+        /// Group to rebuild (all groups if omitted)
+        #[clap(short, long)]
+        group: Option<String>,
+    },
+}
+
+/// Completion Source Subcommands
+#[derive(Debug, Clone, Subcommand)]
+enum CompleteCommand {
+    /// Print entry indexes with short previews (`index<TAB>preview`)
+    Indexes {
+        /// Group to list indexes from
+        #[clap(short, long)]
+        group: Option<String>,
+        /// Preview max-length
+        #[clap(short, long, default_value_t = 40)]
+        length: usize,
+    },
+}
+
+/// Parse `key=value` Setting Pair
+fn parse_setting(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(k, v)| (k.trim().to_owned(), v.trim().to_owned()))
+        .ok_or_else(|| format!("expected key=value: {s:?}"))
+}
+
+/// Parse Profile Name (Used as a Directory Name)
+fn parse_profile(s: &str) -> Result<String, String> {
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    match !s.is_empty() && s.chars().all(valid) {
+        true => Ok(s.to_owned()),
+        false => Err(format!("invalid profile name: {s:?}")),
+    }
+}
+
+/// Arguments for Trace Command
+#[derive(Debug, Clone, Args)]
+struct TraceArgs {
+    /// Enable or disable per-request timing logs
+    #[arg(action = ArgAction::Set, value_parser = parse_toggle)]
+    enabled: bool,
+}
+
+/// Parse On/Off Style Boolean Toggle
+fn parse_toggle(s: &str) -> Result<bool, String> {
+    match s {
+        "on" | "true" | "yes" | "1" => Ok(true),
+        "off" | "false" | "no" | "0" => Ok(false),
+        _ => Err(format!("invalid toggle: {s:?}")),
+    }
+}
+
+/// Arguments for Info Command
+#[derive(Debug, Clone, Args)]
+struct InfoArgs {
+    /// Clipboard entry index within manager
+    #[arg(add = ArgValueCompleter::new(complete_indexes))]
+    entry_num: Option<usize>,
+    /// Group to Inspect from
+    #[clap(short, long)]
+    group: Option<String>,
+    /// Override Table Style
+    #[clap(short = 's', long)]
+    table_style: Option<Style>,
+}
+
 /// Arguments for List-Groups Command
 #[derive(Debug, Clone, Args)]
 struct ListArgs {
     /// Override Table Style
     #[clap(short = 's', long)]
     table_style: Option<Style>,
+    /// Sort groups by size, count or recent (ascending)
+    #[clap(long, default_value = "recent")]
+    sort: GroupSort,
+}
+
+/// Arguments for Stats Command
+#[derive(Debug, Clone, Args)]
+struct StatsArgs {
+    /// Groups to analyze (default group if empty)
+    groups: Vec<String>,
+    /// Analyze every group
+    #[clap(short, long, conflicts_with = "groups")]
+    all: bool,
+    /// Include domain, hour-of-day, mime and largest-entry breakdowns
+    #[clap(short, long)]
+    report: bool,
+    /// Print the full report as JSON
+    #[clap(short, long)]
+    json: bool,
+    /// Override Table Style
+    #[clap(short = 's', long)]
+    table_style: Option<Style>,
 }
 
 /// Arguments for Show Command
@@ -140,9 +457,52 @@ struct ShowArgs {
     /// Clipboard Preview Max-Length
     #[clap(short, long)]
     length: Option<usize>,
+    /// Do not truncate previews to fit terminal width
+    #[clap(short, long)]
+    no_truncate: bool,
     /// List All Groups if Specified
     #[clap(short, long)]
     all: bool,
+    /// Show time remaining until each entry expires
+    #[clap(short, long)]
+    expires: bool,
+    /// Only show entries whose note contains the given text
+    #[clap(long)]
+    note: Option<String>,
+    /// Only show entries captured from the given selection (clipboard/primary)
+    #[clap(long)]
+    selection: Option<Selection>,
+    /// Only show entries captured on the given host
+    #[clap(long)]
+    host: Option<String>,
+    /// Extra columns to show (expires/selection/host)
+    #[clap(long = "column")]
+    columns: Vec<PreviewColumn>,
+    /// Show secrets instead of masking them
+    #[clap(long)]
+    reveal: bool,
+    /// Override Table Style
+    #[clap(short = 's', long)]
+    table_style: Option<Style>,
+}
+
+/// Arguments for Search Command
+#[derive(Debug, Clone, Args)]
+struct SearchArgs {
+    /// Text to search for within entries (case-insensitive)
+    query: String,
+    /// Groups to search (defaults to the default group)
+    #[clap(short, long)]
+    group: Vec<String>,
+    /// Search all groups
+    #[clap(short, long, conflicts_with = "group")]
+    all: bool,
+    /// Clipboard Preview Max-Length
+    #[clap(short, long)]
+    length: Option<usize>,
+    /// Show secrets instead of masking them
+    #[clap(long)]
+    reveal: bool,
     /// Override Table Style
     #[clap(short = 's', long)]
     table_style: Option<Style>,
@@ -150,8 +510,12 @@ struct ShowArgs {
 
 #[derive(Debug, Clone, Args)]
 struct DeleteArgs {
-    /// Clipboard entry index within manager
-    entry_num: Option<usize>,
+    /// Entries to delete: an index, range (`2-5`) or list (`1,3,7-9`)
+    #[arg(add = ArgValueCompleter::new(complete_indexes))]
+    entries: Option<Wipe>,
+    /// Delete text entries containing the pattern (case-insensitive)
+    #[clap(short, long = "match", conflicts_with = "entries")]
+    pattern: Option<String>,
     /// Group to Delete From
     #[clap(short, long)]
     group: Option<String>,
@@ -160,6 +524,17 @@ struct DeleteArgs {
     clear: bool,
 }
 
+/// Arguments for Pin/Unpin Commands
+#[derive(Debug, Clone, Args)]
+struct PinArgs {
+    /// Clipboard entry index within manager
+    #[arg(add = ArgValueCompleter::new(complete_indexes))]
+    entry_num: usize,
+    /// Group containing the entry
+    #[clap(short, long)]
+    group: Option<String>,
+}
+
 /// Arguments for Daemon Command
 #[derive(Debug, Clone, Args)]
 struct DaemonArgs {
@@ -172,6 +547,21 @@ struct DaemonArgs {
     /// Fork and run in background
     #[clap(short, long)]
     background: bool,
+    /// Serve history without connecting to wayland (no capture/re-offer)
+    #[clap(long)]
+    headless: bool,
+    /// Working directory when running in background
+    #[clap(long, requires = "background")]
+    workdir: Option<String>,
+    /// File mode creation mask (octal) when running in background
+    #[clap(long, requires = "background")]
+    umask: Option<Umask>,
+    /// Redirect stdout/stderr to this file when running in background
+    #[clap(long, requires = "background")]
+    log_file: Option<String>,
+    /// Write and lock a PID file when running in background
+    #[clap(long, requires = "background")]
+    pid_file: Option<String>,
 }
 
 /// Valid CLI Command Actions
@@ -189,17 +579,53 @@ enum Command {
     /// Edit an existing entry
     #[clap(visible_alias = "e")]
     Edit(EditArgs),
+    /// Type an entry into the focused window
+    Type(TypeArgs),
+    /// Show details and statistics about an entry
+    #[clap(visible_alias = "i")]
+    Info(InfoArgs),
     /// Check current status of daemon
-    Check,
+    Check(CheckArgs),
+    /// Diagnose common setup problems and suggest fixes
+    Doctor,
     /// List clipboard groups
     #[clap(visible_alias = "l")]
     ListGroups(ListArgs),
     /// Show clipboard group entries within manager
     #[clap(visible_alias = "s")]
     Show(ShowArgs),
+    /// Search text entries across groups
+    Search(SearchArgs),
     /// Delete entry within manager
     #[clap(visible_alias = "d")]
     Delete(DeleteArgs),
+    /// Pin entry so it survives expiration and clears
+    Pin(PinArgs),
+    /// Unpin a previously pinned entry
+    Unpin(PinArgs),
+    /// Summarize history (top domains, busiest hours, biggest entries)
+    Stats(StatsArgs),
+    /// Import a data-set file (emoji, lines) into a read-only group
+    ImportSet(ImportSetArgs),
+    /// Pick an entry using a dmenu-style menu and re-copy it
+    Pick(PickArgs),
+    /// Print the current clipboard entry for status bars
+    Current(CurrentArgs),
+    /// Stream new entries to stdout as they are captured
+    Watch(WatchArgs),
+    /// Mount clipboard history as a filesystem
+    Mount(MountArgs),
+    /// Manage group configuration
+    #[clap(subcommand)]
+    Group(GroupCommand),
+    /// Repair the history store (e.g. after restoring a backup)
+    #[clap(subcommand)]
+    Maintenance(MaintenanceCommand),
+    /// Toggle daemon request tracing (on/off)
+    Trace(TraceArgs),
+    /// Print completion candidates for shells and pickers
+    #[clap(subcommand)]
+    Complete(CompleteCommand),
     /// Run clipboard manager daemon
     Daemon(DaemonArgs),
 }
@@ -215,26 +641,48 @@ struct Cli {
     /// Configuration for WClipD
     #[clap(short, long)]
     config: Option<PathBuf>,
+    /// Isolated profile with its own socket, config and storage
+    #[clap(long, global = true, env = PROFILE_ENV, value_parser = parse_profile)]
+    profile: Option<String>,
+    /// Confine requests to groups within this daemon-side namespace
+    #[clap(long, global = true, env = NAMESPACE_ENV)]
+    namespace: Option<String>,
+    /// Increase logging verbosity (-v debug, -vv trace)
+    #[clap(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+    /// Suppress informational output
+    #[clap(short, long, global = true)]
+    quiet: bool,
+    /// Stable, parse-friendly output for scripting
+    #[clap(long, global = true)]
+    porcelain: bool,
     /// WClipD Command
     #[clap(subcommand)]
     command: Command,
 }
 
 impl Cli {
-    /// Load Configuration and Overload Empty Cli Settings
-    fn load_config(&mut self) -> Result<Config, CliError> {
-        let path = self.config.clone().or_else(|| {
-            xdg::BaseDirectories::with_prefix(XDG_PREFIX)
+    /// Locate Configuration File for the Selected Profile
+    fn config_path(&self) -> Option<PathBuf> {
+        self.config.clone().or_else(|| {
+            xdg_dirs()
                 .expect("Failed to read xdg base dirs")
                 .find_config_file(DEFAULT_CONFIG)
-        });
-        let config = match path {
-            Some(path) => {
-                let config = read_to_string(path)?;
-                serde_yaml::from_str(&config)?
-            }
-            None => Config::default(),
-        };
+        })
+    }
+
+    /// Load Configuration and Overload Empty Cli Settings
+    fn load_config(&mut self) -> Result<Config, CliError> {
+        wclipd::set_profile(self.profile.clone());
+        let path = self.config_path();
+        let mut config = read_config(path.as_ref())?;
+        // switch to the profile selected within the default config
+        if let Some(profile) = config.profile.clone().filter(|_| self.profile.is_none()) {
+            self.profile = Some(parse_profile(&profile).map_err(CliError::ConflictError)?);
+            wclipd::set_profile(self.profile.clone());
+            // re-read so storage paths resolve within the profile
+            config = read_config(self.config_path().or(path).as_ref())?;
+        }
         self.socket = self.socket.clone().or(config.socket.clone());
         Ok(config)
     }
@@ -243,7 +691,7 @@ impl Cli {
     fn get_socket(&self) -> PathBuf {
         let path = match self.socket.as_ref() {
             Some(sock) => sock.to_owned(),
-            None => xdg::BaseDirectories::with_prefix(XDG_PREFIX)
+            None => xdg_dirs()
                 .expect("Failed to read xdg base dirs")
                 .place_runtime_file(DEFAULT_SOCK)
                 .expect("Failed to create daemon unix socket")
@@ -253,6 +701,20 @@ impl Cli {
         PathBuf::from(shellexpand::tilde(&path).to_string())
     }
 
+    /// Connect to Daemon and Declare Namespace (if any)
+    fn connect(&self) -> Result<Client, CliError> {
+        let mut client = Client::new(self.get_socket())?;
+        if let Some(namespace) = self.namespace.clone() {
+            client.hello(false, Some(namespace))?;
+        }
+        Ok(client)
+    }
+
+    /// Convert Timestamp to Unix Seconds for Porcelain Output
+    fn unix_time(&self, ts: SystemTime) -> u64 {
+        ts.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+    }
+
     ///Convert Timestamp to HumanTime
     fn human_time(&self, ts: SystemTime, now: &SystemTime) -> String {
         let since = now.duration_since(ts).unwrap_or_default();
@@ -260,10 +722,51 @@ impl Cli {
         humantime::format_duration(since).to_string()
     }
 
+    /// Render Group Name with its Label (Colored when Writing to a Terminal)
+    fn group_title(&self, group: &str, label: Option<&str>, color: Option<GroupColor>) -> String {
+        let title = match label {
+            Some(label) => format!("{group} [{label}]"),
+            None => group.to_owned(),
+        };
+        let colored = stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+        match color.filter(|_| colored) {
+            Some(color) => color.paint(&title),
+            None => title,
+        }
+    }
+
+    /// Convert Byte Count into Human Readable Size
+    fn human_size(&self, bytes: usize) -> String {
+        let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+        let mut size = bytes as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < units.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        match unit {
+            0 => format!("{bytes} B"),
+            _ => format!("{size:.1} {}", units[unit]),
+        }
+    }
+
+    /// Convert Expiration into Human Readable Time Remaining
+    fn human_expires(&self, expires: Option<SystemTime>, now: &SystemTime) -> String {
+        match expires {
+            None => "never".to_owned(),
+            Some(ts) => match ts.duration_since(*now) {
+                Ok(left) => {
+                    let left = Duration::from_secs(left.as_secs());
+                    humantime::format_duration(left).to_string()
+                }
+                Err(_) => "expired".to_owned(),
+            },
+        }
+    }
+
     /// Copy Command Handler
-    fn copy(&self, args: CopyArgs) -> Result<(), CliError> {
-        let path = self.get_socket();
-        let mut client = Client::new(path)?;
+    fn copy(&self, config: Config, args: CopyArgs) -> Result<(), CliError> {
+        let mut client = self.connect()?;
         if args.clear {
             if !args.text.is_empty() || args.file.is_some() {
                 return Err(CliError::ConflictError(
@@ -288,32 +791,52 @@ impl Cli {
                 }
             },
         };
-        log::debug!("sending entry {}", entry.preview(100));
-        client.copy(entry, args.primary, args.group, args.index)?;
+        let source = (args.source || config.copy.record_source).then(Source::current);
+        let entry = entry
+            .with_note(args.note)
+            .with_source(source)
+            .with_secret(args.secret);
+        match secret::is_secret(&entry) {
+            true => log::debug!("sending entry {}", secret::mask(&entry.to_text())),
+            false => log::debug!("sending entry {}", entry.preview(100)),
+        }
+        let options = CopyOptions {
+            primary: args.primary,
+            group: args.group,
+            index: args.index,
+            force: args.force && args.index.is_some(),
+            create: args.create,
+            revision: None,
+        };
+        client.copy_with(entry, options)?;
         Ok(())
     }
 
     /// Select Command Handler
     fn select(&self, args: SelectArgs) -> Result<(), CliError> {
-        let path = self.get_socket();
-        let mut client = Client::new(path)?;
-        client.select(args.entry_num, args.primary, args.group)?;
+        let mut client = self.connect()?;
+        match (args.entry_num, args.query) {
+            (Some(index), _) => client.select(index, args.primary, args.group, args.no_touch)?,
+            (None, Some(query)) => {
+                client.select_match(query, args.first, args.primary, args.group, args.no_touch)?
+            }
+            (None, None) => unreachable!("clap requires index or query"),
+        }
         Ok(())
     }
 
     /// Paste Command Handler
-    fn paste(&self, args: PasteArgs) -> Result<(), CliError> {
-        let path = self.get_socket();
-        let mut client = Client::new(path)?;
+    fn paste(&self, config: Config, args: PasteArgs) -> Result<(), CliError> {
+        let mut client = self.connect()?;
         // retrieve entry from active clipboard or manager
-        let entry = if args.active {
+        let mut entry = if args.active {
             let mut stream = WlClipboardPasteStream::init(WlListenType::ListenOnCopy)?;
             let Some(message) = stream.get_clipboard()? else {
                 return Err(CliError::Warning("no content in clipboard".to_owned()));
             };
             Entry::from(message)
         } else {
-            let (entry, _) = client.find(args.entry_num, args.group)?;
+            let (entry, ..) = client.find(args.entry_num, args.group)?;
             entry
         };
         // return warning if empty
@@ -325,109 +848,611 @@ impl Cli {
             for mime in entry.mime {
                 println!("{mime}");
             }
+            if let Some(charset) = entry.charset {
+                println!("text/plain;charset={charset}");
+            }
             return Ok(());
         }
         // avoid printing if not-text and `text-only` enabled
         if args.text_only && !entry.is_text() {
             return Err(CliError::Warning("not a text snippet".to_owned()));
         }
+        // substitute template placeholders when requested
+        if (args.prompt || !args.fill.is_empty()) && entry.is_text() {
+            let text = entry.to_text();
+            let mut values: HashMap<String, String> = args.fill.into_iter().collect();
+            if args.prompt {
+                for name in template::placeholders(&text) {
+                    if values.contains_key(&name) {
+                        continue;
+                    }
+                    eprint!("{name}: ");
+                    let mut value = String::new();
+                    stdin().read_line(&mut value)?;
+                    values.insert(name, value.trim_end_matches(['\r', '\n']).to_owned());
+                }
+            }
+            entry.body = ClipBody::Text(template::fill(&text, &values));
+        }
+        if args.join && entry.is_text() {
+            entry.body = ClipBody::Text(prompt::join(&entry.to_text()));
+        }
         // write output to stdout
         let mut out = stdout();
-        out.write(entry.as_bytes())?;
-        if !args.no_newline {
-            out.write(&['\n' as u8])?;
+        if args.raw {
+            if !entry.is_text() && out.is_terminal() && !args.force {
+                return Err(CliError::Warning(
+                    "refusing to write binary to a terminal (use --force)".to_owned(),
+                ));
+            }
+            out.write_all(entry.as_bytes())?;
+            return Ok(());
+        }
+        // text is written as utf-8 unless another charset is requested
+        let text = entry.is_text().then(|| entry.to_text());
+        let text = match (text, args.charset.as_deref()) {
+            (Some(text), Some(label)) => {
+                let label = match label {
+                    "original" => entry.charset.as_deref().unwrap_or("utf-8"),
+                    label => label,
+                };
+                let encoded = mime::encode_charset(&text, label);
+                Some(encoded.ok_or_else(|| {
+                    CliError::Warning(format!("entry has unknown charset: {label:?}"))
+                })?)
+            }
+            (text, _) => text.map(String::into_bytes),
+        };
+        let mut content = text.as_deref().unwrap_or(entry.as_bytes());
+        if args.chomp {
+            content = content.strip_suffix(b"\n").unwrap_or(content);
+            content = content.strip_suffix(b"\r").unwrap_or(content);
+        }
+        out.write_all(content)?;
+        let newline = match config.paste.newline {
+            _ if args.no_newline => false,
+            _ if args.newline => true,
+            Newline::Always => true,
+            Newline::Never => false,
+            Newline::Tty => out.is_terminal(),
+        };
+        if newline {
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// Type Entry into Focused Window Command Handler
+    fn type_entry(&self, args: TypeArgs) -> Result<(), CliError> {
+        let mut client = self.connect()?;
+        let (entry, ..) = client.find(args.entry_num, args.group)?;
+        if !entry.is_text() {
+            return Err(CliError::Warning("can only type text entries".to_owned()));
         }
+        let text = entry.to_text();
+        keyboard::type_text(&text, args.tool.as_deref())?;
         Ok(())
     }
 
     /// Edit an Existing Clipboard Entry
     fn edit(&self, args: EditArgs) -> Result<(), CliError> {
-        let path = self.get_socket();
-        let mut client = Client::new(path)?;
+        let mut client = self.connect()?;
         // retrieve entry and confirm entry is text
-        let (mut entry, index) = client.find(args.entry_num, args.group.clone())?;
+        let (mut entry, index, group, mut revision) = client.find(args.entry_num, args.group)?;
         if !entry.is_text() {
             return Err(CliError::EditError("Can Only Edit Text".to_owned()));
         }
         // edit contents and move back to text
-        let data = edit::edit_bytes(entry.as_bytes())?;
-        let text = String::from_utf8(data)
-            .map_err(|e| CliError::EditError(format!("failed to read clip: {e:?}")))?;
-        entry.body = ClipBody::Text(text);
-        // resubmit entry to clipboard
-        client.copy(entry, args.primary, args.group, Some(index))?;
+        let mut text = edit_text(&entry.to_text())?;
+        loop {
+            entry.body = ClipBody::Text(text.clone());
+            // write back to the slot that was found (even when editing the latest)
+            let conflict = match client.overwrite(
+                entry.clone(),
+                args.primary,
+                Some(group.clone()),
+                index,
+                Some(revision),
+            ) {
+                Ok(()) => return Ok(()),
+                Err(ClientError::Unexpected(Response::Conflict { revision, .. })) => revision,
+                Err(err) => return Err(err.into()),
+            };
+            // entry was replaced (or deleted) while the editor was open
+            eprint!("Entry {index} changed while editing. [o]verwrite, [m]erge or [a]bort? ");
+            let mut answer = String::new();
+            stdin().read_line(&mut answer)?;
+            match (answer.trim(), conflict) {
+                ("o" | "overwrite", Some(latest)) => revision = latest,
+                ("m" | "merge", Some(_)) => {
+                    let (latest, _, _, latest_rev) =
+                        client.find(Some(index), Some(group.clone()))?;
+                    text = edit_text(&merge_markers(&latest.to_text(), &text))?;
+                    revision = latest_rev;
+                }
+                ("o" | "overwrite" | "m" | "merge", None) => {
+                    return Err(CliError::EditError(format!("Entry {index} was Deleted")));
+                }
+                _ => return Err(CliError::EditError("Edit Aborted".to_owned())),
+            }
+        }
+    }
+
+    /// Entry Details and Statistics Command Handler
+    fn info(&self, mut config: Config, args: InfoArgs) -> Result<(), CliError> {
+        // override settings
+        config.list.table.style = args.table_style.unwrap_or(config.list.table.style);
+        // retrieve entry and calculate statistics
+        let mut client = self.connect()?;
+        let (entry, index, group, _) = client.find(args.entry_num, args.group)?;
+        let stats = entry.stats();
+        let preview = client
+            .list(0, Some(group.clone()))?
+            .into_iter()
+            .find(|p| p.index == index);
+        let expires = preview.as_ref().and_then(|p| p.expires);
+        let pinned = preview.is_some_and(|p| p.pinned);
+        let count = |n: Option<usize>| n.map(|n| n.to_string()).unwrap_or_else(|| "-".to_owned());
+        let mut data = vec![
+            vec!["index".to_owned(), index.to_string()],
+            vec!["mime".to_owned(), entry.mime()],
+            vec!["bytes".to_owned(), stats.bytes.to_string()],
+            vec!["chars".to_owned(), count(stats.chars)],
+            vec!["words".to_owned(), count(stats.words)],
+            vec!["lines".to_owned(), count(stats.lines)],
+            vec![
+                "entropy".to_owned(),
+                format!("{:.2} bits/byte", stats.entropy),
+            ],
+            vec![
+                "expires".to_owned(),
+                self.human_expires(expires, &SystemTime::now()),
+            ],
+        ];
+        if pinned {
+            data.push(vec!["pinned".to_owned(), "yes".to_owned()]);
+        }
+        if let Some(note) = entry.note {
+            data.push(vec!["note".to_owned(), note]);
+        }
+        if let Some(source) = entry.source {
+            let fields = [
+                ("cwd", source.cwd),
+                ("term", source.term),
+                ("tty", source.tty),
+                ("host", source.hostname),
+            ];
+            for (name, value) in fields {
+                if let Some(value) = value {
+                    data.push(vec![name.to_owned(), value]);
+                }
+            }
+        }
+        // print data table
+        let mut table = AsciiTable::new(Some(group), config.list.table.style);
+        table.align_column(0, Align::Right);
+        table.print(data);
         Ok(())
     }
 
+    /// Determine Daemon State and Retrieve its Status (if Reachable)
+    fn daemon_state(&self) -> (DaemonState, Option<Status>) {
+        let path = self.get_socket();
+        match Client::new(path.clone()) {
+            Ok(mut client) => match client.status() {
+                Ok(status) if status.protocol == PROTOCOL_VERSION => {
+                    (DaemonState::Running, Some(status))
+                }
+                Ok(status) => (DaemonState::Mismatch, Some(status)),
+                Err(_) if Client::new(path.clone()).and_then(|mut c| c.ping()).is_ok() => {
+                    (DaemonState::Mismatch, None)
+                }
+                Err(_) => (DaemonState::Stale, None),
+            },
+            Err(_) if path.exists() => (DaemonState::Stale, None),
+            Err(_) => (DaemonState::NotRunning, None),
+        }
+    }
+
     /// Check-Daemon Command Handler
-    fn check(&self) -> Result<(), CliError> {
+    fn check(&self, args: CheckArgs) -> Result<(), CliError> {
+        let path = self.get_socket();
+        let (state, status) = self.daemon_state();
+        if args.json {
+            let pid = std::fs::read_to_string(path.with_extension("pid"))
+                .ok()
+                .and_then(|pid| pid.trim().parse::<u32>().ok());
+            let report = serde_json::json!({
+                "state": state,
+                "socket": path,
+                "profile": wclipd::profile(),
+                "pid": pid,
+                "protocol": PROTOCOL_VERSION,
+                "status": status,
+            });
+            println!("{report}");
+        } else if self.porcelain {
+            let state = serde_json::to_value(state)?;
+            println!("{}", state.as_str().unwrap_or_default());
+        }
+        // surface failed startup checks even when the daemon is running
+        if !args.json {
+            let health = status.iter().flat_map(|s| s.health.iter());
+            for diag in health.filter(|d| !d.ok || d.detail.is_some()) {
+                let detail = diag.detail.as_deref().unwrap_or("failed");
+                eprintln!("Warning, {}: {detail}", diag.check);
+            }
+        }
+        match state {
+            DaemonState::Running => Ok(()),
+            state => std::process::exit(state as i32),
+        }
+    }
+
+    /// Doctor Command Handler
+    fn doctor(&self) -> Result<(), CliError> {
         let path = self.get_socket();
-        if let Ok(mut client) = Client::new(path) {
-            if let Ok(_) = client.ping() {
-                return Ok(());
+        let (state, status) = self.daemon_state();
+        // (passed, finding, suggested fix)
+        let mut findings: Vec<(bool, String, Option<String>)> = vec![];
+        let restart = Some("restart it with `wclipd daemon --kill`".to_owned());
+        findings.push(match (state, status.as_ref()) {
+            (DaemonState::Running, Some(status)) => (
+                true,
+                format!("daemon v{} running at {path:?}", status.version),
+                None,
+            ),
+            (DaemonState::NotRunning, _) => (
+                false,
+                "daemon is not running".to_owned(),
+                Some("start it with `wclipd daemon --background`".to_owned()),
+            ),
+            (DaemonState::Stale, _) => (
+                false,
+                format!("stale socket left behind at {path:?}"),
+                Some(format!(
+                    "remove it (`rm {path:?}`) and start the daemon again"
+                )),
+            ),
+            (_, status) => {
+                let protocol = status.map(|s| s.protocol.to_string());
+                let protocol = protocol.unwrap_or_else(|| "unknown".to_owned());
+                let finding =
+                    format!("daemon speaks protocol {protocol}, client expects {PROTOCOL_VERSION}");
+                (false, finding, restart.clone())
+            }
+        });
+        let client_version = env!("CARGO_PKG_VERSION");
+        if let Some(status) = status.as_ref().filter(|s| s.version != client_version) {
+            let finding = format!(
+                "daemon v{} differs from client v{client_version}",
+                status.version
+            );
+            findings.push((false, finding, restart));
+        }
+        let health = status.iter().flat_map(|s| s.health.iter());
+        for diag in health.filter(|d| !d.ok) {
+            let detail = diag.detail.as_deref().unwrap_or("failed");
+            findings.push((false, format!("daemon {}: {detail}", diag.check), None));
+        }
+        let headless = status.as_ref().is_some_and(|s| s.headless);
+        findings.push(match std::env::var_os("WAYLAND_DISPLAY") {
+            _ if headless => (
+                true,
+                "daemon is headless (no compositor needed)".to_owned(),
+                None,
+            ),
+            None => (
+                false,
+                "WAYLAND_DISPLAY is not set".to_owned(),
+                Some("run inside a wayland session or use `wclipd daemon --headless`".to_owned()),
+            ),
+            Some(_) => match Wayland.listen(Selection::Clipboard) {
+                Ok(_) => (true, "compositor supports data-control".to_owned(), None),
+                Err(err) => (
+                    false,
+                    format!("compositor lacks data-control support ({err:?})"),
+                    Some("use a compositor with wlr/ext data-control or `--headless`".to_owned()),
+                ),
+            },
+        });
+        let (persist, managers): (Vec<_>, Vec<_>) = other_managers()
+            .into_iter()
+            .map(|(name, pid)| format!("{name} (pid {pid})"))
+            .partition(|m| m.starts_with("wl-clip-persist"));
+        if !managers.is_empty() {
+            findings.push((
+                false,
+                format!("other clipboard managers running: {}", managers.join(", ")),
+                Some("stop them or set `daemon.other_managers` to 'claim' or 'refuse'".to_owned()),
+            ));
+        }
+        if !persist.is_empty() {
+            findings.push((
+                false,
+                format!("persist daemon running: {}", persist.join(", ")),
+                Some("stop it. `daemon.recopy_live` already keeps selections alive".to_owned()),
+            ));
+        }
+        if managers.is_empty() && persist.is_empty() {
+            findings.push((true, "no other clipboard managers running".to_owned(), None));
+        }
+        for (passed, finding, fix) in findings.iter() {
+            println!("{} {finding}", if *passed { "✓" } else { "✗" });
+            if let Some(fix) = fix {
+                println!("    fix: {fix}");
             }
         }
-        std::process::exit(1)
+        match findings.iter().all(|(passed, ..)| *passed) {
+            true => Ok(()),
+            false => std::process::exit(1),
+        }
     }
 
     /// List Populated Groups within Backend
     fn list_groups(&self, mut config: Config, args: ListArgs) -> Result<(), CliError> {
         // override settings
         config.list.table.style = args.table_style.unwrap_or(config.list.table.style);
-        // connect to client and list non-empty groups
-        let path = self.get_socket();
-        let mut client = Client::new(path)?;
-        let mut groups: Vec<(String, usize, SystemTime)> = client
-            .groups()?
-            .into_iter()
-            .filter_map(|group| {
-                let previews = client.list(0, Some(group.clone())).ok()?;
-                let latest = previews.iter().map(|p| p.last_used).max();
-                match previews.is_empty() {
-                    true => None,
-                    false => Some((group, previews.len(), latest.unwrap())),
-                }
-            })
-            .collect();
-        groups.sort_by_key(|(_, _, time)| time.clone());
-        // print data table
+        // connect to client and collect metrics (configured groups may be empty)
+        let mut client = self.connect()?;
+        let mut stats = client.group_stats()?;
+        args.sort.sort(&mut stats);
+        // print tab-separated records in porcelain mode
+        if self.porcelain {
+            for s in stats {
+                let last = s.latest.map(|t| self.unix_time(t).to_string());
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    s.group,
+                    s.entries,
+                    last.unwrap_or_else(|| "-".to_owned()),
+                    s.bytes
+                );
+            }
+            return Ok(());
+        }
+        // print data table with totals across all groups
         let now = SystemTime::now();
-        let data = groups
+        let entries: usize = stats.iter().map(|s| s.entries).sum();
+        let bytes: usize = stats.iter().map(|s| s.bytes).sum();
+        let mut data: Vec<Vec<String>> = stats
             .into_iter()
-            .map(|(g, n, last)| vec![format!("{g} ({n})"), self.human_time(last, &now)])
+            .map(|s| {
+                let last = match s.latest {
+                    Some(last) => self.human_time(last, &now),
+                    None => "(empty)".to_owned(),
+                };
+                let title = self.group_title(&s.group, s.label.as_deref(), s.color);
+                vec![
+                    format!("{title} ({})", s.entries),
+                    self.human_size(s.bytes),
+                    last,
+                ]
+            })
             .collect();
+        data.push(vec![
+            format!("total ({entries})"),
+            self.human_size(bytes),
+            String::new(),
+        ]);
         let table = AsciiTable::new(None, config.list.table.style);
         table.print(data);
         Ok(())
     }
 
+    /// History Statistics Command Handler
+    fn stats(&self, mut config: Config, args: StatsArgs) -> Result<(), CliError> {
+        config.list.table.style = args.table_style.unwrap_or(config.list.table.style);
+        let mut client = self.connect()?;
+        let groups = match args.all {
+            true => client.groups()?,
+            false => args.groups,
+        };
+        let report = client.report(groups)?;
+        if args.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            return Ok(());
+        }
+        let style = config.list.table.style;
+        let summary = vec![
+            vec!["entries".to_owned(), report.entries.to_string()],
+            vec!["bytes".to_owned(), self.human_size(report.bytes)],
+        ];
+        AsciiTable::new(Some("history".to_owned()), style.clone()).print(summary);
+        if !args.report {
+            return Ok(());
+        }
+        // ranked sections are skipped when there is nothing to show
+        let counts = |rows: &[(String, usize)]| -> Table {
+            rows.iter()
+                .map(|(name, count)| vec![name.clone(), count.to_string()])
+                .collect()
+        };
+        let sections = [
+            ("domains", counts(&report.domains)),
+            ("mimes", counts(&report.mimes)),
+        ];
+        for (title, table) in sections.into_iter().filter(|(_, t)| !t.is_empty()) {
+            println!();
+            let mut ascii = AsciiTable::new(Some(title.to_owned()), style.clone());
+            ascii.align_column(1, Align::Right);
+            ascii.print(table);
+        }
+        // hour-of-day histogram scaled to the busiest hour
+        let busiest = report
+            .hours
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or_default()
+            .max(1);
+        let hours: Table = report
+            .hours
+            .iter()
+            .enumerate()
+            .map(|(hour, count)| {
+                let bar = "█".repeat((count * 30).div_ceil(busiest));
+                vec![format!("{hour:02}:00"), count.to_string(), bar]
+            })
+            .collect();
+        println!();
+        let mut ascii = AsciiTable::new(Some("hours".to_owned()), style.clone());
+        ascii.align_column(1, Align::Right);
+        ascii.print(hours);
+        if report.largest.is_empty() {
+            return Ok(());
+        }
+        let largest: Table = report
+            .largest
+            .into_iter()
+            .map(|e| {
+                let size = self.human_size(e.bytes);
+                vec![format!("{}:{}", e.group, e.index), size, e.mime, e.preview]
+            })
+            .collect();
+        println!();
+        let mut ascii = AsciiTable::new(Some("largest".to_owned()), style);
+        ascii.align_column(1, Align::Right);
+        if let Some((Width(w), _)) = terminal_size::terminal_size() {
+            ascii.fit_column(3, w as usize);
+        }
+        ascii.print(largest);
+        Ok(())
+    }
+
     /// Show Clipboard Entry Previews Command Handler
     fn show(&self, mut config: Config, mut args: ShowArgs) -> Result<(), CliError> {
-        // override daemon cli arguments
-        config.list.preview_length = args.length.unwrap_or(config.list.preview_length);
+        // override daemon cli arguments (explicit lengths ignore group settings)
+        let prefer_group = args.length.is_none() && !args.no_truncate;
+        config.list.preview_length = match args.no_truncate {
+            true => usize::MAX,
+            false => args.length.unwrap_or(config.list.preview_length),
+        };
         config.list.table.style = args.table_style.unwrap_or(config.list.table.style);
+        let width = terminal_size::terminal_size()
+            .filter(|_| !args.no_truncate && !self.porcelain)
+            .map(|(Width(w), _)| w as usize);
         // complete rendering of requested lists
-        let path = self.get_socket();
-        let mut client = Client::new(path)?;
+        let mut client = self.connect()?;
         if args.groups.is_empty() {
+            // the daemon stores primary captures in the live group unless told otherwise
+            let default = match args.selection {
+                Some(Selection::Primary) => {
+                    let daemon = &config.daemon;
+                    daemon
+                        .primary_backend
+                        .clone()
+                        .or(daemon.live_backend.clone())
+                }
+                _ => None,
+            };
             args.groups = args.all.then(|| client.groups()).unwrap_or_else(|| {
-                Ok(vec![config
-                    .list
-                    .default_group
+                Ok(vec![default
+                    .or(config.list.default_group)
                     .unwrap_or_else(|| "default".to_owned())])
             })?;
         }
         let now = SystemTime::now();
         let mut printed = 0;
+        let expires = args.expires || args.columns.contains(&PreviewColumn::Expires);
+        let show_host = args.columns.contains(&PreviewColumn::Host);
+        // mark records currently offered on the clipboard and primary selection
+        let (clipboard, primary) = client.active()?;
+        let owns = |owner: &Owner, group: &str, index: usize| match owner {
+            Owner::Daemon { group: g, index: i } => g == group && *i == index,
+            _ => false,
+        };
+        let labels: HashMap<_, _> = match self.porcelain {
+            true => HashMap::new(),
+            false => client
+                .labels()?
+                .into_iter()
+                .map(|l| (l.group, (l.label, l.color)))
+                .collect(),
+        };
         for group in args.groups {
             // generate preview into table structure
-            let mut previews = client.list(config.list.preview_length, Some(group.clone()))?;
+            let length = config.list.preview_length;
+            let mut previews = match prefer_group {
+                true => client.list_preferred(length, Some(group.clone()))?,
+                false => client.list(length, Some(group.clone()))?,
+            };
             previews.sort_by_key(|p| p.last_used);
+            if let Some(search) = args.note.as_ref().map(|s| s.to_lowercase()) {
+                previews.retain(|p| {
+                    p.note
+                        .as_ref()
+                        .map(|n| n.to_lowercase().contains(&search))
+                        .unwrap_or(false)
+                });
+            }
+            if let Some(selection) = args.selection {
+                previews.retain(|p| p.selection == selection);
+            }
+            if let Some(host) = args.host.as_ref() {
+                previews.retain(|p| p.host.as_ref() == Some(host));
+            }
+            // show selection column when primary and clipboard entries are mixed
+            let requested = args.columns.contains(&PreviewColumn::Selection);
+            let mixed = args.selection.is_none()
+                && previews.iter().any(|p| !p.selection.is_clipboard())
+                && previews.iter().any(|p| p.selection.is_clipboard());
+            // print tab-separated records in porcelain mode
+            if self.porcelain {
+                for p in previews {
+                    let preview = p.display(args.reveal).to_owned();
+                    let mut fields = vec![
+                        group.clone(),
+                        p.index.to_string(),
+                        self.unix_time(p.last_used).to_string(),
+                    ];
+                    if expires {
+                        let expires = p.expires.map(|e| self.unix_time(e).to_string());
+                        fields.push(expires.unwrap_or_else(|| "-".to_owned()));
+                    }
+                    if requested {
+                        fields.push(p.selection.to_string());
+                    }
+                    if show_host {
+                        fields.push(p.host.unwrap_or_else(|| "-".to_owned()));
+                    }
+                    fields.push(preview);
+                    println!("{}", fields.join("\t"));
+                }
+                continue;
+            }
             let data: Table = previews
                 .into_iter()
                 .map(|p| {
                     let human = self.human_time(p.last_used.clone(), &now);
-                    vec![format!("{}", p.index), p.preview, human]
+                    let marker = match (
+                        owns(&clipboard, &group, p.index),
+                        owns(&primary, &group, p.index),
+                    ) {
+                        (true, true) => " ●○",
+                        (true, false) => " ●",
+                        (false, true) => " ○",
+                        (false, false) => "",
+                    };
+                    let pin = match p.pinned {
+                        true => " *",
+                        false => "",
+                    };
+                    let mut row = vec![
+                        format!("{}{marker}{pin}", p.index),
+                        p.display(args.reveal).to_owned(),
+                        human,
+                    ];
+                    if expires {
+                        row.push(self.human_expires(p.expires, &now));
+                    }
+                    if mixed || requested {
+                        row.push(p.selection.to_string());
+                    }
+                    if show_host {
+                        row.push(p.host.unwrap_or_else(|| "-".to_owned()));
+                    }
+                    row
                 })
                 .collect();
             // skip empty record-sets
@@ -440,10 +1465,69 @@ impl Cli {
                 println!("");
             }
             // build ascii table
+            let title = match labels.get(&group) {
+                Some((label, color)) => self.group_title(&group, label.as_deref(), *color),
+                None => group,
+            };
+            let mut table = AsciiTable::new(Some(title), config.list.table.style.clone());
+            table.align_column(0, config.list.table.index_align.clone());
+            table.align_column(1, config.list.table.preview_align.clone());
+            table.align_column(2, config.list.table.time_align.clone());
+            table.align_column(3, config.list.table.time_align.clone());
+            if let Some(width) = width {
+                table.fit_column(1, width);
+            }
+            table.print(data);
+        }
+        Ok(())
+    }
+
+    /// Search Command Handler
+    fn search(&self, mut config: Config, args: SearchArgs) -> Result<(), CliError> {
+        let length = args.length.unwrap_or(config.list.preview_length);
+        config.list.table.style = args.table_style.unwrap_or(config.list.table.style);
+        let width = terminal_size::terminal_size()
+            .filter(|_| !self.porcelain)
+            .map(|(Width(w), _)| w as usize);
+        let mut client = self.connect()?;
+        let groups = match args.all {
+            true => client.groups()?,
+            false => args.group,
+        };
+        let matches = client.search(args.query, groups, length)?;
+        if matches.is_empty() && !self.porcelain {
+            return Err(CliError::Warning("no matching entries".to_owned()));
+        }
+        let now = SystemTime::now();
+        for (n, GroupMatches { group, previews }) in matches.into_iter().enumerate() {
+            if self.porcelain {
+                for p in previews {
+                    let last = self.unix_time(p.last_used);
+                    println!("{group}\t{}\t{last}\t{}", p.index, p.display(args.reveal));
+                }
+                continue;
+            }
+            let data: Table = previews
+                .into_iter()
+                .map(|p| {
+                    let human = self.human_time(p.last_used, &now);
+                    vec![
+                        p.index.to_string(),
+                        p.display(args.reveal).to_owned(),
+                        human,
+                    ]
+                })
+                .collect();
+            if n > 0 {
+                println!();
+            }
             let mut table = AsciiTable::new(Some(group), config.list.table.style.clone());
             table.align_column(0, config.list.table.index_align.clone());
             table.align_column(1, config.list.table.preview_align.clone());
             table.align_column(2, config.list.table.time_align.clone());
+            if let Some(width) = width {
+                table.fit_column(1, width);
+            }
             table.print(data);
         }
         Ok(())
@@ -451,8 +1535,7 @@ impl Cli {
 
     /// Delete Command Handler
     fn delete(&self, config: Config, args: DeleteArgs) -> Result<(), CliError> {
-        let path = self.get_socket();
-        let mut client = Client::new(path)?;
+        let mut client = self.connect()?;
         let name = args
             .group
             .clone()
@@ -463,17 +1546,304 @@ impl Cli {
             client.wipe(Wipe::All, args.group)?;
             return Ok(());
         }
-        let index = match args.entry_num {
-            Some(index) => index,
-            None => client
-                .list(0, args.group.clone())?
-                .into_iter()
-                .map(|p| p.index)
-                .max()
-                .unwrap_or(0),
+        let wipe = match (args.entries, args.pattern) {
+            (_, Some(pattern)) => Wipe::Matching { pattern },
+            (Some(wipe), _) => wipe,
+            (None, None) => {
+                let index = client
+                    .list(0, args.group.clone())?
+                    .into_iter()
+                    .map(|p| p.index)
+                    .max()
+                    .unwrap_or(0);
+                Wipe::Single { index }
+            }
+        };
+        log::info!("deleting {wipe:?} for group {name:?}");
+        client.wipe(wipe, args.group)?;
+        Ok(())
+    }
+
+    /// Pin/Unpin Command Handler
+    fn pin(&self, args: PinArgs, pinned: bool) -> Result<(), CliError> {
+        let mut client = self.connect()?;
+        log::info!("setting pinned={pinned} for entry {}", args.entry_num);
+        client.pin(args.entry_num, pinned, args.group)?;
+        Ok(())
+    }
+
+    /// Import Data-Set Command Handler
+    fn import_set(&self, args: ImportSetArgs) -> Result<(), CliError> {
+        let content = read_to_string(&args.path)?;
+        let entries = dataset::parse(args.format, &content);
+        let group = args.group.unwrap_or_else(|| {
+            args.path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| "default".to_owned())
+        });
+        log::info!("importing {} entries into {group:?}", entries.len());
+        let mut client = self.connect()?;
+        client.import(group, entries)?;
+        Ok(())
+    }
+
+    /// Pick Entry with Menu Command Handler
+    fn pick(&self, config: Config, args: PickArgs) -> Result<(), CliError> {
+        let mut client = self.connect()?;
+        let mut previews = client.list_preferred(config.list.preview_length, args.group.clone())?;
+        previews.sort_by_key(|p| std::cmp::Reverse(p.last_used));
+        let choices: String = previews
+            .into_iter()
+            .map(|p| match &p.note {
+                Some(note) => format!("{}\t{}\t{note}\n", p.index, p.display(args.reveal)),
+                None => format!("{}\t{}\n", p.index, p.display(args.reveal)),
+            })
+            .collect();
+        // run menu command with choices on stdin
+        let menu = args.menu.unwrap_or(config.pick.menu);
+        let mut words = menu.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| CliError::ConflictError("empty menu command".to_owned()))?;
+        let mut child = std::process::Command::new(program)
+            .args(words)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()?;
+        child
+            .stdin
+            .take()
+            .expect("menu stdin missing")
+            .write_all(choices.as_bytes())?;
+        let output = child.wait_with_output()?;
+        let choice = String::from_utf8_lossy(&output.stdout);
+        let choice = choice.trim_end_matches(['\r', '\n']);
+        let calculator = config.pick.calculator && !args.no_calc;
+        let index = choice
+            .split('\t')
+            .next()
+            .and_then(|i| i.trim().parse().ok());
+        // evaluate typed or picked calculations and copy the result instead
+        let text = match index {
+            Some(index) if calculator => {
+                let (entry, ..) = client.find(Some(index), args.group.clone())?;
+                entry.is_text().then(|| entry.to_text())
+            }
+            Some(_) => None,
+            None => Some(choice.to_owned()),
         };
-        log::info!("deleting index {index} for group {name:?}");
-        client.wipe(Wipe::Single { index }, args.group)?;
+        if let Some(result) = text
+            .filter(|_| calculator)
+            .and_then(|t| calc::calculate(&t))
+        {
+            log::info!("copying calculated result {result:?}");
+            client.copy(Entry::text(result, None), args.primary, None, None)?;
+            return Ok(());
+        }
+        let Some(index) = index else {
+            return Err(CliError::Warning("nothing picked".to_owned()));
+        };
+        client.select(index, args.primary, args.group, false)?;
+        Ok(())
+    }
+
+    /// Current Clipboard Entry Command Handler
+    fn current(&self, config: Config, args: CurrentArgs) -> Result<(), CliError> {
+        let mut client = self.connect()?;
+        let length = config.list.preview_length;
+        let print = |client: &mut Client, group: String, index: usize| {
+            let (owner, _) = client.active().unwrap_or_default();
+            let (entry, ..) = client.find(Some(index), Some(group.clone()))?;
+            let mut out = stdout();
+            writeln!(
+                out,
+                "{}",
+                render_format(
+                    &args.format,
+                    &owner,
+                    &group,
+                    index,
+                    &entry,
+                    length,
+                    args.reveal
+                )
+            )?;
+            out.flush()?;
+            Ok::<(), CliError>(())
+        };
+        // live and terminal groups as the daemon resolved them (routing and aliases)
+        let mut groups = client.copy_groups()?;
+        if groups.is_empty() {
+            groups.push("default".to_owned());
+        }
+        // prefer the entry the daemon is serving when it owns the clipboard
+        let (mut owner, _) = client.active()?;
+        let mut latest = match owner.clone() {
+            Owner::Daemon { group, index } => Some(((group, index), SystemTime::now())),
+            _ => None,
+        };
+        // otherwise find most recently used entry between live and terminal groups
+        let owned = latest.is_some();
+        for group in groups.into_iter().filter(|_| !owned) {
+            let previews = client.list(0, Some(group.clone()))?;
+            if let Some(p) = previews.into_iter().max_by_key(|p| p.last_used) {
+                if latest
+                    .as_ref()
+                    .map(|(_, l)| p.last_used > *l)
+                    .unwrap_or(true)
+                {
+                    latest = Some(((group, p.index), p.last_used));
+                }
+            }
+        }
+        if let Some(((group, index), _)) = latest {
+            print(&mut client, group, index)?;
+        }
+        if !args.follow {
+            return Ok(());
+        }
+        // print whenever the clipboard changes owner (blank line once cleared)
+        for event in self.connect()?.subscribe()? {
+            if let Event::Stopped = event? {
+                break;
+            }
+            let (current, _) = client.active()?;
+            if current == owner {
+                continue;
+            }
+            owner = current;
+            match owner.clone() {
+                Owner::Daemon { group, index } => print(&mut client, group, index)?,
+                Owner::Unknown => writeln!(stdout())?,
+                Owner::External { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Watch Command Handler
+    fn watch(&self, config: Config, args: WatchArgs) -> Result<(), CliError> {
+        let mut client = self.connect()?;
+        let events = self.connect()?.subscribe()?;
+        let separator = if args.null { "\0" } else { "\n" };
+        let mut out = stdout();
+        for event in events {
+            let (group, index, live) = match event? {
+                Event::Copied {
+                    group, index, live, ..
+                } => (group, index, live),
+                Event::Deleted { .. } | Event::Owned { .. } => continue,
+                Event::Stopped => break,
+            };
+            if (args.live && !live) || (!args.group.is_empty() && !args.group.contains(&group)) {
+                continue;
+            }
+            // entry may already be gone when deleted right after being copied
+            let Ok((entry, ..)) = client.find(Some(index), Some(group.clone())) else {
+                continue;
+            };
+            let secret = !args.reveal && secret::is_secret(&entry);
+            let preview = match secret {
+                true => secret::mask(&entry.to_text()),
+                false => entry.preview(config.list.preview_length),
+            };
+            if args.json {
+                let line = serde_json::json!({
+                    "group": group,
+                    "index": index,
+                    "live": live,
+                    "mime": entry.mime(),
+                    "bytes": entry.as_bytes().len(),
+                    "preview": preview,
+                    // masked secrets must not leak through the full text
+                    "text": (entry.is_text() && !secret).then(|| entry.to_text()),
+                });
+                write!(out, "{line}{separator}")?;
+            } else if args.preview || (!entry.is_text() && out.is_terminal()) {
+                // binary content is only written when piped
+                write!(out, "{group}\t{index}\t{preview}{separator}")?;
+            } else if entry.is_text() {
+                write!(out, "{}{separator}", entry.to_text())?;
+            } else {
+                out.write_all(entry.as_bytes())?;
+                out.write_all(separator.as_bytes())?;
+            }
+            out.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Mount Filesystem Command Handler
+    fn mount(&self, args: MountArgs) -> Result<(), CliError> {
+        let client = self.connect()?;
+        let mountpoint = PathBuf::from(shellexpand::tilde(&args.path.to_string_lossy()).as_ref());
+        log::info!("mounting clipboard history at {mountpoint:?}");
+        clipfs::mount(client, &mountpoint)?;
+        Ok(())
+    }
+
+    /// Group Management Command Handler
+    fn group(&self, command: GroupCommand) -> Result<(), CliError> {
+        let mut client = self.connect()?;
+        match command {
+            GroupCommand::Set { name, settings } => {
+                client.configure(name, settings.into_iter().collect())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Maintenance Command Handler
+    fn maintenance(&self, command: MaintenanceCommand) -> Result<(), CliError> {
+        let mut client = self.connect()?;
+        match command {
+            MaintenanceCommand::Reindex { group } => {
+                for r in client.reindex(group)? {
+                    match self.porcelain {
+                        true => println!("{}\t{}\t{}", r.group, r.records, r.repaired),
+                        false => println!(
+                            "reindexed {}: {} records ({} repaired)",
+                            r.group, r.records, r.repaired
+                        ),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Trace Toggle Command Handler
+    fn trace(&self, args: TraceArgs) -> Result<(), CliError> {
+        let mut client = self.connect()?;
+        client.trace(args.enabled)?;
+        Ok(())
+    }
+
+    /// List Entry Previews to Offer as Index Completions
+    fn index_candidates(
+        &self,
+        group: Option<String>,
+        length: usize,
+    ) -> Result<Vec<Preview>, CliError> {
+        let mut client = self.connect()?;
+        let mut previews = client.list(length, group)?;
+        // keep one candidate per line for line-oriented consumers
+        for preview in previews.iter_mut() {
+            preview.preview = preview.preview.replace(['\t', '\n'], " ");
+        }
+        Ok(previews)
+    }
+
+    /// Completion Source Command Handler
+    fn complete(&self, command: CompleteCommand) -> Result<(), CliError> {
+        match command {
+            CompleteCommand::Indexes { group, length } => {
+                for preview in self.index_candidates(group, length)? {
+                    println!("{}\t{}", preview.index, preview.preview);
+                }
+            }
+        }
         Ok(())
     }
 
@@ -482,9 +1852,28 @@ impl Cli {
         // override daemon cli arguments
         config.daemon.kill = args.kill;
         config.daemon.capture_live = args.live.unwrap_or(config.daemon.capture_live);
+        config.daemon.headless |= args.headless;
         // fork and run in background if enabled
         if args.background {
-            let daemon = daemonize::Daemonize::new();
+            let bg = &mut config.daemon.background;
+            bg.workdir = args.workdir.unwrap_or(bg.workdir.clone());
+            bg.umask = args.umask.unwrap_or(bg.umask);
+            bg.log_file = args.log_file.or(bg.log_file.take());
+            bg.pid_file = args.pid_file.or(bg.pid_file.take());
+            let expand = |path: &str| PathBuf::from(shellexpand::tilde(path).as_ref());
+            let mut daemon = daemonize::Daemonize::new()
+                .working_directory(expand(&bg.workdir))
+                .umask(bg.umask.0);
+            if let Some(path) = bg.pid_file.as_deref() {
+                daemon = daemon.pid_file(expand(path));
+            }
+            if let Some(path) = bg.log_file.as_deref() {
+                let log = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(expand(path))?;
+                daemon = daemon.stdout(log.try_clone()?).stderr(log);
+            }
             daemon.start()?;
         }
         // run daemon
@@ -495,36 +1884,142 @@ impl Cli {
     }
 }
 
+/// Parse Configuration File (Defaults if Missing)
+fn read_config(path: Option<&PathBuf>) -> Result<Config, CliError> {
+    match path {
+        Some(path) => {
+            let config = read_to_string(path)?;
+            Ok(serde_yaml::from_str(&config)?)
+        }
+        None => Ok(Config::default()),
+    }
+}
+
+/// Configure Logging from Config, Environment and CLI Flags (in that order)
+fn init_logger(cli: &Cli, config: &LogConfig) {
+    let mut builder = env_logger::Builder::new();
+    builder.parse_filters(&config.directives());
+    if let Ok(filters) = std::env::var("RUST_LOG") {
+        builder.parse_filters(&filters);
+    }
+    match (cli.quiet, cli.verbose) {
+        (true, _) => builder.filter_level(LevelFilter::Warn),
+        (_, 0) => &mut builder,
+        (_, 1) => builder.filter_level(LevelFilter::Debug),
+        _ => builder.filter_level(LevelFilter::Trace),
+    };
+    builder.init();
+}
+
 /// run and operate cli
-fn process_cli() -> Result<(), CliError> {
-    let mut cli = Cli::parse();
+fn process_cli(mut cli: Cli) -> Result<(), CliError> {
     let config = cli.load_config()?;
+    init_logger(&cli, &config.log);
     match cli.command.clone() {
-        Command::Copy(args) => cli.copy(args),
+        Command::Copy(args) => cli.copy(config, args),
         Command::ReCopy(args) => cli.select(args),
-        Command::Paste(args) => cli.paste(args),
+        Command::Paste(args) => cli.paste(config, args),
         Command::Edit(args) => cli.edit(args),
-        Command::Check => cli.check(),
+        Command::Type(args) => cli.type_entry(args),
+        Command::Info(args) => cli.info(config, args),
+        Command::Check(args) => cli.check(args),
+        Command::Doctor => cli.doctor(),
         Command::ListGroups(args) => cli.list_groups(config, args),
         Command::Show(args) => cli.show(config, args),
+        Command::Search(args) => cli.search(config, args),
         Command::Delete(args) => cli.delete(config, args),
+        Command::Pin(args) => cli.pin(args, true),
+        Command::Unpin(args) => cli.pin(args, false),
+        Command::Stats(args) => cli.stats(config, args),
+        Command::ImportSet(args) => cli.import_set(args),
+        Command::Pick(args) => cli.pick(config, args),
+        Command::Current(args) => cli.current(config, args),
+        Command::Watch(args) => cli.watch(config, args),
+        Command::Mount(args) => cli.mount(args),
+        Command::Group(command) => cli.group(command),
+        Command::Maintenance(command) => cli.maintenance(command),
+        Command::Trace(args) => cli.trace(args),
+        Command::Complete(command) => cli.complete(command),
         Command::Daemon(args) => cli.daemon(config, args),
     }
 }
 
-fn main() {
-    // enable log and set default level
-    if std::env::var("RUST_LOG").is_err() {
-        std::env::set_var("RUST_LOG", "info");
+/// Open Text in the User's Editor
+fn edit_text(text: &str) -> Result<String, CliError> {
+    let data = edit::edit_bytes(text.as_bytes())?;
+    Ok(mime::decode_text(&data, &[]))
+}
+
+/// Combine Concurrently Edited Texts using Git-Style Conflict Markers
+fn merge_markers(theirs: &str, ours: &str) -> String {
+    let line = |s: &str| match s.ends_with('\n') {
+        true => s.to_owned(),
+        false => format!("{s}\n"),
+    };
+    format!(
+        "<<<<<<< current\n{}=======\n{}>>>>>>> edited\n",
+        line(theirs),
+        line(ours)
+    )
+}
+
+/// Group Already Given (`-g`/`--group`) on the Command Line being Completed
+fn typed_group() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    // completion requests pass the words being completed after `--`
+    let words = match args.iter().position(|a| a == "--") {
+        Some(start) => &args[start + 1..],
+        None => &args[..],
+    };
+    words
+        .iter()
+        .enumerate()
+        .find_map(|(i, word)| match word.as_str() {
+            "-g" | "--group" => words.get(i + 1).cloned(),
+            word => word.strip_prefix("--group=").map(str::to_owned),
+        })
+}
+
+/// Dynamic Shell Completion of Entry Indexes (Empty if Daemon is Unreachable)
+fn complete_indexes(current: &OsStr) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+    // profile and namespace still apply through their environment variables
+    let Ok(mut cli) = Cli::try_parse_from(["wclipd", "complete", "indexes"]) else {
+        return vec![];
+    };
+    if cli.load_config().is_err() {
+        return vec![];
     }
-    env_logger::init();
+    cli.index_candidates(typed_group(), 40)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|preview| (preview.index.to_string(), preview.preview))
+        .filter(|(index, _)| index.starts_with(current.as_ref()))
+        .map(|(index, preview)| CompletionCandidate::new(index).help(Some(preview.into())))
+        .collect()
+}
+
+fn main() {
+    CompleteEnv::with_factory(Cli::command).complete();
+    let cli = Cli::parse();
+    let quiet = cli.quiet;
 
     // run cli and send nice output based on response
-    if let Err(err) = process_cli() {
+    if let Err(err) = process_cli(cli) {
         match err {
+            CliError::Warning(_) if quiet => {}
             CliError::Warning(warn) => eprintln!("Warning, {warn}"),
             CliError::EditError(err) => eprintln!("Failed to edit clipboard, {err}"),
             CliError::ConflictError(err) => eprintln!("Conflicting arguments, {err}"),
+            CliError::DaemonError(DaemonError::AlreadyRunning) => {
+                eprintln!("Daemon Already Running. Use `wclipd daemon -k` to Replace It");
+            }
+            CliError::DaemonError(DaemonError::HealthCheck(err)) => {
+                eprintln!("Daemon Startup Check Failed, {err}");
+            }
+            CliError::ClientError(ClientError::Unexpected(Response::Error { error })) => {
+                eprintln!("Daemon Rejected Request, {error}");
+            }
             CliError::ClientError(_)
                 if io::Error::last_os_error().kind() == io::ErrorKind::ConnectionRefused =>
             {
@@ -535,3 +2030,14 @@ fn main() {
         std::process::exit(1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli_arguments_are_unique() {
+        // clap only reports clashing flags (like `-q`) when a command is built
+        Cli::command().debug_assert();
+    }
+}
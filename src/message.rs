@@ -1,15 +1,333 @@
 //! Daemon Message Implementations
 
+use std::io::{self, Read};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
-use crate::clipboard::{Entry, Preview};
+use crate::backend::{GroupColor, Settings};
+use crate::clipboard::{Entry, Preview, Selection};
+use crate::report::{HistoryReport, LargeEntry};
+
+/// Socket Protocol Version (Bumped on Incompatible Changes)
+pub static PROTOCOL_VERSION: u32 = 5;
+
+/// Most Ranges Accepted in a Single `Wipe::Many` Request
+pub const MAX_WIPE_RANGES: usize = 256;
+
+/// Size of the Big-Endian Payload Length Preceding each Frame
+pub const FRAME_HEADER: usize = 4;
+
+/// Encode Message as a Length-Prefixed MessagePack Frame
+///
+/// Entry bodies are written as raw bytes instead of the base64 text used
+/// for JSON, so large binary copies are never inflated or re-encoded.
+pub fn frame<T: Serialize>(message: &T) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+    let mut frame = vec![0; FRAME_HEADER];
+    rmp_serde::encode::write_named(&mut frame, message)?;
+    let size = (frame.len() - FRAME_HEADER) as u32;
+    frame[..FRAME_HEADER].copy_from_slice(&size.to_be_bytes());
+    Ok(frame)
+}
+
+/// Decode Message from Frame Payload
+pub fn unframe<T: DeserializeOwned>(payload: &[u8]) -> Result<T, rmp_serde::decode::Error> {
+    rmp_serde::from_slice(payload)
+}
+
+/// Read Length-Prefixed Frame Payload (None once the Peer Disconnects)
+///
+/// Frames over the maximum size are rejected before anything is allocated.
+pub fn read_frame(reader: &mut impl Read, max: usize) -> io::Result<Option<Vec<u8>>> {
+    let mut header = [0u8; FRAME_HEADER];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+    let size = u32::from_be_bytes(header) as usize;
+    if size > max {
+        let err = format!("frame of {size} bytes exceeds {max} bytes");
+        return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+    }
+    let mut payload = vec![0; size];
+    reader.read_exact(&mut payload)?;
+    Ok(Some(payload))
+}
+
+/// Separator between Client Namespace and Group Name
+pub static NAMESPACE_SEP: char = ':';
+
+/// Prefix Group Name with Client Namespace
+pub fn namespaced(namespace: &str, group: &str) -> String {
+    format!("{namespace}{NAMESPACE_SEP}{group}")
+}
+
+/// Strip Client Namespace from Group Name (None if Outside Namespace)
+fn unscoped(namespace: &str, group: &str) -> Option<String> {
+    group
+        .strip_prefix(namespace)?
+        .strip_prefix(NAMESPACE_SEP)
+        .map(str::to_owned)
+}
+
+/// Check Namespace is Non-Empty and cannot Escape into Other Groups
+pub fn valid_namespace(namespace: &str) -> bool {
+    !namespace.is_empty() && !namespace.contains(NAMESPACE_SEP)
+}
+
+/// Storage Metrics for a Single Group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupStats {
+    pub group: String,
+    pub entries: usize,
+    pub bytes: usize,
+    pub latest: Option<SystemTime>,
+    /// Configured Display Label
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Configured Display Color
+    #[serde(default)]
+    pub color: Option<GroupColor>,
+}
+
+/// Configured Display Label and Color of a Group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupLabel {
+    pub group: String,
+    pub label: Option<String>,
+    pub color: Option<GroupColor>,
+}
+
+/// Ordering for Group Metrics (Ascending)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum GroupSort {
+    Size,
+    Count,
+    #[default]
+    Recent,
+}
+
+impl GroupSort {
+    /// Sort Group Metrics in Place
+    pub fn sort(&self, stats: &mut [GroupStats]) {
+        match self {
+            Self::Size => stats.sort_by_key(|s| s.bytes),
+            Self::Count => stats.sort_by_key(|s| s.entries),
+            Self::Recent => stats.sort_by_key(|s| s.latest),
+        }
+    }
+}
+
+impl FromStr for GroupSort {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "size" => Ok(Self::Size),
+            "count" => Ok(Self::Count),
+            "recent" => Ok(Self::Recent),
+            _ => Err(format!("invalid sort: {s:?}")),
+        }
+    }
+}
+
+/// Daemon Status Details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Status {
+    pub version: String,
+    pub protocol: u32,
+    pub pid: u32,
+    pub started: SystemTime,
+    pub capture_live: bool,
+    #[serde(default)]
+    pub headless: bool,
+    #[serde(default)]
+    pub copies_active: usize,
+    #[serde(default)]
+    pub copies_served: u64,
+    #[serde(default)]
+    pub copies_rejected: u64,
+    #[serde(default)]
+    pub watcher_restarts: u64,
+    #[serde(default)]
+    pub health: Vec<Diagnostic>,
+}
+
+/// Result of a Daemon Startup Health Check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub check: String,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(check: &str, result: Result<(), String>) -> Self {
+        Self {
+            check: check.to_owned(),
+            ok: result.is_ok(),
+            detail: result.err(),
+        }
+    }
+}
+
+/// Daemon Events Published to Subscribers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+pub enum Event {
+    /// Entry Added to Group (by Terminal Request or Live Capture)
+    Copied {
+        group: String,
+        index: usize,
+        mime: String,
+        live: bool,
+    },
+    /// Entry (or All Entries if No Index) Deleted from Group
+    Deleted { group: String, index: Option<usize> },
+    /// Selection Changed Owner (Copies, Peeks, Clears and Other Applications)
+    Owned { selection: Selection, owner: Owner },
+    /// Daemon Shutting Down
+    Stopped,
+}
+
+impl Event {
+    /// Restrict Event to Client Namespace (None if Outside Namespace)
+    pub fn scoped(self, namespace: &str) -> Option<Self> {
+        Some(match self {
+            Self::Copied {
+                group,
+                index,
+                mime,
+                live,
+            } => Self::Copied {
+                group: unscoped(namespace, &group)?,
+                index,
+                mime,
+                live,
+            },
+            Self::Deleted { group, index } => Self::Deleted {
+                group: unscoped(namespace, &group)?,
+                index,
+            },
+            Self::Owned { selection, owner } => Self::Owned {
+                selection,
+                owner: owner.scoped(namespace),
+            },
+            Self::Stopped => Self::Stopped,
+        })
+    }
+}
+
+/// Daemon View of Current Selection Ownership
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "owner", rename_all = "lowercase")]
+pub enum Owner {
+    /// Selection is Empty or has not been Observed
+    #[default]
+    Unknown,
+    /// Selection is Served by the Daemon from History
+    Daemon { group: String, index: usize },
+    /// Selection is Owned by Another Application
+    External { mimes: Vec<String> },
+}
+
+impl Owner {
+    /// Short Owner Name for Display
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Unknown => "unknown",
+            Self::Daemon { .. } => "daemon",
+            Self::External { .. } => "external",
+        }
+    }
+    /// Hide Daemon Groups Outside Client Namespace
+    pub fn scoped(self, namespace: &str) -> Self {
+        match self {
+            Self::Daemon { group, index } => match unscoped(namespace, &group) {
+                Some(group) => Self::Daemon { group, index },
+                None => Self::Unknown,
+            },
+            owner => owner,
+        }
+    }
+}
 
 /// Delete Specified Items from History
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "request", rename_all = "lowercase")]
 pub enum Wipe {
     All,
-    Single { index: usize },
+    Single {
+        index: usize,
+    },
+    /// Several Inclusive Ranges of Indexes (Single Indexes Start and End Together)
+    Many {
+        ranges: Vec<(usize, usize)>,
+    },
+    /// Inclusive Range of Indexes
+    Range {
+        start: usize,
+        end: usize,
+    },
+    /// Text Entries Containing Pattern (Case-Insensitive)
+    Matching {
+        pattern: String,
+    },
+}
+
+impl Wipe {
+    /// Check if Wipe Selects the Record
+    pub fn selects(&self, index: usize, entry: &Entry) -> bool {
+        match self {
+            Self::All => true,
+            Self::Single { index: i } => *i == index,
+            Self::Many { ranges } => ranges.iter().any(|(s, e)| (*s..=*e).contains(&index)),
+            Self::Range { start, end } => (*start..=*end).contains(&index),
+            Self::Matching { pattern } => {
+                let pattern = pattern.to_lowercase();
+                entry.is_text() && entry.to_text().to_lowercase().contains(&pattern)
+            }
+        }
+    }
+}
+
+/// Parse `all`, an Index, an Inclusive Range (`2-5`) or a List (`1,3,7-9`)
+impl FromStr for Wipe {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid entries: {s:?}");
+        let range = |r: &str| -> Result<(usize, usize), String> {
+            let (start, end) = r.split_once('-').unwrap_or((r, r));
+            let start = start.trim().parse().map_err(|_| invalid())?;
+            let end = end.trim().parse().map_err(|_| invalid())?;
+            match start <= end {
+                true => Ok((start, end)),
+                false => Err(invalid()),
+            }
+        };
+        if s == "all" {
+            return Ok(Self::All);
+        }
+        if !s.contains(',') {
+            return Ok(match range(s)? {
+                (start, end) if start == end => Self::Single { index: start },
+                (start, end) => Self::Range { start, end },
+            });
+        }
+        // ranges are kept as bounds so huge spans never allocate
+        let ranges = s
+            .split(',')
+            .filter(|p| !p.trim().is_empty())
+            .map(range)
+            .collect::<Result<Vec<_>, _>>()?;
+        match ranges.len() > MAX_WIPE_RANGES {
+            true => Err(format!("too many ranges (max {MAX_WIPE_RANGES}): {s:?}")),
+            false => Ok(Self::Many { ranges }),
+        }
+    }
 }
 
 /// Message Backend Group Type Alias
@@ -18,37 +336,298 @@ pub type Grp = Option<String>;
 /// Message Index Type Alias;
 pub type Idx = Option<usize>;
 
+/// Previews of Search Matches within a Group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupMatches {
+    pub group: String,
+    pub previews: Vec<Preview>,
+}
+
+/// Records Changed within a Group since a Daemon Sequence Number
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListChanges {
+    /// Current Sequence Number (Passed to the Next `ListSince`)
+    pub seq: u64,
+    /// Sequence no Longer Covered (Added holds the Full Preview List)
+    #[serde(default)]
+    pub reset: bool,
+    pub added: Vec<Preview>,
+    pub touched: Vec<Preview>,
+    pub removed: Vec<usize>,
+}
+
+/// Outcome of Rebuilding a Group from its Stored Records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reindexed {
+    pub group: String,
+    pub records: usize,
+    /// Records whose Index Disagreed with their Storage Key
+    pub repaired: usize,
+}
+
 /// All Possible Request Messages Supported by Daemon
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "request", rename_all = "lowercase")]
 pub enum Request {
     /// Ping Message to Check if Server is Alive
     Ping,
+    /// Declare Connection Settings (Read-Only and Namespace cannot be Reverted)
+    Hello {
+        readonly: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        namespace: Option<String>,
+    },
     /// Stop Daemon Instance
     Stop,
+    /// Retrieve Daemon Status Details
+    Status,
+    /// Toggle Per-Request Timing Logs
+    Trace { enabled: bool },
     /// Clear Active Clipboard
     Clear,
     /// List Existing Groups
     Groups,
+    /// Entry Counts, Sizes and Recency for Every Group
+    GroupStats,
+    /// Display Labels and Colors for Every Group
+    Labels,
     /// Add New Clipboard Entry
     Copy {
         entry: Entry,
         primary: bool,
         group: Grp,
         index: Idx,
+        /// Replace Record at Index even if Overwrites are Refused
+        #[serde(default)]
+        force: bool,
+        /// Allow Copying into a Group that does not Exist Yet
+        #[serde(default)]
+        create: bool,
+        /// Expected Revision of the Record at Index (Rejected on Mismatch)
+        #[serde(default)]
+        revision: Option<u64>,
     },
     /// Recopy an Existing Entry
     Select {
         index: usize,
         primary: bool,
         group: Grp,
+        /// Copy without Updating Entry Recency
+        #[serde(default)]
+        no_touch: bool,
+    },
+    /// Recopy Entry Best Matching Fuzzy Query
+    Match {
+        query: String,
+        first: bool,
+        primary: bool,
+        group: Grp,
+        #[serde(default)]
+        no_touch: bool,
     },
     /// View Clipboard History
-    List { length: usize, group: Grp },
+    List {
+        length: usize,
+        group: Grp,
+        /// Let the Group `preview_length` Override the Requested Length
+        #[serde(default)]
+        prefer_group: bool,
+        /// Etag of Previously Received Previews (Unchanged Response if Current)
+        #[serde(default)]
+        etag: Option<String>,
+    },
+    /// View Records Changed since Sequence Number (Zero for Full List)
+    ListSince { seq: u64, length: usize, group: Grp },
     /// Find Specific History Entry
     Find { index: Option<usize>, group: Grp },
     /// Delete Clipboard Entries
     Wipe { wipe: Wipe, group: Grp },
+    /// Rebuild Sequence Counters and Preview Caches from Stored Records (All Groups if None)
+    Reindex { group: Grp },
+    /// Pin or Unpin Entry (Pinned Entries Survive Expiration and Clears)
+    Pin {
+        index: usize,
+        pinned: bool,
+        group: Grp,
+    },
+    /// Override Group Configuration at Runtime
+    Configure { group: String, settings: Settings },
+    /// Stream Daemon Events over Connection
+    Subscribe,
+    /// Import Entries into Read-Only Data-Set Group
+    Import { group: String, entries: Vec<Entry> },
+    /// Retrieve Current Clipboard/Primary Ownership
+    Active,
+    /// Aggregate History Statistics across Groups (Default Group if Empty)
+    Report { groups: Vec<String> },
+    /// Full-Text Search of Text Entries across Groups (Default Group if Empty)
+    Search {
+        query: String,
+        groups: Vec<String>,
+        length: usize,
+    },
+}
+
+impl Request {
+    /// Short Request Name for Logging
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Ping => "ping",
+            Self::Hello { .. } => "hello",
+            Self::Stop => "stop",
+            Self::Status => "status",
+            Self::Trace { .. } => "trace",
+            Self::Clear => "clear",
+            Self::Groups => "groups",
+            Self::GroupStats => "groupstats",
+            Self::Labels => "labels",
+            Self::Copy { .. } => "copy",
+            Self::Select { .. } => "select",
+            Self::Match { .. } => "match",
+            Self::List { .. } => "list",
+            Self::ListSince { .. } => "list_since",
+            Self::Find { .. } => "find",
+            Self::Wipe { .. } => "wipe",
+            Self::Pin { .. } => "pin",
+            Self::Reindex { .. } => "reindex",
+            Self::Configure { .. } => "configure",
+            Self::Subscribe => "subscribe",
+            Self::Import { .. } => "import",
+            Self::Active => "active",
+            Self::Report { .. } => "report",
+            Self::Search { .. } => "search",
+        }
+    }
+    /// Check if Request is Permitted on a Read-Only Connection
+    pub fn is_readonly(&self) -> bool {
+        matches!(
+            self,
+            Self::Ping
+                | Self::Hello { .. }
+                | Self::Status
+                | Self::Groups
+                | Self::GroupStats
+                | Self::Labels
+                | Self::List { .. }
+                | Self::ListSince { .. }
+                | Self::Find { .. }
+                | Self::Subscribe
+                | Self::Active
+                | Self::Report { .. }
+                | Self::Search { .. }
+        )
+    }
+    /// Restrict Request to Groups within Client Namespace (None if Not Permitted)
+    ///
+    /// Requests without a group use the namespaced `default` group instead of
+    /// the daemon's terminal group.
+    pub fn scoped(self, namespace: &str, default: &str) -> Option<Self> {
+        let scope = |group: Grp| Some(namespaced(namespace, group.as_deref().unwrap_or(default)));
+        Some(match self {
+            Self::Stop | Self::Trace { .. } | Self::Reindex { .. } => return None,
+            Self::Copy {
+                entry,
+                primary,
+                group,
+                index,
+                force,
+                create,
+                revision,
+            } => Self::Copy {
+                entry,
+                primary,
+                group: scope(group),
+                index,
+                force,
+                create,
+                revision,
+            },
+            Self::Select {
+                index,
+                primary,
+                group,
+                no_touch,
+            } => Self::Select {
+                index,
+                primary,
+                group: scope(group),
+                no_touch,
+            },
+            Self::Match {
+                query,
+                first,
+                primary,
+                group,
+                no_touch,
+            } => Self::Match {
+                query,
+                first,
+                primary,
+                group: scope(group),
+                no_touch,
+            },
+            Self::List {
+                length,
+                group,
+                prefer_group,
+                etag,
+            } => Self::List {
+                length,
+                group: scope(group),
+                prefer_group,
+                etag,
+            },
+            Self::ListSince { seq, length, group } => Self::ListSince {
+                seq,
+                length,
+                group: scope(group),
+            },
+            Self::Find { index, group } => Self::Find {
+                index,
+                group: scope(group),
+            },
+            Self::Wipe { wipe, group } => Self::Wipe {
+                wipe,
+                group: scope(group),
+            },
+            Self::Pin {
+                index,
+                pinned,
+                group,
+            } => Self::Pin {
+                index,
+                pinned,
+                group: scope(group),
+            },
+            Self::Configure { group, settings } => Self::Configure {
+                group: namespaced(namespace, &group),
+                settings,
+            },
+            Self::Import { group, entries } => Self::Import {
+                group: namespaced(namespace, &group),
+                entries,
+            },
+            Self::Report { groups } if groups.is_empty() => Self::Report {
+                groups: vec![namespaced(namespace, default)],
+            },
+            Self::Report { groups } => Self::Report {
+                groups: groups.iter().map(|g| namespaced(namespace, g)).collect(),
+            },
+            Self::Search {
+                query,
+                groups,
+                length,
+            } => Self::Search {
+                query,
+                groups: match groups.is_empty() {
+                    true => vec![namespaced(namespace, default)],
+                    false => groups.iter().map(|g| namespaced(namespace, g)).collect(),
+                },
+                length,
+            },
+            request => request,
+        })
+    }
 }
 
 /// All Possible Response Messages Supported by Daemon
@@ -59,12 +638,53 @@ pub enum Response {
     Ok,
     /// Error Message
     Error { error: String },
+    /// Daemon Status Details
+    Status { status: Status },
     /// List of Avaialble Groups
     Groups { groups: Vec<String> },
-    /// Returned Clipboard Entry
-    Entry { entry: Entry, index: usize },
-    /// Clipboard Previews
-    Previews { previews: Vec<Preview> },
+    /// Metrics for Available Groups
+    GroupStats { stats: Vec<GroupStats> },
+    /// Display Labels for Available Groups
+    Labels { labels: Vec<GroupLabel> },
+    /// Returned Clipboard Entry with its Index and Group
+    Entry {
+        entry: Box<Entry>,
+        index: usize,
+        group: String,
+        #[serde(default)]
+        revision: u64,
+    },
+    /// Record at Index Changed since the Expected Revision (None if Deleted)
+    Conflict {
+        index: usize,
+        group: String,
+        revision: Option<u64>,
+    },
+    /// Clipboard Previews with their Etag
+    Previews {
+        previews: Vec<Preview>,
+        #[serde(default)]
+        etag: Option<String>,
+    },
+    /// Previews Matching the Requested Etag are still Current
+    Unchanged { etag: String },
+    /// Records Changed since the Requested Sequence Number
+    Changes { changes: ListChanges },
+    /// Groups Rebuilt by Reindex
+    Reindexed { groups: Vec<Reindexed> },
+    /// Streamed Daemon Event (after Subscribe)
+    Event { event: Event },
+    /// Current Selection Ownership and Groups Receiving Copies (Live then Terminal)
+    Active {
+        clipboard: Owner,
+        primary: Owner,
+        #[serde(default)]
+        groups: Vec<String>,
+    },
+    /// Aggregate History Statistics
+    Report { report: HistoryReport },
+    /// Search Matches for each Group with Results
+    Matches { matches: Vec<GroupMatches> },
 }
 
 impl Response {
@@ -73,4 +693,86 @@ impl Response {
     pub fn error(error: String) -> Self {
         Self::Error { error }
     }
+    /// Hide Groups Outside Client Namespace
+    pub fn scoped(self, namespace: &str) -> Self {
+        match self {
+            Self::Groups { groups } => Self::Groups {
+                groups: groups
+                    .iter()
+                    .filter_map(|g| unscoped(namespace, g))
+                    .collect(),
+            },
+            Self::GroupStats { stats } => Self::GroupStats {
+                stats: stats
+                    .into_iter()
+                    .filter_map(|s| {
+                        let group = unscoped(namespace, &s.group)?;
+                        Some(GroupStats { group, ..s })
+                    })
+                    .collect(),
+            },
+            Self::Labels { labels } => Self::Labels {
+                labels: labels
+                    .into_iter()
+                    .filter_map(|l| {
+                        let group = unscoped(namespace, &l.group)?;
+                        Some(GroupLabel { group, ..l })
+                    })
+                    .collect(),
+            },
+            Self::Active {
+                clipboard,
+                primary,
+                groups,
+            } => Self::Active {
+                clipboard: clipboard.scoped(namespace),
+                primary: primary.scoped(namespace),
+                groups: groups
+                    .iter()
+                    .filter_map(|g| unscoped(namespace, g))
+                    .collect(),
+            },
+            Self::Report { mut report } => {
+                report.largest = report
+                    .largest
+                    .into_iter()
+                    .filter_map(|entry| {
+                        let group = unscoped(namespace, &entry.group)?;
+                        Some(LargeEntry { group, ..entry })
+                    })
+                    .collect();
+                Self::Report { report }
+            }
+            Self::Matches { matches } => Self::Matches {
+                matches: matches
+                    .into_iter()
+                    .filter_map(|m| {
+                        let group = unscoped(namespace, &m.group)?;
+                        Some(GroupMatches { group, ..m })
+                    })
+                    .collect(),
+            },
+            Self::Entry {
+                entry,
+                index,
+                group,
+                revision,
+            } => Self::Entry {
+                entry,
+                index,
+                group: unscoped(namespace, &group).unwrap_or(group),
+                revision,
+            },
+            Self::Conflict {
+                index,
+                group,
+                revision,
+            } => Self::Conflict {
+                index,
+                group: unscoped(namespace, &group).unwrap_or(group),
+                revision,
+            },
+            response => response,
+        }
+    }
 }
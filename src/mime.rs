@@ -1,6 +1,37 @@
 //! MimeType Evaluation for Clipboard Entries
 
 use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use xdg_mime::SharedMimeInfo;
+
+static MIME_DB: OnceLock<SharedMimeInfo> = OnceLock::new();
+
+/// Shared MIME Database (Loaded Once on First Use)
+pub fn mime_db() -> &'static SharedMimeInfo {
+    MIME_DB.get_or_init(SharedMimeInfo::new)
+}
+
+/// Resolve Common File Extensions without the MIME Database
+pub fn common_mime(extension: &str) -> Option<&'static str> {
+    Some(match extension.to_ascii_lowercase().as_str() {
+        "txt" | "text" | "log" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "csv" => "text/csv",
+        "js" => "text/javascript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        _ => return None,
+    })
+}
 
 /// Check if given MIME type is valid plain-text
 pub fn is_text(mime_type: &str) -> bool {
@@ -16,33 +47,135 @@ pub fn is_image(mime_type: &str) -> bool {
     mime_type.starts_with("image/")
 }
 
+/// Check if MIME type Matches Pattern (Exact or `prefix/*`)
+pub fn matches(pattern: &str, mime_type: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => mime_type.starts_with(prefix),
+        None => mime_type == pattern,
+    }
+}
+
+/// Built-In Magic Numbers for Systems without shared-mime-info
+static MAGIC: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"%PDF-", "application/pdf"),
+    (b"BM", "image/bmp"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+];
+
+/// Sniff MimeType from Magic Bytes and UTF-8 Heuristics
+pub fn sniff_mime(data: &[u8]) -> Option<&'static str> {
+    if let Some((_, mime)) = MAGIC.iter().find(|(magic, _)| data.starts_with(magic)) {
+        return Some(mime);
+    }
+    if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    // valid utf-8 without control characters (besides whitespace) is text
+    let text = std::str::from_utf8(data).ok()?;
+    let control = |c: char| c.is_control() && !matches!(c, '\t' | '\n' | '\r' | '\x0c');
+    (!text.chars().any(control)).then_some("text/plain")
+}
+
 /// Guess MimeType from FilePath
 pub fn guess_mime_path(path: &PathBuf) -> String {
-    let mime_db = xdg_mime::SharedMimeInfo::new();
-    let guess = mime_db.guess_mime_type().path(path).guess();
+    let common = path
+        .extension()
+        .and_then(|e| common_mime(&e.to_string_lossy()));
+    if let Some(mime) = common {
+        return mime.to_owned();
+    }
+    let guess = mime_db().guess_mime_type().path(path).guess();
     guess.mime_type().to_string()
 }
 
 /// Guess MimeType from Raw Bytes Slice
 pub fn guess_mime_data(data: &[u8]) -> String {
-    let mime_db = xdg_mime::SharedMimeInfo::new();
-    match mime_db.get_mime_type_for_data(data) {
+    match mime_db().get_mime_type_for_data(data) {
         Some((mime, _)) => format!("{}", mime),
-        None => match data.is_ascii() {
-            true => "text/plain".to_owned(),
-            false => "unknown".to_owned(),
-        },
+        None => sniff_mime(data).unwrap_or("unknown").to_owned(),
+    }
+}
+
+/// Extract Charset Parameter from MimeType (`text/plain;charset=utf-8`)
+pub fn charset(mime_type: &str) -> Option<&str> {
+    mime_type.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+/// Check if MimeType Declares UTF-8 Text
+fn is_utf8(mime_type: &str) -> bool {
+    mime_type == "UTF8_STRING"
+        || charset(mime_type)
+            .and_then(|c| encoding_rs::Encoding::for_label(c.as_bytes()))
+            .is_some_and(|e| e == encoding_rs::UTF_8)
+}
+
+/// Find MimeType Declaring a Known Charset other than UTF-8
+pub fn legacy_charset(mime_types: &[String]) -> Option<&String> {
+    mime_types.iter().filter(|m| !is_utf8(m)).find(|m| {
+        charset(m)
+            .and_then(|c| encoding_rs::Encoding::for_label(c.as_bytes()))
+            .is_some()
+    })
+}
+
+/// Decode Bytes from Charset into UTF-8 (None if Charset is Unknown)
+pub fn decode_charset(data: &[u8], charset: &str) -> Option<String> {
+    let encoding = encoding_rs::Encoding::for_label(charset.as_bytes())?;
+    Some(encoding.decode_without_bom_handling(data).0.into_owned())
+}
+
+/// Encode UTF-8 Text into Charset (None if Charset is Unknown)
+pub fn encode_charset(text: &str, charset: &str) -> Option<Vec<u8>> {
+    let encoding = encoding_rs::Encoding::for_label(charset.as_bytes())?;
+    Some(encoding.encode(text).0.into_owned())
+}
+
+/// Check if Non-UTF-8 Bytes are Plausibly Latin-1 Text (No C1 Controls)
+fn looks_latin1(data: &[u8]) -> bool {
+    data.iter().all(|b| match b {
+        0x80..=0x9f => false,
+        b'\t' | b'\n' | b'\r' | 0x0c => true,
+        b => *b >= 0x20 && *b != 0x7f,
+    })
+}
+
+/// Decode Text Bytes as UTF-8 (Using Declared Charset or Detecting Latin-1)
+///
+/// Valid UTF-8 is only reinterpreted when the hints declare a legacy
+/// charset and none of them declare UTF-8.
+pub fn decode_text(data: &[u8], hints: &[String]) -> String {
+    let declared = legacy_charset(hints).filter(|_| !hints.iter().any(|h| is_utf8(h)));
+    if let Some(text) = std::str::from_utf8(data)
+        .ok()
+        .filter(|_| declared.is_none())
+    {
+        return text.to_owned();
+    }
+    match declared.and_then(|m| decode_charset(data, charset(m)?)) {
+        Some(text) => text,
+        None if looks_latin1(data) => data.iter().map(|b| *b as char).collect(),
+        None => String::from_utf8_lossy(data).into_owned(),
     }
 }
 
 /// Preview Raw Bytes Slice using MimeDB and Available Mime Hints
 pub fn preview_data(data: &[u8], hints: &Vec<String>) -> String {
-    let mime_db = xdg_mime::SharedMimeInfo::new();
-    match mime_db.get_mime_type_for_data(data) {
+    match mime_db().get_mime_type_for_data(data) {
         Some((mime, _)) => format!("binary data [{mime}]"),
-        None => match hints.iter().any(|h| is_text(h)) {
-            true => String::from_utf8(data.to_owned()).expect("invalid text"),
-            false => format!("unknown data {data:?}"),
+        None => match sniff_mime(data).filter(|m| !is_text(m)) {
+            Some(mime) => format!("binary data [{mime}]"),
+            None if hints.iter().any(|h| is_text(h)) => decode_text(data, hints),
+            None => format!("unknown data {data:?}"),
         },
     }
 }
@@ -0,0 +1,30 @@
+//! Desktop Notifications for Daemon Errors
+
+use std::process::Command;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Minimum Time Between Notifications to Avoid Flooding
+static COOLDOWN: Duration = Duration::from_secs(30);
+static LAST_SENT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Raise Desktop Notification via `notify-send` (Throttled)
+pub fn notify_error(summary: &str, body: &str) {
+    let mut last = LAST_SENT.lock().expect("notify lock failed");
+    if last.map(|l| l.elapsed() < COOLDOWN).unwrap_or(false) {
+        log::debug!("notification throttled: {summary}");
+        return;
+    }
+    *last = Some(Instant::now());
+    let summary = summary.to_owned();
+    let body = body.to_owned();
+    thread::spawn(move || {
+        let result = Command::new("notify-send")
+            .args(["--app-name=wclipd", "--urgency=critical", &summary, &body])
+            .status();
+        if let Err(err) = result {
+            log::warn!("failed to send desktop notification: {err:?}");
+        }
+    });
+}
@@ -0,0 +1,97 @@
+//! Shell Prompt Trimming and Line Joining for Command-Line Snippets
+
+use serde::Deserialize;
+
+/// List Markers Stripped when Joining Lines
+static BULLETS: [&str; 3] = ["- ", "* ", "• "];
+
+/// Strip Bullet or Numbered List Marker (`1.`/`1)`) from Line
+fn strip_bullet(line: &str) -> &str {
+    if let Some(rest) = BULLETS.iter().find_map(|b| line.strip_prefix(b)) {
+        return rest;
+    }
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    match digits > 0 {
+        true => line[digits..]
+            .strip_prefix(". ")
+            .or_else(|| line[digits..].strip_prefix(") "))
+            .unwrap_or(line),
+        false => line,
+    }
+}
+
+/// Join Wrapped, Backslash-Continued or Bulleted Lines into a Single Line
+pub fn join(text: &str) -> String {
+    text.lines()
+        .map(|line| strip_bullet(line.trim()))
+        .map(|line| line.strip_suffix('\\').unwrap_or(line).trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Continuation Prompt (PS2) Stripped only from Continued Lines
+static CONTINUATION: &str = "> ";
+
+fn _prefixes() -> Vec<String> {
+    vec!["$ ".to_owned()]
+}
+
+/// Prompt Trimming Configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct PromptTrimmer {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Prompt Prefixes Stripped from the Start of each Line
+    #[serde(default = "_prefixes")]
+    pub prefixes: Vec<String>,
+}
+
+impl Default for PromptTrimmer {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            prefixes: _prefixes(),
+        }
+    }
+}
+
+impl PromptTrimmer {
+    /// Strip Prompts and Join Continued Lines (None if Text is not a Snippet)
+    ///
+    /// Every line must start with a prompt prefix unless the previous line
+    /// ended in a continuation backslash, so command output is left alone.
+    /// Continued lines may start with the `> ` continuation prompt instead.
+    pub fn trim(&self, text: &str) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+        let mut commands: Vec<String> = vec![];
+        let mut continued = false;
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let stripped = self
+                .prefixes
+                .iter()
+                .find_map(|p| line.strip_prefix(p.as_str()));
+            let command = match (stripped, continued) {
+                (Some(command), _) => command.trim(),
+                (None, true) => line.strip_prefix(CONTINUATION).unwrap_or(line).trim(),
+                (None, false) => return None,
+            };
+            let (command, next) = match command.strip_suffix('\\') {
+                Some(command) => (command.trim_end(), true),
+                None => (command, false),
+            };
+            match commands.last_mut().filter(|_| continued) {
+                Some(last) if !command.is_empty() => {
+                    last.push(' ');
+                    last.push_str(command);
+                }
+                Some(_) => {}
+                None => commands.push(command.to_owned()),
+            }
+            continued = next;
+        }
+        (!commands.is_empty()).then(|| commands.join("\n"))
+    }
+}
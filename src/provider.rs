@@ -0,0 +1,166 @@
+//! Clipboard Providers used by the Daemon (Wayland or In-Memory)
+
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use wayland_clipboard_listener::{WlClipboardCopyStream, WlClipboardPasteStream};
+use wayland_clipboard_listener::{WlClipboardListenerError, WlListenType};
+
+use crate::clipboard::{Entry, Selection};
+
+/// Stream of Selection Updates (`None` when the Selection is Cleared)
+pub trait Listener {
+    /// Read Current Selection Contents
+    fn current(&mut self) -> Result<Option<Entry>, WlClipboardListenerError>;
+    /// Block until the Next Selection Update (`None` when Stream Ends)
+    fn next(&mut self) -> Option<Result<Option<Entry>, WlClipboardListenerError>>;
+}
+
+/// Prepared Connection for Serving a Single Entry
+pub trait Offer: Send {
+    /// Serve Entry until Another Client Takes the Selection
+    fn serve(self: Box<Self>, entry: Entry, primary: bool) -> Result<(), WlClipboardListenerError>;
+}
+
+/// Selection Access Implementation
+pub trait ClipboardProvider: Send + Sync {
+    /// Open Listener for Selection Updates
+    fn listen(&self, selection: Selection) -> Result<Box<dyn Listener>, WlClipboardListenerError>;
+    /// Connect in Preparation to Serve an Entry
+    fn offer(&self) -> Result<Box<dyn Offer>, WlClipboardListenerError>;
+}
+
+/// Wayland Data-Control Protocol
+pub struct Wayland;
+
+struct WaylandListener(WlClipboardPasteStream);
+
+impl Listener for WaylandListener {
+    fn current(&mut self) -> Result<Option<Entry>, WlClipboardListenerError> {
+        Ok(self.0.get_clipboard()?.map(Entry::from))
+    }
+    fn next(&mut self) -> Option<Result<Option<Entry>, WlClipboardListenerError>> {
+        let message = self.0.paste_stream().next()?;
+        Some(message.map(|m| m.map(Entry::from)))
+    }
+}
+
+struct WaylandOffer(WlClipboardCopyStream);
+
+impl Offer for WaylandOffer {
+    fn serve(self: Box<Self>, entry: Entry, primary: bool) -> Result<(), WlClipboardListenerError> {
+        let mut stream = self.0;
+        let entry = entry.with_charset_target();
+        // the copy stream writes one body for every target, so charset
+        // targets are only offered while their bytes match it
+        let context = entry.body.as_bytes().to_vec();
+        let mimes = entry
+            .mime
+            .iter()
+            .filter(|m| *entry.target_bytes(m) == *context)
+            .map(|s| s.as_str())
+            .collect();
+        stream.copy_to_clipboard(context, mimes, primary)
+    }
+}
+
+impl ClipboardProvider for Wayland {
+    fn listen(&self, selection: Selection) -> Result<Box<dyn Listener>, WlClipboardListenerError> {
+        let listen = match selection {
+            Selection::Clipboard => WlListenType::ListenOnCopy,
+            Selection::Primary => WlListenType::ListenOnSelect,
+        };
+        let stream = WlClipboardPasteStream::init(listen)?;
+        Ok(Box::new(WaylandListener(stream)))
+    }
+    fn offer(&self) -> Result<Box<dyn Offer>, WlClipboardListenerError> {
+        Ok(Box::new(WaylandOffer(WlClipboardCopyStream::init()?)))
+    }
+}
+
+/// Selections Shared by the In-Memory Provider and its Streams
+#[derive(Default)]
+struct Board {
+    clipboard: Option<Entry>,
+    primary: Option<Entry>,
+    listeners: Vec<(Selection, Sender<Option<Entry>>)>,
+}
+
+impl Board {
+    fn slot(&mut self, selection: Selection) -> &mut Option<Entry> {
+        match selection {
+            Selection::Clipboard => &mut self.clipboard,
+            Selection::Primary => &mut self.primary,
+        }
+    }
+    /// Replace Selection and Notify Listeners (Dropping Closed Ones)
+    fn set(&mut self, selection: Selection, entry: Option<Entry>) {
+        *self.slot(selection) = entry.clone();
+        self.listeners
+            .retain(|(s, tx)| *s != selection || tx.send(entry.clone()).is_ok());
+    }
+}
+
+/// Clipboard Kept in Process Memory (Headless Mode and Testing)
+#[derive(Default, Clone)]
+pub struct InMemory(Arc<Mutex<Board>>);
+
+impl InMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Set Selection as if Another Application Copied (`None` Clears it)
+    pub fn set(&self, selection: Selection, entry: Option<Entry>) {
+        self.0.lock().expect("board lock").set(selection, entry);
+    }
+    /// Read Current Selection Contents
+    pub fn current(&self, selection: Selection) -> Option<Entry> {
+        self.0.lock().expect("board lock").slot(selection).clone()
+    }
+    /// Read Selection Contents as a Target (None if it is not Offered)
+    pub fn paste(&self, selection: Selection, mime: &str) -> Option<Vec<u8>> {
+        let entry = self.current(selection)?.with_charset_target();
+        let offered = entry.mime.iter().any(|m| m == mime);
+        offered.then(|| entry.target_bytes(mime).into_owned())
+    }
+}
+
+struct MemoryListener {
+    selection: Selection,
+    board: InMemory,
+    updates: Receiver<Option<Entry>>,
+}
+
+impl Listener for MemoryListener {
+    fn current(&mut self) -> Result<Option<Entry>, WlClipboardListenerError> {
+        Ok(self.board.current(self.selection))
+    }
+    fn next(&mut self) -> Option<Result<Option<Entry>, WlClipboardListenerError>> {
+        self.updates.recv().ok().map(Ok)
+    }
+}
+
+struct MemoryOffer(InMemory);
+
+impl Offer for MemoryOffer {
+    fn serve(self: Box<Self>, entry: Entry, primary: bool) -> Result<(), WlClipboardListenerError> {
+        self.0.set(Selection::from(primary), Some(entry));
+        Ok(())
+    }
+}
+
+impl ClipboardProvider for InMemory {
+    fn listen(&self, selection: Selection) -> Result<Box<dyn Listener>, WlClipboardListenerError> {
+        let (tx, updates) = channel();
+        let mut board = self.0.lock().expect("board lock");
+        board.listeners.push((selection, tx));
+        Ok(Box::new(MemoryListener {
+            selection,
+            board: self.clone(),
+            updates,
+        }))
+    }
+    fn offer(&self) -> Result<Box<dyn Offer>, WlClipboardListenerError> {
+        Ok(Box::new(MemoryOffer(self.clone())))
+    }
+}
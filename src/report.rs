@@ -0,0 +1,122 @@
+//! Aggregate History Statistics (Top Domains, Busiest Hours, Largest Entries)
+
+use std::collections::{BTreeSet, HashMap};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::backend::Record;
+use crate::clipboard::Entry;
+use crate::mime::is_text;
+
+/// Number of Rows Kept for Ranked Sections
+static TOP_COUNT: usize = 10;
+
+/// Preview Length for Largest Entries
+static PREVIEW_LENGTH: usize = 60;
+
+/// Summary of a Single Large Entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LargeEntry {
+    pub group: String,
+    pub index: usize,
+    pub bytes: usize,
+    pub mime: String,
+    pub preview: String,
+}
+
+/// Aggregate Analysis of Clipboard History
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryReport {
+    pub entries: usize,
+    pub bytes: usize,
+    /// Most Copied Domains (Counted Once per Entry)
+    pub domains: Vec<(String, usize)>,
+    /// Copies per Local Hour-of-Day
+    pub hours: Vec<usize>,
+    /// Entry Count for each MimeType
+    pub mimes: Vec<(String, usize)>,
+    /// Largest Entries by Size
+    pub largest: Vec<LargeEntry>,
+}
+
+/// Sort Counts Descending (Ties by Name) and Keep the Top Rows
+fn rank(counts: HashMap<String, usize>, keep: usize) -> Vec<(String, usize)> {
+    let mut ranked: Vec<_> = counts.into_iter().collect();
+    ranked.sort_by(|(a, x), (b, y)| y.cmp(x).then(a.cmp(b)));
+    ranked.truncate(keep);
+    ranked
+}
+
+/// Extract Hostnames of HTTP(S) URLs within Text
+pub fn domains(text: &str) -> BTreeSet<String> {
+    text.split(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>' | '(' | ')'))
+        .filter_map(|word| {
+            let start = word.find("https://").or_else(|| word.find("http://"))?;
+            let rest = word[start..].split_once("://")?.1;
+            let authority = rest.split(['/', '?', '#']).next()?;
+            let host = authority.rsplit('@').next()?.split(':').next()?;
+            let host = host.to_lowercase();
+            let host = host.strip_prefix("www.").unwrap_or(&host);
+            (host.contains('.') || host == "localhost").then(|| host.to_owned())
+        })
+        .collect()
+}
+
+/// Primary MimeType of Entry (Skipping Text Targets Offered by Binary Data)
+fn report_mime(entry: &Entry) -> String {
+    let binary = match entry.is_text() {
+        true => None,
+        false => entry.mime.iter().find(|m| m.contains('/') && !is_text(m)),
+    };
+    let mime = binary.cloned().unwrap_or_else(|| entry.mime());
+    mime.split(';').next().unwrap_or_default().to_owned()
+}
+
+/// Local Hour-of-Day of Timestamp
+fn local_hour(ts: SystemTime) -> usize {
+    let secs = ts.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    unsafe { libc::localtime_r(&secs, &mut tm) };
+    tm.tm_hour as usize % 24
+}
+
+impl HistoryReport {
+    /// Aggregate Records Paired with the Name of their Group
+    pub fn build<'a>(records: impl IntoIterator<Item = (&'a str, &'a Record)>) -> Self {
+        let mut report = Self {
+            hours: vec![0; 24],
+            ..Default::default()
+        };
+        let mut domain_counts = HashMap::new();
+        let mut mime_counts = HashMap::new();
+        let mut largest = vec![];
+        for (group, record) in records {
+            let entry = &record.entry;
+            let bytes = entry.as_bytes().len();
+            let mime = report_mime(entry);
+            report.entries += 1;
+            report.bytes += bytes;
+            report.hours[local_hour(record.entry_date)] += 1;
+            if entry.is_text() {
+                for domain in domains(&entry.to_text()) {
+                    *domain_counts.entry(domain).or_default() += 1;
+                }
+            }
+            *mime_counts.entry(mime.clone()).or_default() += 1;
+            largest.push(LargeEntry {
+                group: group.to_owned(),
+                index: record.index,
+                bytes,
+                mime,
+                preview: entry.preview(PREVIEW_LENGTH),
+            });
+        }
+        largest.sort_by_key(|b| std::cmp::Reverse(b.bytes));
+        largest.truncate(TOP_COUNT);
+        report.domains = rank(domain_counts, TOP_COUNT);
+        report.mimes = rank(mime_counts, usize::MAX);
+        report.largest = largest;
+        report
+    }
+}
@@ -0,0 +1,71 @@
+//! Lua Scripting Hooks for Capture Processing
+
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use mlua::{Function, Lua, Table, Value};
+
+use crate::clipboard::{ClipBody, Entry};
+use crate::message::Grp;
+
+/// Capture Hook Loaded from a Lua Script
+///
+/// Scripts define a global `capture(entry)` function receiving a table
+/// with `mime`, `group`, `size` and `text` (text entries only) fields.
+/// Returning `nil`/`false` rejects the entry, `true` keeps it as-is, and
+/// returning the (modified) table applies changes to `text` and `group`.
+pub struct Hook {
+    path: PathBuf,
+    lua: Mutex<Lua>,
+}
+
+impl Hook {
+    /// Load and Evaluate Lua Script from the Given Path
+    pub fn load(path: &Path) -> Result<Self, mlua::Error> {
+        let code = read_to_string(path).map_err(mlua::Error::external)?;
+        let lua = Lua::new();
+        lua.load(&code).set_name(path.to_string_lossy()).exec()?;
+        lua.globals().get::<_, Function>("capture")?;
+        Ok(Self {
+            path: path.to_owned(),
+            lua: Mutex::new(lua),
+        })
+    }
+
+    /// Run Capture Hook and Return Modified Entry (or None if Rejected)
+    pub fn process(&self, mut entry: Entry, group: Grp) -> Option<(Entry, Grp)> {
+        let lua = self.lua.lock().expect("script lock failed");
+        let result = (|| -> Result<Value, mlua::Error> {
+            let table = lua.create_table()?;
+            table.set("mime", entry.mime.clone())?;
+            table.set("group", group.clone())?;
+            table.set("size", entry.as_bytes().len())?;
+            if entry.is_text() {
+                table.set("text", entry.to_text())?;
+            }
+            let capture: Function = lua.globals().get("capture")?;
+            capture.call(table)
+        })();
+        let changes: Table = match result {
+            Ok(Value::Nil) | Ok(Value::Boolean(false)) => return None,
+            Ok(Value::Table(table)) => table,
+            Ok(_) => return Some((entry, group)),
+            Err(err) => {
+                log::error!("script {:?} failed: {err}", self.path);
+                return Some((entry, group));
+            }
+        };
+        let group = changes
+            .get::<_, Option<String>>("group")
+            .ok()
+            .flatten()
+            .or(group);
+        if let Ok(Some(text)) = changes.get::<_, Option<String>>("text") {
+            if entry.is_text() && text.as_bytes() != entry.as_bytes() {
+                entry.body = ClipBody::Text(text);
+            }
+        }
+        Some((entry, group))
+    }
+}
@@ -0,0 +1,83 @@
+//! Secret Detection and Masking for Previews and Logs
+
+use crate::clipboard::Entry;
+
+/// MimeType Offered by Password Managers (KeePassXC, KeePass) for Secrets
+pub static PASSWORD_HINT: &str = "x-kde-passwordManagerHint";
+
+/// Well-Known API Token Prefixes
+static TOKEN_PREFIXES: [&str; 11] = [
+    "sk-",
+    "ghp_",
+    "gho_",
+    "ghs_",
+    "github_pat_",
+    "glpat-",
+    "xoxb-",
+    "xoxp-",
+    "AKIA",
+    "AIza",
+    "npm_",
+];
+
+/// Entries Larger than this are Never Considered Secrets
+static MAX_SECRET_SIZE: usize = 4096;
+
+/// Check if Text Looks like a Token, Password or Private Key
+pub fn looks_secret(text: &str) -> bool {
+    let text = text.trim();
+    if text.starts_with("-----BEGIN") && text.contains("PRIVATE KEY-----") {
+        return true;
+    }
+    if text.len() < 16 || text.len() > 512 || text.contains(char::is_whitespace) {
+        return false;
+    }
+    if TOKEN_PREFIXES.iter().any(|p| text.starts_with(p)) {
+        return true;
+    }
+    // long random-looking tokens mix cases and digits (unlike hashes and words)
+    text.len() >= 24
+        && !text.contains(['/', '\\'])
+        && text.chars().any(|c| c.is_ascii_uppercase())
+        && text.chars().any(|c| c.is_ascii_lowercase())
+        && text.chars().any(|c| c.is_ascii_digit())
+        && is_scrambled(text)
+}
+
+/// Check if Character Classes Change Often (Unlike the Words of Identifiers)
+fn is_scrambled(text: &str) -> bool {
+    let class = |c: char| match c {
+        'a'..='z' => 1,
+        'A'..='Z' => 2,
+        '0'..='9' => 3,
+        _ => 0,
+    };
+    let classes: Vec<u8> = text.chars().map(class).filter(|c| *c != 0).collect();
+    let changes = classes.windows(2).filter(|w| w[0] != w[1]).count();
+    changes * 2 > classes.len()
+}
+
+/// Check if Entry was Tagged or Detected as a Secret
+pub fn is_secret(entry: &Entry) -> bool {
+    if entry.secret || entry.mime.iter().any(|m| m == PASSWORD_HINT) {
+        return true;
+    }
+    entry.is_text() && entry.as_bytes().len() <= MAX_SECRET_SIZE && looks_secret(&entry.to_text())
+}
+
+/// Mask Secret Keeping its Prefix and Last Characters (`sk-****…abcd`)
+pub fn mask(text: &str) -> String {
+    let chars: Vec<char> = text.trim().chars().collect();
+    if chars.len() < 12 {
+        return "********".to_owned();
+    }
+    let prefix = chars[..12]
+        .iter()
+        .position(|c| matches!(c, '-' | '_'))
+        .filter(|p| *p > 0)
+        .map(|p| p + 1)
+        .unwrap_or(3);
+    let head: String = chars[..prefix].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{head}****…{tail}")
+}
@@ -0,0 +1,105 @@
+//! Oversized Entries Spilled to Files and Stored as `text/uri-list`
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::clipboard::Entry;
+use crate::sync::extension;
+use crate::urilist::{file_path, file_uri, is_uri_list, parse, URI_LIST};
+use crate::xdg_dirs;
+
+/// Directory (within the Data Dir) Holding Spilled Entries
+static SPILL_DIR: &str = "spill";
+
+/// File Name Prefix for Group (Group Names may hold Namespace Separators)
+fn file_group(group: &str) -> String {
+    group
+        .chars()
+        .map(|c| match c.is_alphanumeric() || c == '-' {
+            true => c,
+            false => '_',
+        })
+        .collect()
+}
+
+/// Write Entry Body to a File and Return an Entry Referencing it
+pub fn spill(group: &str, entry: &Entry) -> Result<Entry, String> {
+    let dir = xdg_dirs()
+        .map_err(|e| e.to_string())?
+        .create_data_directory(SPILL_DIR)
+        .map_err(|e| e.to_string())?;
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let group = file_group(group);
+    let path = dir.join(format!("{group}-{stamp}.{}", extension(entry)));
+    let data = entry.as_bytes();
+    std::fs::write(&path, data).map_err(|e| format!("failed to write {path:?}: {e}"))?;
+    log::info!("spilled {} byte entry to {path:?}", data.len());
+    let note = entry
+        .note
+        .clone()
+        .unwrap_or_else(|| format!("{} ({} bytes)", entry.mime(), data.len()));
+    Ok(Entry::text(file_uri(&path), Some(URI_LIST.to_owned()))
+        .with_note(Some(note))
+        .with_secret(entry.secret)
+        .with_source(entry.source.clone())
+        .with_selection(entry.selection))
+}
+
+/// File Referenced by a Single-File `text/uri-list` Entry
+pub fn referenced(entry: &Entry) -> Option<PathBuf> {
+    if !is_uri_list(&entry.mime) {
+        return None;
+    }
+    let text = entry.to_text();
+    let [uri] = parse(&text)[..] else {
+        return None;
+    };
+    file_path(uri)
+}
+
+/// Delete Files Spilled by Groups that are no Longer Referenced by any Record
+///
+/// Records are only collected when spilled files exist and the number of
+/// deleted files is returned.
+pub fn sweep(
+    groups: &[String],
+    referenced: impl FnOnce() -> HashSet<PathBuf>,
+) -> Result<usize, String> {
+    if groups.is_empty() {
+        return Ok(0);
+    }
+    let groups: HashSet<String> = groups.iter().map(|g| file_group(g)).collect();
+    let dir = xdg_dirs()
+        .map_err(|e| e.to_string())?
+        .get_data_home()
+        .join(SPILL_DIR);
+    // files are named `{group}-{stamp}.{ext}` and stamps never hold a dash
+    let spilled_by = |path: &PathBuf| {
+        let stem = path.file_stem()?.to_str()?;
+        Some(groups.contains(stem.rsplit_once('-')?.0))
+    };
+    let files: Vec<PathBuf> = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|e| Some(e.ok()?.path()))
+            .filter(|p| spilled_by(p).unwrap_or(false))
+            .collect(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(format!("failed to read {dir:?}: {err}")),
+    };
+    if files.is_empty() {
+        return Ok(0);
+    }
+    let referenced = referenced();
+    let mut deleted = 0;
+    for path in files.into_iter().filter(|p| !referenced.contains(p)) {
+        match std::fs::remove_file(&path) {
+            Ok(()) => deleted += 1,
+            Err(err) => log::warn!("failed to delete spilled entry {path:?}: {err}"),
+        }
+    }
+    Ok(deleted)
+}
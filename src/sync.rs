@@ -0,0 +1,236 @@
+//! Directory Exports for File-Based Sync Tools (Syncthing, rsync)
+//!
+//! Every machine exports records into its own `<dir>/<machine>` folder as
+//! one file per entry with a JSON metadata sidecar, and imports sidecars
+//! found within the folders of other machines.
+
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::backend::Record;
+use crate::clipboard::Entry;
+
+/// Identifiers of Imported Entries within the Machine Folder
+static IMPORTED: &str = ".imported";
+
+fn _true() -> bool {
+    true
+}
+
+fn _interval() -> u64 {
+    60
+}
+
+/// Directory Sync Configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct SyncConfig {
+    pub dir: String,
+    /// Folder Name for this Machine (Defaults to the Hostname)
+    #[serde(default)]
+    pub machine: Option<String>,
+    #[serde(default = "_interval")]
+    pub interval_secs: u64,
+    /// Groups Exported and Imported (Nothing is Synced when Empty)
+    #[serde(default)]
+    pub groups: Vec<String>,
+    #[serde(default = "_true")]
+    pub import: bool,
+}
+
+impl SyncConfig {
+    /// Time between Sync Passes
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+    /// Check if Group is Included in Sync
+    pub fn includes(&self, group: &str) -> bool {
+        self.groups.iter().any(|g| g == group)
+    }
+}
+
+/// Metadata Written beside each Exported Entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sidecar {
+    pub id: String,
+    pub machine: String,
+    pub group: String,
+    /// Entry Contents File (Relative to the Machine Folder)
+    pub file: String,
+    pub text: bool,
+    pub mime: Vec<String>,
+    pub entry_date: SystemTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Hostname the Entry was Originally Captured On
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+}
+
+/// Stable Identifier of Record Contents (FNV-1a over Mimes, Body and Date)
+///
+/// Imported records keep their entry date so the same identifier is
+/// produced on every machine and entries are never exported twice.
+pub fn record_id(record: &Record) -> String {
+    let nanos = record
+        .entry_date
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let mimes = record.entry.mime.join("\n");
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let parts = [
+        mimes.as_bytes(),
+        record.entry.as_bytes(),
+        &nanos.to_le_bytes(),
+    ];
+    for byte in parts.into_iter().flatten() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{hash:016x}")
+}
+
+/// File Extension for Exported Entry
+pub fn extension(entry: &Entry) -> String {
+    if entry.is_text() {
+        return "txt".to_owned();
+    }
+    let mime = entry.mime();
+    let essence = mime.split(';').next().unwrap_or_default();
+    let subtype = essence.rsplit('/').next().unwrap_or_default();
+    let subtype = subtype.split('+').next().unwrap_or_default();
+    match subtype.chars().all(|c| c.is_ascii_alphanumeric()) && !subtype.is_empty() {
+        true => subtype.to_owned(),
+        false => "bin".to_owned(),
+    }
+}
+
+/// Shared Sync Directory
+#[derive(Debug, Clone)]
+pub struct SyncDir {
+    pub root: PathBuf,
+    pub machine: String,
+}
+
+impl SyncDir {
+    pub fn new(cfg: &SyncConfig) -> Self {
+        Self {
+            root: PathBuf::from(shellexpand::tilde(&cfg.dir).as_ref()),
+            machine: cfg.machine.clone().unwrap_or_else(crate::hostname),
+        }
+    }
+    /// Read Sidecars from every Machine Folder (Skipping Unreadable Files)
+    pub fn sidecars(&self) -> std::io::Result<Vec<Sidecar>> {
+        let machines = match std::fs::read_dir(&self.root) {
+            Ok(machines) => machines,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err),
+        };
+        let mut sidecars = vec![];
+        for machine in machines.filter_map(Result::ok) {
+            let name = machine.file_name().to_string_lossy().into_owned();
+            let Ok(files) = std::fs::read_dir(machine.path()) else {
+                continue;
+            };
+            let files = files.filter_map(Result::ok).map(|f| f.path());
+            for path in files.filter(|p| p.extension().is_some_and(|e| e == "json")) {
+                let sidecar = std::fs::read(&path)
+                    .map_err(|err| err.to_string())
+                    .and_then(|data| serde_json::from_slice(&data).map_err(|e| e.to_string()));
+                match sidecar {
+                    // trust the folder over the machine recorded in the sidecar
+                    Ok(sidecar) => sidecars.push(Sidecar {
+                        machine: name.to_owned(),
+                        ..sidecar
+                    }),
+                    Err(err) => log::warn!("skipping sync sidecar {path:?}: {err}"),
+                }
+            }
+        }
+        Ok(sidecars)
+    }
+    /// Identifiers of Entries Exported by any Machine
+    pub fn exported(&self) -> std::io::Result<HashSet<String>> {
+        Ok(self.sidecars()?.into_iter().map(|s| s.id).collect())
+    }
+    /// Write Record Contents and Sidecar into this Machine's Folder
+    pub fn export(&self, group: &str, record: &Record) -> std::io::Result<Sidecar> {
+        let dir = self.root.join(&self.machine);
+        std::fs::create_dir_all(&dir)?;
+        let id = record_id(record);
+        let sidecar = Sidecar {
+            file: format!("{id}.{}", extension(&record.entry)),
+            id,
+            machine: self.machine.to_owned(),
+            group: group.to_owned(),
+            text: record.entry.is_text(),
+            mime: record.entry.mime.clone(),
+            entry_date: record.entry_date,
+            note: record.entry.note.clone(),
+            host: record.host.clone(),
+        };
+        // sidecar is written last so partially synced entries are skipped
+        std::fs::write(dir.join(&sidecar.file), record.entry.as_bytes())?;
+        let meta = serde_json::to_vec_pretty(&sidecar)?;
+        let tmp = dir.join(format!(".{}.json.tmp", sidecar.id));
+        std::fs::write(&tmp, meta)?;
+        std::fs::rename(tmp, dir.join(format!("{}.json", sidecar.id)))?;
+        Ok(sidecar)
+    }
+    /// Identifiers Previously Imported by this Machine
+    ///
+    /// Kept so entries deleted locally are not imported again.
+    pub fn imported(&self) -> std::io::Result<HashSet<String>> {
+        let path = self.root.join(&self.machine).join(IMPORTED);
+        match std::fs::read_to_string(path) {
+            Ok(ids) => Ok(ids.lines().map(str::to_owned).collect()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(HashSet::new()),
+            Err(err) => Err(err),
+        }
+    }
+    /// Record Identifiers of Newly Imported Entries
+    pub fn mark_imported(&self, ids: &[String]) -> std::io::Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let dir = self.root.join(&self.machine);
+        std::fs::create_dir_all(&dir)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(IMPORTED))?;
+        ids.iter().try_for_each(|id| writeln!(file, "{id}"))
+    }
+    /// Sidecars Exported by Other Machines
+    pub fn foreign(&self) -> std::io::Result<Vec<Sidecar>> {
+        let mut sidecars = self.sidecars()?;
+        sidecars.retain(|s| s.machine != self.machine);
+        Ok(sidecars)
+    }
+    /// Load Exported Entry as a Record (Keeping its Original Entry Date)
+    pub fn load(&self, sidecar: &Sidecar, index: usize) -> std::io::Result<Record> {
+        let file = PathBuf::from(&sidecar.file);
+        let name = file.file_name().ok_or(ErrorKind::InvalidData)?;
+        let data = std::fs::read(self.root.join(&sidecar.machine).join(name))?;
+        let mime = sidecar.mime.first().cloned();
+        let mut entry = match String::from_utf8(data) {
+            Ok(text) if sidecar.text => Entry::text(text, None),
+            Ok(text) => Entry::data(text.as_bytes(), mime),
+            Err(err) => Entry::data(err.as_bytes(), mime),
+        };
+        if !sidecar.mime.is_empty() {
+            entry.mime = sidecar.mime.clone();
+        }
+        let mut record = Record::new(index, entry.with_note(sidecar.note.clone()));
+        record.entry_date = sidecar.entry_date;
+        record.last_used = sidecar.entry_date;
+        // fallback to the exporting machine for sidecars without a host
+        record.host = Some(sidecar.host.clone().unwrap_or(sidecar.machine.clone()));
+        Ok(record)
+    }
+}
@@ -85,8 +85,23 @@ fn repeat(c: &str, num: usize) -> String {
     (0..num).map(|_| c).collect()
 }
 
+/// Visible Character Width of Entry (Ignoring ANSI Color Escapes)
+fn width(entry: &str) -> usize {
+    let mut chars = entry.chars();
+    let mut count = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => {
+                chars.find(|c| c.is_ascii_alphabetic());
+            }
+            _ => count += 1,
+        }
+    }
+    count
+}
+
 fn align(entry: Entry, size: usize, fill: &str, align: &Align) -> String {
-    let buf = size - entry.chars().count();
+    let buf = size - width(&entry);
     match align {
         Align::Left => format!("{fill}{entry}{fill}{}", repeat(fill, buf)),
         Align::Right => format!("{}{fill}{entry}{fill}", repeat(fill, buf)),
@@ -99,11 +114,23 @@ fn align(entry: Entry, size: usize, fill: &str, align: &Align) -> String {
     }
 }
 
+/// Truncate Entry to Fit within Specified Character Width
+fn truncate(entry: Entry, size: usize) -> Entry {
+    if width(&entry) <= size {
+        return entry;
+    }
+    let keep = size.saturating_sub(3);
+    let mut entry: String = entry.chars().take(keep).collect();
+    entry.extend("...".chars().take(size - keep));
+    entry
+}
+
 /// Ascii Table Generator Utility
 pub struct AsciiTable {
     title: Option<String>,
     style: StyleArray,
     align: HashMap<usize, Align>,
+    fit: Option<(usize, usize)>,
 }
 
 impl AsciiTable {
@@ -113,9 +140,15 @@ impl AsciiTable {
             title,
             style: style.array(),
             align: HashMap::new(),
+            fit: None,
         }
     }
 
+    /// Shrink Column to Keep Table within Maximum Width
+    pub fn fit_column(&mut self, col: usize, width: usize) {
+        self.fit = Some((col, width));
+    }
+
     /// Configure Column Default Alignment
     pub fn align_column(&mut self, col: usize, align: Align) {
         self.align.insert(col, align);
@@ -135,6 +168,7 @@ impl AsciiTable {
         let mut cols = vec![];
         for (i, col) in row.into_iter().enumerate() {
             let size = col_sizes[i];
+            let col = truncate(col, size);
             let algn = algn.or(self.align.get(&i)).unwrap_or(&Align::Left);
             let render = align(col, size, fill, algn);
             cols.push(render);
@@ -154,7 +188,7 @@ impl AsciiTable {
             .map(|index| {
                 table
                     .iter()
-                    .map(|x| x.get(index).map(|s| s.chars().count()).unwrap_or(0))
+                    .map(|x| x.get(index).map(|s| width(s)).unwrap_or(0))
                     .max()
                     .expect("empty table columns")
             })
@@ -167,7 +201,14 @@ impl AsciiTable {
         if let Some(title) = self.title.as_ref() {
             let index = col_sizes.len() / 2;
             start_row[index] = format!(" {} ", title);
-            col_sizes[index] = std::cmp::max(col_sizes[index], title.len() + 2);
+            col_sizes[index] = std::cmp::max(col_sizes[index], width(title) + 2);
+        }
+        // shrink fitted column when table exceeds maximum width
+        if let Some((col, width)) = self.fit {
+            let total = col_sizes.iter().map(|s| s + 2).sum::<usize>() + col_sizes.len() + 1;
+            if let Some(size) = col_sizes.get_mut(col).filter(|_| total > width) {
+                *size = size.saturating_sub(total - width).max(3);
+            }
         }
         lines.push(self.draw_row(
             start_row,
@@ -0,0 +1,41 @@
+//! Snippet Templates with `{{placeholder}}` Substitution
+
+use std::collections::HashMap;
+
+/// Iterate (Start, End, Name) of Placeholders within Text
+fn scan(text: &str) -> impl Iterator<Item = (usize, usize, &str)> {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let start = offset + text[offset..].find("{{")?;
+        let end = start + text[start..].find("}}")? + 2;
+        offset = end;
+        Some((start, end, text[start + 2..end - 2].trim()))
+    })
+    .filter(|(_, _, name)| !name.is_empty())
+}
+
+/// List Unique Placeholder Names in Order of Appearance
+pub fn placeholders(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    for (_, _, name) in scan(text) {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_owned());
+        }
+    }
+    names
+}
+
+/// Substitute Placeholder Values (Unknown Placeholders are Kept)
+pub fn fill(text: &str, values: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for (start, end, name) in scan(text) {
+        if let Some(value) = values.get(name) {
+            out.push_str(&text[last..start]);
+            out.push_str(value);
+            last = end;
+        }
+    }
+    out.push_str(&text[last..]);
+    out
+}
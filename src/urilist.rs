@@ -0,0 +1,150 @@
+//! File-Manager URI Lists (`text/uri-list`) Previews and Dereferencing
+
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::clipboard::Entry;
+use crate::mime::guess_mime_path;
+
+/// MimeType Offered by File Managers for Copied Files
+pub static URI_LIST: &'static str = "text/uri-list";
+
+/// Check if Entry MimeTypes Contain a URI List
+pub fn is_uri_list(mimes: &[String]) -> bool {
+    mimes.iter().any(|m| m.split(';').next() == Some(URI_LIST))
+}
+
+/// Decode `%XX` Escapes in URI Component
+fn percent_decode(s: &str) -> String {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let hex = tail
+            .get(..2)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (byte, hex) {
+            (b'%', Some(decoded)) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Parse URIs from List (Skips Comments and `copy`/`cut` Headers)
+pub fn parse(text: &str) -> Vec<&str> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.starts_with('#') && l.contains(':'))
+        .collect()
+}
+
+/// Encode Local Path as `file://` URI (Escaping Reserved Bytes)
+pub fn file_uri(path: &Path) -> String {
+    let mut uri = "file://".to_owned();
+    for byte in path.as_os_str().as_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(*byte as char)
+            }
+            byte => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    uri
+}
+
+/// Resolve `file://` URI into Local Path
+pub fn file_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    // skip optional host component (`file://localhost/path`)
+    let path = &rest[rest.find('/')?..];
+    Some(PathBuf::from(percent_decode(path)))
+}
+
+/// Display Names for Every URI in List (Final Path Segment)
+pub fn names(text: &str) -> Vec<String> {
+    parse(text)
+        .into_iter()
+        .map(|uri| {
+            let uri = uri.trim_end_matches('/');
+            let name = uri.rsplit('/').next().unwrap_or(uri);
+            percent_decode(name)
+        })
+        .collect()
+}
+
+/// How Captured URI Lists are Stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UriListMode {
+    /// Store the URI List Verbatim
+    #[default]
+    Verbatim,
+    /// Store Contents of a Single Small Local File instead
+    Contents,
+}
+
+fn _max_size() -> usize {
+    1024 * 1024
+}
+
+/// URI List Capture Configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct UriListConfig {
+    #[serde(default)]
+    pub mode: UriListMode,
+    #[serde(default = "_max_size")]
+    pub max_size: usize,
+}
+
+impl Default for UriListConfig {
+    fn default() -> Self {
+        Self {
+            mode: UriListMode::default(),
+            max_size: _max_size(),
+        }
+    }
+}
+
+impl UriListConfig {
+    /// Replace Single-File URI List with File Contents (if Enabled and Small)
+    pub fn resolve(&self, entry: Entry) -> Entry {
+        if self.mode != UriListMode::Contents || !is_uri_list(&entry.mime) {
+            return entry;
+        }
+        let text = entry.to_text();
+        let [uri] = parse(&text)[..] else {
+            return entry;
+        };
+        let Some(path) = file_path(uri) else {
+            return entry;
+        };
+        let small = std::fs::metadata(&path)
+            .map(|m| m.is_file() && m.len() as usize <= self.max_size)
+            .unwrap_or(false);
+        if !small {
+            log::debug!("not dereferencing {path:?}. missing or too large");
+            return entry;
+        }
+        match std::fs::read(&path) {
+            Ok(data) => {
+                log::debug!("stored contents of copied file {path:?}");
+                Entry::data(&data, Some(guess_mime_path(&path)))
+                    .with_note(entry.note)
+                    .with_selection(entry.selection)
+            }
+            Err(err) => {
+                log::warn!("failed to read copied file {path:?}: {err}");
+                entry
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Tracker-Parameter Stripping for Copied URLs
+
+use serde::Deserialize;
+
+/// Query Parameter Removal Rule (Trailing `*` Matches Prefix)
+#[derive(Debug, Clone, Deserialize)]
+pub struct UrlRule {
+    pub param: String,
+    #[serde(default)]
+    pub allow: Vec<String>,
+}
+
+impl UrlRule {
+    fn new(param: &str) -> Self {
+        Self {
+            param: param.to_owned(),
+            allow: vec![],
+        }
+    }
+    /// Check if Rule Applies to Parameter on Specified Host
+    fn matches(&self, host: &str, key: &str) -> bool {
+        let allowed = self
+            .allow
+            .iter()
+            .any(|a| host == a || host.ends_with(&format!(".{a}")));
+        let matched = match self.param.strip_suffix('*') {
+            Some(prefix) => key.starts_with(prefix),
+            None => key == self.param,
+        };
+        matched && !allowed
+    }
+}
+
+fn _rules() -> Vec<UrlRule> {
+    [
+        "utm_*", "fbclid", "gclid", "gclsrc", "dclid", "msclkid", "yclid", "igshid", "mc_cid",
+        "mc_eid", "_hsenc", "_hsmi", "mkt_tok",
+    ]
+    .into_iter()
+    .map(UrlRule::new)
+    .collect()
+}
+
+/// URL Cleaner Configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct UrlCleaner {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "_rules")]
+    pub rules: Vec<UrlRule>,
+}
+
+impl Default for UrlCleaner {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rules: _rules(),
+        }
+    }
+}
+
+impl UrlCleaner {
+    /// Strip Tracking Parameters from URL (None if Text is not a URL)
+    pub fn clean(&self, text: &str) -> Option<String> {
+        let url = text.trim();
+        if !self.enabled || url.contains(char::is_whitespace) {
+            return None;
+        }
+        let rest = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))?;
+        let host = rest
+            .split(['/', '?', '#'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        let (url, fragment) = match url.split_once('#') {
+            Some((url, fragment)) => (url, Some(fragment)),
+            None => (url, None),
+        };
+        let (base, query) = url.split_once('?')?;
+        let params: Vec<&str> = query
+            .split('&')
+            .filter(|p| !p.is_empty())
+            .filter(|p| {
+                let key = p.split('=').next().unwrap_or_default();
+                !self.rules.iter().any(|r| r.matches(&host, key))
+            })
+            .collect();
+        let mut cleaned = base.to_owned();
+        if !params.is_empty() {
+            cleaned.push('?');
+            cleaned.push_str(&params.join("&"));
+        }
+        if let Some(fragment) = fragment {
+            cleaned.push('#');
+            cleaned.push_str(fragment);
+        }
+        Some(cleaned)
+    }
+}
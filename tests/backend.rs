@@ -0,0 +1,283 @@
+//! Backend Group Deduplication, Indexing and Configuration Tests
+
+use wclipd::backend::{
+    disk_path, Backend, BackendConfig, Cipher, Dedupe, DiskTuning, GroupColor, GroupConfig,
+    LargeEntryPolicy, Manager, Record, Storage,
+};
+use wclipd::clipboard::Entry;
+
+fn text(s: &str) -> Entry {
+    Entry::text(s.to_owned(), None)
+}
+
+#[test]
+fn dedupe_strategies_detect_repeats() {
+    let mut backend = Storage::Memory.backend();
+    let cases = [
+        (Dedupe::Exact, "hello", "hello ", false),
+        (Dedupe::Exact, "hello", "hello", true),
+        (Dedupe::Trimmed, "hello", " hello\n", true),
+        (Dedupe::Trimmed, "hello", "Hello", false),
+        (Dedupe::IgnoreCase, "hello", " HeLLo", true),
+        (Dedupe::Never, "hello", "hello", false),
+    ];
+    for (n, (dedupe, first, second, same)) in cases.into_iter().enumerate() {
+        let mut group = backend.group(Some(&format!("case{n}")));
+        let a = group.push(text(first), dedupe);
+        let b = group.push(text(second), dedupe);
+        assert_eq!(a == b, same, "{dedupe} {first:?} {second:?}");
+        assert_eq!(group.iter().count(), if same { 1 } else { 2 });
+    }
+    assert_eq!("case-insensitive".parse(), Ok(Dedupe::IgnoreCase));
+    assert_eq!("none".parse(), Ok(Dedupe::Never));
+}
+
+#[test]
+fn indexes_are_never_reused() {
+    let mut backend = Storage::Memory.backend();
+    let first = backend.group(None).push(text("a"), Dedupe::Never);
+    let second = backend.group(None).push(text("b"), Dedupe::Never);
+    assert_ne!(first, second);
+    backend.group(None).delete(&second);
+    let third = backend.group(None).push(text("c"), Dedupe::Never);
+    assert!(third > second);
+    let mut group = backend.group(None);
+    group.insert(10, Record::new(10, text("pinned")));
+    assert_eq!(backend.group(None).push(text("d"), Dedupe::Never), 11);
+}
+
+#[test]
+fn reindex_repairs_keys_sequence_and_previews() {
+    let mut backend = Storage::Memory.backend();
+    let mut group = backend.group(None);
+    group.push(text("kept"), Dedupe::Never);
+    // a hand-edited record stored under a key it disagrees with
+    let mut record = Record::new(0, text("moved"));
+    record.preview = None;
+    group.insert(7, record);
+    assert_eq!(group.reindex(), (2, 1));
+    let record = group.get(&7).expect("missing record");
+    assert_eq!(record.index, 7);
+    assert!(record.preview.is_some());
+    assert_eq!(
+        group.get(&0).expect("missing record").entry.to_text(),
+        "kept"
+    );
+    assert!(group.push(text("new"), Dedupe::Never) > 7);
+}
+
+#[test]
+fn disk_paths_expand_home_and_xdg_dirs() {
+    let home = std::env::var("HOME").expect("missing home");
+    let path = disk_path("~/clips/").expect("invalid path");
+    assert_eq!(path, std::path::Path::new(&home).join("clips"));
+    // relative paths and the xdg data dir resolve to the same store
+    let relative = disk_path("./secrets").expect("invalid path");
+    let xdg = disk_path("$XDG_DATA_HOME/wclipd/secrets").expect("invalid path");
+    assert!(relative.is_absolute());
+    assert_eq!(relative, xdg);
+}
+
+#[test]
+fn disk_stores_accept_sled_tuning() {
+    let yaml = "{disk: /tmp/wclipd-store, cache_capacity: 1048576, compression: true}";
+    let storage: Storage = serde_yaml::from_str(yaml).expect("invalid storage");
+    let Storage::Disk(path, tuning) = storage else {
+        panic!("expected disk storage");
+    };
+    assert_eq!(path.to_str(), Some("/tmp/wclipd-store"));
+    let expected = DiskTuning {
+        cache_capacity: Some(1048576),
+        compression: true,
+        flush_every_ms: None,
+    };
+    assert_eq!(tuning, expected);
+    // plain names keep the sled defaults
+    let storage: Storage = serde_yaml::from_str("disk").expect("invalid storage");
+    assert!(matches!(storage, Storage::Disk(_, t) if t == DiskTuning::default()));
+    assert!(serde_yaml::from_str::<Storage>("{disk: memory, compression: true}").is_err());
+    // groups sharing a store cannot disagree on its tuning
+    let yaml = "{a: {storage: {disk: /tmp/wclipd-store, compression: true}}, \
+        b: {storage: /tmp/wclipd-store}}";
+    let config: BackendConfig = serde_yaml::from_str(yaml).expect("invalid config");
+    let err = Manager::new(config)
+        .check()
+        .expect_err("conflicting tuning accepted");
+    assert!(err.contains("differently"));
+}
+
+#[test]
+fn redb_stores_persist_records_and_sequence() {
+    let dir = std::env::temp_dir().join(format!("wclipd-redb-{}", std::process::id()));
+    let path = dir.join("history.redb");
+    let yaml = format!(
+        "work: {{storage: 'redb:{}', expiration: never}}",
+        path.display()
+    );
+    let config: BackendConfig = serde_yaml::from_str(&yaml).expect("invalid config");
+    let mut manager = Manager::new(config.clone());
+    let mut group = manager.group(Some("work"));
+    let first = group.push(text("a"), Dedupe::Never);
+    let second = group.push(text("b"), Dedupe::Never);
+    group.delete(&second);
+    drop(group);
+    drop(manager);
+    // records and the index sequence survive reopening the file
+    let mut manager = Manager::new(config);
+    assert!(manager.groups().contains(&"work".to_owned()));
+    let mut group = manager.group(Some("work"));
+    assert_eq!(
+        group.get(&first).map(|r| r.entry.to_text()),
+        Some("a".to_owned())
+    );
+    assert_eq!(group.keys(), [first]);
+    assert!(group.push(text("c"), Dedupe::Never) > second);
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn jsonl_stores_append_greppable_lines_and_compact() {
+    let dir = std::env::temp_dir().join(format!("wclipd-jsonl-{}", std::process::id()));
+    let yaml = format!(
+        "work: {{storage: 'jsonl:{}', expiration: never}}",
+        dir.display()
+    );
+    let config: BackendConfig = serde_yaml::from_str(&yaml).expect("invalid config");
+    let mut manager = Manager::new(config.clone());
+    let mut group = manager.group(Some("work"));
+    let first = group.push(text("hello grep"), Dedupe::Never);
+    let second = group.push(text("b"), Dedupe::Never);
+    group.delete(&second);
+    let log = std::fs::read_to_string(dir.join("work.jsonl")).expect("missing log");
+    // each new index is reserved before its record is written
+    assert_eq!(log.lines().count(), 5);
+    assert!(log.contains("hello grep"));
+    assert!(log.ends_with(&format!("{{\"deleted\":{second}}}\n")));
+    // stale lines are compacted away without reusing deleted indexes
+    for _ in 0..100 {
+        group.touch(first);
+    }
+    let log = std::fs::read_to_string(dir.join("work.jsonl")).expect("missing log");
+    assert!(log.lines().count() < 100);
+    drop(group);
+    let config_two = config.clone();
+    let mut manager = Manager::new(config);
+    assert!(manager.groups().contains(&"work".to_owned()));
+    let mut group = manager.group(Some("work"));
+    assert_eq!(group.keys(), [first]);
+    assert!(group.push(text("c"), Dedupe::Never) > second);
+    // writers sharing the store never hand out the same index
+    let writers: Vec<_> = (0..2)
+        .map(|_| {
+            let config = config_two.clone();
+            std::thread::spawn(move || {
+                let mut group = Manager::new(config).group(Some("work"));
+                (0..20)
+                    .map(|n| group.push(text(&n.to_string()), Dedupe::Never))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+    let mut indexes: Vec<usize> = writers
+        .into_iter()
+        .flat_map(|w| w.join().expect("writer panicked"))
+        .collect();
+    indexes.sort();
+    indexes.dedup();
+    assert_eq!(indexes.len(), 40);
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn group_labels_and_colors_are_configured() {
+    let yaml = "work: {storage: memory, label: Work, color: blue}";
+    let config: BackendConfig = serde_yaml::from_str(yaml).expect("invalid config");
+    let mut manager = Manager::new(config);
+    let label = manager.label(Some("work"));
+    assert_eq!(label, (Some("Work".to_owned()), Some(GroupColor::Blue)));
+    assert_eq!(manager.label(Some("other")), (None, None));
+    // looking up settings of unknown groups never lists them
+    assert_eq!(manager.groups(), ["work"]);
+    let mut group = GroupConfig::default();
+    group.set("color", "purple").expect("set failed");
+    assert_eq!(group.color, Some(GroupColor::Magenta));
+    assert!(group.set("color", "pink").is_err());
+    assert_eq!(GroupColor::Red.paint("x"), "\x1b[31mx\x1b[0m");
+}
+
+#[test]
+fn max_entry_size_applies_large_entry_policy() {
+    let yaml = "images: {storage: memory, max_entry_size: 1024, large_entry_policy: spill-to-file}";
+    let config: BackendConfig = serde_yaml::from_str(yaml).expect("invalid config");
+    let mut manager = Manager::new(config);
+    let limit = manager.max_entry_size(Some("images"));
+    assert_eq!(limit, Some((1024, LargeEntryPolicy::SpillToFile)));
+    assert_eq!(manager.max_entry_size(Some("other")), None);
+    let mut group = GroupConfig::default();
+    group.set("max_entry_size", "10").expect("set failed");
+    group
+        .set("large_entry_policy", "store")
+        .expect("set failed");
+    assert_eq!(group.max_entry_size, Some(10));
+    assert_eq!(group.large_entry_policy, LargeEntryPolicy::Store);
+    assert!(group.set("large_entry_policy", "truncate").is_err());
+    group.set("max_entry_size", "none").expect("set failed");
+    assert_eq!(group.max_entry_size, None);
+}
+
+#[test]
+fn pinned_records_survive_clean_and_clear() {
+    let yaml = "work: {storage: memory, expiration: never, max_entries: 1}";
+    let config: BackendConfig = serde_yaml::from_str(yaml).expect("invalid config");
+    let mut manager = Manager::new(config);
+    let mut group = manager.group(Some("work"));
+    let pinned = group.push(text("keep"), Dedupe::Never);
+    assert!(group.pin(pinned, true));
+    group.push(text("a"), Dedupe::Never);
+    group.push(text("b"), Dedupe::Never);
+    // records are cleaned whenever the group is opened
+    let mut group = manager.group(Some("work"));
+    let mut kept: Vec<String> = group.iter().map(|r| r.entry.to_text()).collect();
+    kept.sort();
+    assert_eq!(kept, ["b", "keep"]);
+    group.clear();
+    let kept: Vec<String> = group.iter().map(|r| r.entry.to_text()).collect();
+    assert_eq!(kept, ["keep"]);
+    assert!(!group.pin(99, true));
+}
+
+#[test]
+fn encrypted_groups_round_trip_sealed_records() {
+    let yaml = "vault: {storage: memory, expiration: never, encryption: {passphrase: hunter2}}";
+    let config: BackendConfig = serde_yaml::from_str(yaml).expect("invalid config");
+    let mut manager = Manager::new(config);
+    let entry = text("s3cret token").with_note(Some("api".to_owned()));
+    let index = manager.group(Some("vault")).push(entry, Dedupe::Trimmed);
+    let record = manager.group(Some("vault")).get(&index).expect("missing");
+    assert_eq!(record.entry.to_text(), "s3cret token");
+    assert_eq!(record.entry.note.as_deref(), Some("api"));
+    // duplicates are still detected through the decrypted view
+    let again = manager
+        .group(Some("vault"))
+        .push(text("s3cret token "), Dedupe::Trimmed);
+    assert_eq!(index, again);
+    // sealed blobs only open with the same secret
+    let cfg = |p: &str| serde_yaml::from_str(&format!("passphrase: {p}")).expect("invalid");
+    let cipher = Cipher::new(&cfg("hunter2")).expect("cipher failed");
+    let sealed = cipher.seal(b"s3cret token").expect("seal failed");
+    assert!(!sealed.windows(6).any(|w| w == b"s3cret"));
+    assert_eq!(cipher.open(&sealed).expect("open failed"), b"s3cret token");
+    let other = Cipher::new(&cfg("hunter3")).expect("cipher failed");
+    assert!(other.open(&sealed).is_err());
+    let missing = "vault: {storage: memory, encryption: {keyfile: /nonexistent/key}}";
+    let config: BackendConfig = serde_yaml::from_str(missing).expect("invalid config");
+    assert!(Manager::new(config).check().is_err());
+    // spilled files would hold the plaintext
+    let spill = "vault: {storage: memory, encryption: {passphrase: hunter2}, \
+        max_entry_size: 10, large_entry_policy: spill-to-file}";
+    let config: BackendConfig = serde_yaml::from_str(spill).expect("invalid config");
+    let err = Manager::new(config)
+        .check()
+        .expect_err("encrypted spill accepted");
+    assert!(err.contains("spill-to-file"));
+}
@@ -0,0 +1,24 @@
+//! Calculator Expression Detection and Evaluation Tests
+
+use wclipd::calc::calculate;
+
+#[test]
+fn evaluates_expressions_but_not_dates() {
+    assert_eq!(calculate("2 + 3 * 4").as_deref(), Some("14"));
+    assert_eq!(calculate("= 2^10").as_deref(), Some("1024"));
+    assert_eq!(calculate("7 / 2").as_deref(), Some("3.5"));
+    assert_eq!(calculate("10/16/2026"), None);
+    assert_eq!(calculate("2026-10-16"), None);
+    assert_eq!(calculate("555-0100"), None);
+    // an explicit prefix still evaluates date-like input
+    assert!(calculate("=10/16/2026").is_some());
+}
+
+#[test]
+fn deep_nesting_is_rejected_without_overflowing() {
+    assert_eq!(calculate("=((1 + 2)) * -(-3)").as_deref(), Some("9"));
+    let parens = format!("={}1{}", "(".repeat(50_000), ")".repeat(50_000));
+    assert_eq!(calculate(&parens), None);
+    assert_eq!(calculate(&format!("={}1", "-".repeat(50_000))), None);
+    assert_eq!(calculate(&format!("=2{}", "^1".repeat(50_000))), None);
+}
@@ -0,0 +1,27 @@
+//! Clipboard Entry Preview Tests
+
+use wclipd::clipboard::Entry;
+use wclipd::urilist::URI_LIST;
+
+#[test]
+fn cached_preview_matches_entry_preview() {
+    let text = "héllo   wörld\n".repeat(40);
+    let entry = Entry::text(text, None);
+    let cache = entry.preview_cache(64);
+    for width in [0, 3, 10, 32, 63] {
+        assert_eq!(cache.render(width), Some(entry.preview(width)));
+    }
+    // widths beyond the cache must be regenerated from the entry
+    assert_eq!(cache.render(64), None);
+    assert_eq!(
+        entry.preview_cache(usize::MAX).render(1000),
+        Some(entry.preview(1000))
+    );
+}
+
+#[test]
+fn uri_list_previews_file_names() {
+    let uris = "copy\nfile:///home/me/My%20Notes.txt\nfile:///tmp/photos/\n";
+    let entry = Entry::data(uris.as_bytes(), Some(URI_LIST.to_owned()));
+    assert_eq!(entry.preview(80), "My Notes.txt photos");
+}
@@ -0,0 +1,90 @@
+//! Daemon Test Harness with an In-Memory Clipboard Provider
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use wclipd::backend::{Backend, Storage};
+use wclipd::client::Client;
+use wclipd::clipboard::{Entry, Selection};
+use wclipd::config::DaemonConfig;
+use wclipd::daemon::{Daemon, DaemonHandle};
+use wclipd::provider::InMemory;
+
+static HARNESS_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Daemon Running on a Temporary Socket with In-Memory Storage
+pub struct Harness {
+    pub socket: PathBuf,
+    pub clipboard: InMemory,
+    dir: PathBuf,
+    handle: Option<DaemonHandle>,
+}
+
+impl Harness {
+    /// Default Test Configuration (Separate Term and Live Groups)
+    pub fn config() -> DaemonConfig {
+        DaemonConfig {
+            term_backend: Some("term".to_owned()),
+            live_backend: Some("live".to_owned()),
+            ..Default::default()
+        }
+    }
+    /// Start Daemon and Wait until it Accepts Connections
+    pub fn start(cfg: DaemonConfig) -> Self {
+        Self::start_with(cfg, Storage::Memory.backend())
+    }
+    /// Start Daemon on a Specific Backend (e.g. a Configured `Manager`)
+    pub fn start_with(cfg: DaemonConfig, backend: Box<dyn Backend>) -> Self {
+        let id = HARNESS_ID.fetch_add(1, Ordering::SeqCst);
+        let dir = std::env::temp_dir().join(format!("wclipd-test-{}-{id}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("failed to create test dir");
+        let socket = dir.join("daemon.sock");
+        let clipboard = InMemory::new();
+        let handle = Daemon::with_backend(socket.clone(), cfg, backend)
+            .expect("failed to build daemon")
+            .with_provider(Arc::new(clipboard.clone()))
+            .start();
+        let harness = Self {
+            socket,
+            clipboard,
+            dir,
+            handle: Some(handle),
+        };
+        harness.wait_for(|| Client::new(harness.socket.clone()).ok()?.ping().ok());
+        harness
+    }
+    /// Connect New Client to Daemon
+    pub fn client(&self) -> Client {
+        Client::new(self.socket.clone()).expect("failed to connect to daemon")
+    }
+    /// Simulate Another Application Setting the Selection
+    pub fn emit(&self, selection: Selection, entry: Option<Entry>) {
+        self.clipboard.set(selection, entry);
+    }
+    /// Poll until Condition Returns a Value (Panics after Timeout)
+    pub fn wait_for<T>(&self, mut check: impl FnMut() -> Option<T>) -> T {
+        let start = Instant::now();
+        loop {
+            if let Some(value) = check() {
+                return value;
+            }
+            if start.elapsed() > Duration::from_secs(5) {
+                panic!("timed out waiting for daemon");
+            }
+            sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+            let _ = handle.join();
+        }
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
@@ -0,0 +1,970 @@
+//! End-to-End Daemon Tests against a In-Memory Clipboard Provider
+
+mod common;
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+
+use common::Harness;
+
+use wclipd::backend::{Backend, BackendConfig, Dedupe, GroupColor, Manager};
+use wclipd::client::{Client, ClientError, CopyOptions};
+use wclipd::clipboard::{Entry, Selection, Source};
+use wclipd::config::{AppConfig, DaemonConfig, OverwriteMode, SizeRouting};
+use wclipd::message::{Event, GroupSort, Owner, Response, Wipe, MAX_WIPE_RANGES, PROTOCOL_VERSION};
+use wclipd::secret::PASSWORD_HINT;
+use wclipd::sync::SyncConfig;
+use wclipd::urilist::{UriListMode, URI_LIST};
+
+#[test]
+fn status_reports_protocol() {
+    let harness = Harness::start(Harness::config());
+    let status = harness.client().status().expect("status failed");
+    assert_eq!(status.protocol, PROTOCOL_VERSION);
+    assert!(status.health.iter().all(|d| d.ok));
+}
+
+#[test]
+fn large_binary_entries_are_framed() {
+    let harness = Harness::start(DaemonConfig {
+        max_request_size: 4 * 1024 * 1024,
+        ..Harness::config()
+    });
+    let mut client = harness.client();
+    let data: Vec<u8> = (0..2 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let entry = Entry::data(&data, Some("image/png".to_owned()));
+    let term = || Some("term".to_owned());
+    client
+        .copy(entry, false, term(), None)
+        .expect("copy failed");
+    let (found, ..) = client.find(None, term()).expect("find failed");
+    assert_eq!(found.as_bytes(), data.as_slice());
+    // oversized frames are refused without being read
+    let huge = Entry::data(&vec![0; 4 * 1024 * 1024], None);
+    assert!(harness.client().copy(huge, false, term(), None).is_err());
+}
+
+#[test]
+fn line_delimited_clients_are_still_served() {
+    let harness = Harness::start(Harness::config());
+    let mut socket = UnixStream::connect(&harness.socket).expect("connect failed");
+    socket
+        .write_all(b"{\"request\": \"ping\"}\n")
+        .expect("write failed");
+    let mut line = String::new();
+    BufReader::new(&socket)
+        .read_line(&mut line)
+        .expect("read failed");
+    assert_eq!(line.trim(), r#"{"response":"ok"}"#);
+}
+
+#[test]
+fn copy_is_stored_and_offered() {
+    let harness = Harness::start(Harness::config());
+    let mut client = harness.client();
+    let entry = Entry::text("hello world".to_owned(), None);
+    client
+        .copy(entry.clone(), false, None, None)
+        .expect("copy failed");
+    let previews = client
+        .list(80, Some("term".to_owned()))
+        .expect("list failed");
+    assert_eq!(previews.len(), 1);
+    assert_eq!(previews[0].preview, "hello world");
+    harness.wait_for(|| {
+        let current = harness.clipboard.current(Selection::Clipboard)?;
+        (current.body == entry.body).then_some(())
+    });
+    let (clipboard, _) = client.active().expect("active failed");
+    assert_eq!(
+        clipboard,
+        Owner::Daemon {
+            group: "term".to_owned(),
+            index: previews[0].index
+        }
+    );
+}
+
+#[test]
+fn legacy_charset_is_offered_as_alternate_target() {
+    let harness = Harness::start(Harness::config());
+    let mut client = harness.client();
+    let mut entry = Entry::text("café".to_owned(), None);
+    entry.charset = Some("iso-8859-1".to_owned());
+    client.copy(entry, false, None, None).expect("copy failed");
+    let target = "text/plain;charset=iso-8859-1";
+    let latin1 = harness.wait_for(|| harness.clipboard.paste(Selection::Clipboard, target));
+    assert_eq!(latin1, b"caf\xe9");
+    let utf8 = harness
+        .clipboard
+        .paste(Selection::Clipboard, "text/plain;charset=utf-8");
+    assert_eq!(utf8.as_deref(), Some("café".as_bytes()));
+}
+
+#[test]
+fn owner_changes_are_published_for_peeks_and_clears() {
+    // without live capture echoes of the daemon's copies never change the owner
+    let mut cfg = Harness::config();
+    cfg.headless = true;
+    let harness = Harness::start(cfg);
+    let mut client = harness.client();
+    let mut events = harness.client().subscribe().expect("subscribe failed");
+    let term = || Some("term".to_owned());
+    client
+        .copy(Entry::text("first".to_owned(), None), false, term(), None)
+        .expect("copy failed");
+    client
+        .copy(Entry::text("second".to_owned(), None), false, term(), None)
+        .expect("copy failed");
+    client
+        .select(0, false, term(), true)
+        .expect("select failed");
+    client.clear().expect("clear failed");
+    let mut owners = Vec::new();
+    while owners.last() != Some(&Owner::Unknown) {
+        match events.next().expect("events ended").expect("event failed") {
+            Event::Owned {
+                selection: Selection::Clipboard,
+                owner,
+            } => owners.push(owner),
+            _ => continue,
+        }
+    }
+    let daemon = |index| Owner::Daemon {
+        group: "term".to_owned(),
+        index,
+    };
+    assert_eq!(owners, [daemon(0), daemon(1), daemon(0), Owner::Unknown]);
+}
+
+#[test]
+fn live_capture_is_stored() {
+    let harness = Harness::start(Harness::config());
+    let mut client = harness.client();
+    harness.emit(
+        Selection::Clipboard,
+        Some(Entry::text("captured".to_owned(), None)),
+    );
+    let previews = harness.wait_for(|| {
+        let previews = client.list(80, Some("live".to_owned())).ok()?;
+        (!previews.is_empty()).then_some(previews)
+    });
+    assert_eq!(previews[0].preview, "captured");
+}
+
+#[test]
+fn daemon_copy_is_not_recaptured() {
+    let harness = Harness::start(Harness::config());
+    let mut client = harness.client();
+    let entry = Entry::text("echo".to_owned(), None);
+    client
+        .copy(entry.clone(), false, None, None)
+        .expect("copy failed");
+    // the provider echoes the daemon's own copy before the external one
+    harness.wait_for(|| harness.clipboard.current(Selection::Clipboard));
+    harness.emit(
+        Selection::Clipboard,
+        Some(Entry::text("external".to_owned(), None)),
+    );
+    let previews = harness.wait_for(|| {
+        let previews = client.list(80, Some("live".to_owned())).ok()?;
+        (!previews.is_empty()).then_some(previews)
+    });
+    let texts: Vec<_> = previews.iter().map(|p| p.preview.as_str()).collect();
+    assert_eq!(texts, vec!["external"]);
+}
+
+#[test]
+fn headless_stores_without_capture() {
+    let mut cfg = Harness::config();
+    cfg.headless = true;
+    let harness = Harness::start(cfg);
+    let mut client = harness.client();
+    let status = client.status().expect("status failed");
+    assert!(status.headless && !status.capture_live);
+    assert!(status.health.iter().all(|d| d.check != "data_control"));
+    client
+        .copy(Entry::text("snippet".to_owned(), None), false, None, None)
+        .expect("copy failed");
+    let previews = client
+        .list(80, Some("term".to_owned()))
+        .expect("list failed");
+    assert_eq!(previews[0].preview, "snippet");
+}
+
+#[test]
+fn namespace_confines_groups() {
+    let harness = Harness::start(Harness::config());
+    let mut sandbox = harness.client();
+    sandbox
+        .hello(false, Some("app".to_owned()))
+        .expect("hello failed");
+    sandbox
+        .copy(Entry::text("private".to_owned(), None), false, None, None)
+        .expect("copy failed");
+    assert_eq!(sandbox.groups().expect("groups failed"), vec!["term"]);
+    assert_eq!(sandbox.list(80, None).expect("list failed").len(), 1);
+    assert!(sandbox.stop().is_err());
+    assert!(sandbox.hello(false, Some("other".to_owned())).is_err());
+    // unscoped clients see the entry under its namespaced group only
+    let mut client = harness.client();
+    assert!(client.list(80, None).expect("list failed").is_empty());
+    let previews = client
+        .list(80, Some("app:term".to_owned()))
+        .expect("list failed");
+    assert_eq!(previews[0].preview, "private");
+}
+
+#[test]
+fn app_socket_is_confined_to_namespace() {
+    let mut cfg = Harness::config();
+    cfg.apps
+        .insert("org.example.App".to_owned(), AppConfig::default());
+    let harness = Harness::start(cfg);
+    let socket = harness
+        .socket
+        .with_file_name("apps")
+        .join("org.example.App.sock");
+    let mut app = Client::new(socket).expect("failed to connect to app socket");
+    assert!(app.hello(false, Some("other".to_owned())).is_err());
+    app.copy(Entry::text("sandboxed".to_owned(), None), false, None, None)
+        .expect("copy failed");
+    let previews = harness
+        .client()
+        .list(80, Some("org.example.App:term".to_owned()))
+        .expect("list failed");
+    assert_eq!(previews[0].preview, "sandboxed");
+}
+
+#[test]
+fn copied_file_contents_are_stored() {
+    let mut cfg = Harness::config();
+    cfg.uri_list.mode = UriListMode::Contents;
+    let harness = Harness::start(cfg);
+    let path = std::env::temp_dir().join(format!("wclipd-uri-{}.txt", std::process::id()));
+    std::fs::write(&path, "file contents").expect("failed to write file");
+    let uri = format!("file://{}\n", path.display());
+    let entry = Entry::data(uri.as_bytes(), Some(URI_LIST.to_owned()));
+    harness.emit(Selection::Clipboard, Some(entry));
+    let mut client = harness.client();
+    let previews = harness.wait_for(|| {
+        let previews = client.list(80, Some("live".to_owned())).ok()?;
+        (!previews.is_empty()).then_some(previews)
+    });
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(previews[0].preview, "file contents");
+    assert!(previews[0].mime.starts_with("text/plain"));
+}
+
+#[test]
+fn shell_prompts_are_trimmed_from_snippets() {
+    let mut cfg = Harness::config();
+    cfg.trim_prompts.enabled = true;
+    let harness = Harness::start(cfg);
+    let mut client = harness.client();
+    let mut capture = |text: &str| {
+        let count = client
+            .list(80, Some("live".to_owned()))
+            .expect("list failed")
+            .len();
+        harness.emit(
+            Selection::Clipboard,
+            Some(Entry::text(text.to_owned(), None)),
+        );
+        harness.wait_for(|| {
+            let previews = client.list(80, Some("live".to_owned())).ok()?;
+            (previews.len() > count).then_some(())
+        });
+        let (entry, ..) = client
+            .find(None, Some("live".to_owned()))
+            .expect("find failed");
+        entry.to_text()
+    };
+    assert_eq!(capture("$ ls -la"), "ls -la");
+    assert_eq!(
+        capture("$ cargo build \\\n>   --release\n$ echo done"),
+        "cargo build --release\necho done"
+    );
+    // command output and quoted text are kept verbatim
+    assert_eq!(capture("$ ls\nCargo.toml"), "$ ls\nCargo.toml");
+    assert_eq!(capture("> quoted\n> reply"), "> quoted\n> reply");
+}
+
+#[test]
+fn denied_mimetypes_are_never_captured() {
+    let mut cfg = Harness::config();
+    cfg.deny_mimes.push("application/x-vault*".to_owned());
+    let harness = Harness::start(cfg);
+    let mut client = harness.client();
+    let hinted = Entry {
+        offered: vec!["text/plain".to_owned(), PASSWORD_HINT.to_owned()],
+        ..Entry::text("hunter2".to_owned(), None)
+    };
+    let vault = Entry::data(b"token", Some("application/x-vault-key".to_owned()));
+    for entry in [hinted, vault, Entry::text("public".to_owned(), None)] {
+        harness.emit(Selection::Clipboard, Some(entry));
+    }
+    let previews = harness.wait_for(|| {
+        let previews = client.list(80, Some("live".to_owned())).ok()?;
+        (!previews.is_empty()).then_some(previews)
+    });
+    assert_eq!(previews.len(), 1);
+    assert_eq!(previews[0].preview, "public");
+}
+
+#[test]
+fn pinned_entries_survive_wipe_all() {
+    let text = |s: &str| Entry::text(s.to_owned(), None);
+    let term = || Some("term".to_owned());
+    let harness = Harness::start(Harness::config());
+    let mut client = harness.client();
+    for value in ["keep", "drop"] {
+        client
+            .copy(text(value), false, term(), None)
+            .expect("copy failed");
+    }
+    let index = |client: &mut Client, value: &str| {
+        let previews = client.list(80, term()).expect("list failed");
+        previews
+            .into_iter()
+            .find(|p| p.preview == value)
+            .map(|p| p.index)
+    };
+    let keep = index(&mut client, "keep").expect("missing entry");
+    client.pin(keep, true, term()).expect("pin failed");
+    assert!(client.pin(99, true, term()).is_err());
+    client.wipe(Wipe::All, term()).expect("wipe failed");
+    let previews = client.list(80, term()).expect("list failed");
+    assert_eq!(previews.len(), 1);
+    assert!(previews[0].pinned);
+    // unpinned entries are cleared as usual
+    client.pin(keep, false, term()).expect("unpin failed");
+    client.wipe(Wipe::All, term()).expect("wipe failed");
+    assert!(index(&mut client, "keep").is_none());
+}
+
+#[test]
+fn large_group_previews_are_cached_with_etags() {
+    let text = |s: &str| Entry::text(s.to_owned(), None);
+    let term = || Some("term".to_owned());
+    let harness = Harness::start(DaemonConfig {
+        list_cache_threshold: Some(1),
+        ..Harness::config()
+    });
+    let mut client = harness.client();
+    for value in ["a", "b"] {
+        client
+            .copy(text(value), false, term(), None)
+            .expect("copy failed");
+    }
+    let (previews, etag) = client
+        .list_changed(80, term(), None)
+        .expect("list failed")
+        .expect("missing previews");
+    assert_eq!(previews.len(), 2);
+    let unchanged = client.list_changed(80, term(), Some(etag.clone()));
+    assert!(unchanged.expect("list failed").is_none());
+    // new entries invalidate the cache immediately
+    client
+        .copy(text("c"), false, term(), None)
+        .expect("copy failed");
+    let (previews, etag) = client
+        .list_changed(80, term(), Some(etag))
+        .expect("list failed")
+        .expect("missing previews");
+    assert_eq!(previews.len(), 3);
+    // pinning has no event but still drops the cached previews
+    client
+        .pin(previews[0].index, true, term())
+        .expect("pin failed");
+    let (previews, refreshed) = client
+        .list_changed(80, term(), Some(etag.clone()))
+        .expect("list failed")
+        .expect("missing previews");
+    assert!(previews[0].pinned);
+    assert_ne!(refreshed, etag);
+}
+
+#[test]
+fn memory_groups_survive_restarts_with_a_snapshot() {
+    let term = || Some("term".to_owned());
+    let path = std::env::temp_dir().join(format!("wclipd-snapshot-{}", std::process::id()));
+    let cfg = || DaemonConfig {
+        memory_snapshot: Some(path.display().to_string()),
+        ..Harness::config()
+    };
+    let harness = Harness::start(cfg());
+    harness
+        .client()
+        .copy(Entry::text("kept".to_owned(), None), false, term(), None)
+        .expect("copy failed");
+    drop(harness);
+    let harness = Harness::start(cfg());
+    let previews = harness.client().list(80, term()).expect("list failed");
+    let texts: Vec<_> = previews.iter().map(|p| p.preview.as_str()).collect();
+    assert_eq!(texts, ["kept"]);
+    drop(harness);
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn cached_previews_are_refreshed_in_the_background() {
+    let text = |s: &str| Entry::text(s.to_owned(), None);
+    let term = || Some("term".to_owned());
+    let dir = std::env::temp_dir().join(format!("wclipd-refresh-{}", std::process::id()));
+    let yaml = format!(
+        "{{default: {{storage: memory, expiration: never}}, \
+        term: {{storage: 'jsonl:{}', expiration: never}}}}",
+        dir.display()
+    );
+    let config: BackendConfig = serde_yaml::from_str(&yaml).expect("invalid config");
+    let cfg = DaemonConfig {
+        list_cache_threshold: Some(1),
+        ..Harness::config()
+    };
+    let harness = Harness::start_with(cfg, Box::new(Manager::new(config.clone())));
+    let mut client = harness.client();
+    client
+        .copy(text("a"), false, term(), None)
+        .expect("copy failed");
+    let (_, etag) = client
+        .list_changed(80, term(), None)
+        .expect("list failed")
+        .expect("missing previews");
+    // another writer appends to the log without the daemon noticing
+    Manager::new(config)
+        .group(Some("term"))
+        .push(text("b"), Dedupe::Never);
+    let (previews, refreshed) = harness.wait_for(|| {
+        client
+            .list_changed(80, term(), Some(etag.clone()))
+            .expect("list failed")
+    });
+    assert_eq!(previews.len(), 2);
+    assert_ne!(refreshed, etag);
+    drop(harness);
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn list_since_returns_changed_records() {
+    let text = |s: &str| Entry::text(s.to_owned(), None);
+    let term = || Some("term".to_owned());
+    let harness = Harness::start(Harness::config());
+    let mut client = harness.client();
+    for value in ["a", "b"] {
+        client
+            .copy(text(value), false, term(), None)
+            .expect("copy failed");
+    }
+    let full = client.list_since(0, 80, term()).expect("list failed");
+    assert!(full.reset);
+    assert_eq!(full.added.len(), 2);
+    let [a, b] = [0, 1].map(|n| full.added[n].index);
+    let none = client
+        .list_since(full.seq, 80, term())
+        .expect("list failed");
+    assert!(!none.reset && none.added.is_empty() && none.removed.is_empty());
+    assert_eq!(none.seq, full.seq);
+    client
+        .copy(text("c"), false, term(), None)
+        .expect("copy failed");
+    client.pin(a, true, term()).expect("pin failed");
+    client
+        .wipe(Wipe::Single { index: b }, term())
+        .expect("wipe failed");
+    let delta = client
+        .list_since(full.seq, 80, term())
+        .expect("list failed");
+    assert!(!delta.reset);
+    assert_eq!(delta.added.len(), 1);
+    assert_eq!(delta.added[0].preview, "c");
+    assert_eq!(delta.touched.len(), 1);
+    assert!(delta.touched[0].pinned);
+    assert_eq!(delta.removed, vec![b]);
+    assert!(delta.seq > full.seq);
+    // clearing the group asks clients to reload everything
+    client.wipe(Wipe::All, term()).expect("wipe failed");
+    let reload = client
+        .list_since(delta.seq, 80, term())
+        .expect("list failed");
+    assert!(reload.reset);
+    assert_eq!(reload.added.len(), 1);
+}
+
+#[test]
+fn list_since_reports_touches_and_cleanup() {
+    let text = |s: &str| Entry::text(s.to_owned(), None);
+    let term = || Some("term".to_owned());
+    let yaml = "{default: {storage: memory, expiration: never}, \
+        term: {storage: memory, expiration: never, max_entries: 2}}";
+    let config: BackendConfig = serde_yaml::from_str(yaml).expect("invalid config");
+    let harness = Harness::start_with(Harness::config(), Box::new(Manager::new(config)));
+    let mut client = harness.client();
+    for value in ["a", "b"] {
+        client
+            .copy(text(value), false, term(), None)
+            .expect("copy failed");
+    }
+    let full = client.list_since(0, 80, term()).expect("list failed");
+    let [a, b] = [0, 1].map(|n| full.added[n].index);
+    // re-selecting moves the record instead of adding it again
+    client
+        .select(a, false, term(), false)
+        .expect("select failed");
+    client
+        .copy(text("c"), false, term(), None)
+        .expect("copy failed");
+    // records over max_entries are cleaned up when the group is next opened
+    let delta = client
+        .list_since(full.seq, 80, term())
+        .expect("list failed");
+    assert!(!delta.reset);
+    assert_eq!(delta.added.len(), 1);
+    assert_eq!(delta.added[0].preview, "c");
+    assert_eq!(delta.touched.len(), 1);
+    assert_eq!(delta.touched[0].index, a);
+    assert_eq!(delta.removed, vec![b]);
+}
+
+#[test]
+fn index_overwrite_is_refused_or_trashed() {
+    let text = |s: &str| Entry::text(s.to_owned(), None);
+    let term = || Some("term".to_owned());
+    let harness = Harness::start(Harness::config());
+    let mut client = harness.client();
+    client
+        .copy(text("pinned"), false, term(), Some(3))
+        .expect("copy failed");
+    assert!(client.copy(text("oops"), false, term(), Some(3)).is_err());
+    client
+        .overwrite(text("forced"), false, term(), 3, None)
+        .expect("overwrite failed");
+    let (entry, index, group, _) = client.find(None, None).expect("find failed");
+    assert_eq!((entry.to_text().as_str(), index), ("forced", 3));
+    assert_eq!(group, "term");
+
+    let mut cfg = Harness::config();
+    cfg.index_overwrite = OverwriteMode::Trash;
+    let harness = Harness::start(cfg);
+    let mut client = harness.client();
+    client
+        .copy(text("pinned"), false, term(), Some(3))
+        .expect("copy failed");
+    client
+        .copy(text("newer"), false, term(), Some(3))
+        .expect("copy failed");
+    let trash = client
+        .list(80, Some("trash".to_owned()))
+        .expect("list failed");
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].preview, "pinned");
+
+    // entries too large to store never replace (or trash) the record
+    let mut cfg = Harness::config();
+    cfg.index_overwrite = OverwriteMode::Trash;
+    let yaml = "{default: {storage: memory, expiration: never}, \
+        term: {storage: memory, expiration: never, max_entry_size: 8}}";
+    let config: BackendConfig = serde_yaml::from_str(yaml).expect("invalid config");
+    let harness = Harness::start_with(cfg, Box::new(Manager::new(config)));
+    let mut client = harness.client();
+    client
+        .copy(text("small"), false, term(), Some(3))
+        .expect("copy failed");
+    let err = client
+        .overwrite(text("far too large"), false, term(), 3, None)
+        .expect_err("oversized overwrite succeeded");
+    assert!(matches!(
+        err,
+        ClientError::Unexpected(Response::Error { .. })
+    ));
+    let (entry, ..) = client.find(Some(3), term()).expect("find failed");
+    assert_eq!(entry.to_text(), "small");
+    let trash = client.list(80, Some("trash".to_owned()));
+    assert!(trash.map_or(true, |previews| previews.is_empty()));
+}
+
+#[test]
+fn wipe_deletes_ranges_and_matches() {
+    let harness = Harness::start(Harness::config());
+    let mut client = harness.client();
+    let term = || Some("term".to_owned());
+    for text in [
+        "alpha",
+        "beta",
+        "gamma",
+        "Secret one",
+        "delta",
+        "secret two",
+    ] {
+        let entry = Entry::text(text.to_owned(), None);
+        client
+            .copy(entry, false, term(), None)
+            .expect("copy failed");
+    }
+    let remaining = |client: &mut Client| -> Vec<String> {
+        let previews = client.list(80, term()).expect("list failed");
+        previews.into_iter().map(|p| p.preview).collect()
+    };
+    let wipe = Wipe::Matching {
+        pattern: "SECRET".to_owned(),
+    };
+    client.wipe(wipe, term()).expect("wipe failed");
+    assert_eq!(remaining(&mut client), ["alpha", "beta", "gamma", "delta"]);
+    client
+        .wipe("1-2".parse().unwrap(), term())
+        .expect("wipe failed");
+    assert_eq!(remaining(&mut client), ["alpha", "delta"]);
+    assert_eq!(
+        "0,4".parse(),
+        Ok(Wipe::Many {
+            ranges: vec![(0, 0), (4, 4)]
+        })
+    );
+    // huge spans stay symbolic instead of being expanded
+    let huge: Result<Wipe, _> = "0-18446744073709551615,1".parse();
+    assert_eq!(
+        huge,
+        Ok(Wipe::Many {
+            ranges: vec![(0, usize::MAX), (1, 1)]
+        })
+    );
+    let many = vec!["1"; MAX_WIPE_RANGES + 1].join(",");
+    assert!(many.parse::<Wipe>().is_err());
+    let ranges = vec![(9, 9); MAX_WIPE_RANGES + 1];
+    assert!(client.wipe(Wipe::Many { ranges }, term()).is_err());
+    client
+        .wipe("0,4".parse().unwrap(), term())
+        .expect("wipe failed");
+    assert!(remaining(&mut client).is_empty());
+    assert!(client.wipe("0-9".parse().unwrap(), term()).is_err());
+}
+
+#[test]
+fn group_stats_sum_entries_and_bytes() {
+    let harness = Harness::start(Harness::config());
+    let mut client = harness.client();
+    for (text, group) in [("one", "term"), ("three", "term"), ("22", "other")] {
+        let entry = Entry::text(text.to_owned(), None);
+        let options = CopyOptions {
+            group: Some(group.to_owned()),
+            create: true,
+            ..Default::default()
+        };
+        client.copy_with(entry, options).expect("copy failed");
+    }
+    let mut stats = client.group_stats().expect("stats failed");
+    GroupSort::Size.sort(&mut stats);
+    // live captures of the daemon's own copies are not under test
+    let summary: Vec<_> = stats
+        .iter()
+        .filter(|s| s.group != "live")
+        .map(|s| (s.group.as_str(), s.entries, s.bytes))
+        .collect();
+    assert_eq!(summary, [("other", 1, 2), ("term", 2, 8)]);
+}
+
+#[test]
+fn group_labels_are_listed_without_stats() {
+    let yaml = "{default: {storage: memory, expiration: never}, \
+        term: {storage: memory, expiration: never, label: Work, color: blue}}";
+    let config: BackendConfig = serde_yaml::from_str(yaml).expect("invalid config");
+    let harness = Harness::start_with(Harness::config(), Box::new(Manager::new(config)));
+    let labels = harness.client().labels().expect("labels failed");
+    let term = labels
+        .into_iter()
+        .find(|l| l.group == "term")
+        .expect("missing term label");
+    assert_eq!(term.label.as_deref(), Some("Work"));
+    assert_eq!(term.color, Some(GroupColor::Blue));
+}
+
+#[test]
+fn live_entries_are_routed_by_size() {
+    let mut cfg = Harness::config();
+    cfg.size_routing = Some(SizeRouting {
+        max_small_size: 8,
+        small_backend: "small".to_owned(),
+        large_backend: "large".to_owned(),
+    });
+    let harness = Harness::start(cfg);
+    let mut client = harness.client();
+    let groups = client.copy_groups().expect("active failed");
+    assert_eq!(groups, ["small", "large", "term"]);
+    let captured = |client: &mut Client, group: &str| {
+        let previews = client.list(80, Some(group.to_owned())).ok()?;
+        (!previews.is_empty()).then(|| previews[0].preview.clone())
+    };
+    harness.emit(
+        Selection::Clipboard,
+        Some(Entry::text("tiny".to_owned(), None)),
+    );
+    assert_eq!(harness.wait_for(|| captured(&mut client, "small")), "tiny");
+    let png = b"\x89PNG\r\n\x1a\n";
+    harness.emit(Selection::Clipboard, Some(Entry::data(png, None)));
+    harness.wait_for(|| captured(&mut client, "large"));
+}
+
+#[test]
+fn unknown_groups_are_rejected_with_suggestions() {
+    let harness = Harness::start(Harness::config());
+    let mut client = harness.client();
+    let text = |t: &str| Entry::text(t.to_owned(), None);
+    let work = || Some("work".to_owned());
+    let error = |err| match err {
+        ClientError::Unexpected(Response::Error { error }) => error,
+        err => panic!("unexpected error: {err:?}"),
+    };
+    let err = client
+        .copy(text("draft"), false, work(), None)
+        .expect_err("copy into missing group succeeded");
+    assert!(error(err).contains("does not exist"));
+    let create = CopyOptions {
+        group: work(),
+        create: true,
+        ..Default::default()
+    };
+    client
+        .copy_with(text("draft"), create)
+        .expect("copy with create failed");
+    let err = client
+        .list(80, Some("wrok".to_owned()))
+        .expect_err("listing missing group succeeded");
+    assert!(error(err).contains("did you mean \"work\"?"));
+    assert_eq!(client.list(80, work()).expect("list failed").len(), 1);
+}
+
+#[test]
+fn stale_writebacks_are_rejected() {
+    let harness = Harness::start(Harness::config());
+    let mut client = harness.client();
+    let text = |t: &str| Entry::text(t.to_owned(), None);
+    let term = || Some("term".to_owned());
+    client
+        .copy(text("draft"), false, term(), Some(2))
+        .expect("copy failed");
+    let (_, index, _, revision) = client.find(Some(2), term()).expect("find failed");
+    assert_eq!(index, 2);
+    // a concurrent writer replaces the entry while it is being edited
+    client
+        .overwrite(text("theirs"), false, term(), 2, Some(revision))
+        .expect("overwrite failed");
+    let conflict = |err| match err {
+        ClientError::Unexpected(Response::Conflict { revision, .. }) => revision,
+        err => panic!("unexpected error: {err:?}"),
+    };
+    let err = client
+        .overwrite(text("ours"), false, term(), 2, Some(revision))
+        .expect_err("stale overwrite succeeded");
+    let theirs = conflict(err).expect("missing revision");
+    assert!(theirs > revision);
+    let (entry, ..) = client.find(Some(2), term()).expect("find failed");
+    assert_eq!(entry.to_text(), "theirs");
+    client
+        .wipe(Wipe::Single { index: 2 }, term())
+        .expect("wipe failed");
+    let err = client
+        .overwrite(text("ours"), false, term(), 2, Some(theirs))
+        .expect_err("overwrite of deleted entry succeeded");
+    assert_eq!(conflict(err), None);
+    // rewriting a deleted index never repeats an earlier revision
+    client
+        .copy(text("again"), false, term(), Some(2))
+        .expect("copy failed");
+    let err = client
+        .overwrite(text("ours"), false, term(), 2, Some(revision))
+        .expect_err("overwrite with recycled revision succeeded");
+    assert!(conflict(err).is_some_and(|r| r > theirs));
+}
+
+#[test]
+fn entries_sync_between_machines_through_directory() {
+    let dir = std::env::temp_dir().join(format!("wclipd-sync-{}", std::process::id()));
+    let machine = |name: &str| {
+        let mut cfg = Harness::config();
+        cfg.sync = Some(SyncConfig {
+            dir: dir.to_string_lossy().into_owned(),
+            machine: Some(name.to_owned()),
+            interval_secs: 1,
+            groups: vec!["term".to_owned()],
+            import: true,
+        });
+        Harness::start(cfg)
+    };
+    let (laptop, desktop) = (machine("laptop"), machine("desktop"));
+    // secrets are exported neither alongside nor before other entries
+    let secret = Entry {
+        secret: true,
+        ..Entry::text("hunter2".to_owned(), None)
+    };
+    laptop
+        .client()
+        .copy(secret, false, Some("term".to_owned()), None)
+        .expect("copy failed");
+    let entry = Entry::text("synced text".to_owned(), None).with_note(Some("n".to_owned()));
+    laptop
+        .client()
+        .copy(entry, false, Some("term".to_owned()), None)
+        .expect("copy failed");
+    let mut client = desktop.client();
+    let previews = desktop.wait_for(|| {
+        let previews = client.list(80, Some("term".to_owned())).ok()?;
+        (!previews.is_empty()).then_some(previews)
+    });
+    assert_eq!(previews[0].preview, "synced text");
+    assert_eq!(previews[0].note.as_deref(), Some("n"));
+    // imported entries keep the host they were originally captured on
+    assert_eq!(previews[0].host, Some(wclipd::hostname()));
+    let exported = |name: &str| {
+        let files = std::fs::read_dir(dir.join(name)).expect("missing machine folder");
+        files
+            .filter_map(Result::ok)
+            .filter(|f| f.path().extension().is_some_and(|e| e == "json"))
+            .count()
+    };
+    // imported entries are not exported again by the importing machine, and
+    // a later export by the desktop proves a full pass has completed since
+    let local = Entry::text("desktop text".to_owned(), None);
+    client
+        .copy(local, false, Some("term".to_owned()), None)
+        .expect("copy failed");
+    desktop.wait_for(|| (exported("desktop") == 1).then_some(()));
+    assert_eq!(exported("laptop"), 1);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn encrypted_groups_are_never_synced() {
+    let dir = std::env::temp_dir().join(format!("wclipd-sync-vault-{}", std::process::id()));
+    let mut cfg = Harness::config();
+    cfg.sync = Some(SyncConfig {
+        dir: dir.to_string_lossy().into_owned(),
+        machine: Some("laptop".to_owned()),
+        interval_secs: 1,
+        groups: vec!["term".to_owned(), "vault".to_owned()],
+        import: false,
+    });
+    let yaml = "{default: {storage: memory}, term: {storage: memory, expiration: never}, \
+        vault: {storage: memory, expiration: never, encryption: {passphrase: hunter2}}}";
+    let config: BackendConfig = serde_yaml::from_str(yaml).expect("invalid config");
+    let harness = Harness::start_with(cfg, Box::new(Manager::new(config)));
+    let mut client = harness.client();
+    let text = |s: &str| Entry::text(s.to_owned(), None);
+    let exported = || {
+        let files = std::fs::read_dir(dir.join("laptop")).ok()?;
+        let contents: Vec<String> = files
+            .filter_map(Result::ok)
+            .filter_map(|f| std::fs::read_to_string(f.path()).ok())
+            .collect();
+        Some(contents)
+    };
+    client
+        .copy(text("vault secret"), false, Some("vault".to_owned()), None)
+        .expect("copy failed");
+    client
+        .copy(text("first"), false, Some("term".to_owned()), None)
+        .expect("copy failed");
+    harness.wait_for(|| {
+        exported()?
+            .iter()
+            .any(|c| c.contains("first"))
+            .then_some(())
+    });
+    // a later export proves the pass covering the vault has finished
+    client
+        .copy(text("second"), false, Some("term".to_owned()), None)
+        .expect("copy failed");
+    let files = harness.wait_for(|| {
+        let files = exported()?;
+        files.iter().any(|c| c.contains("second")).then_some(files)
+    });
+    assert!(files.iter().all(|c| !c.contains("vault secret")));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn large_entries_are_not_offered_on_primary() {
+    let harness = Harness::start(DaemonConfig {
+        max_primary_size: Some(16),
+        ..Harness::config()
+    });
+    let mut client = harness.client();
+    let text = |t: &str| Entry::text(t.to_owned(), None);
+    client
+        .copy(text("small"), true, None, None)
+        .expect("copy failed");
+    let primary = || harness.clipboard.current(Selection::Primary);
+    harness.wait_for(|| primary().filter(|e| e.to_text() == "small"));
+    client
+        .copy(text(&"x".repeat(64)), true, None, None)
+        .expect("copy failed");
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert_eq!(primary().map(|e| e.to_text()).as_deref(), Some("small"));
+    // the entry is still stored while primary keeps its previous owner
+    let previews = client
+        .list(80, Some("term".to_owned()))
+        .expect("list failed");
+    assert_eq!(previews.len(), 2);
+    let (_, primary_owner) = client.active().expect("active failed");
+    assert_eq!(
+        primary_owner,
+        Owner::Daemon {
+            group: "term".to_owned(),
+            index: 0
+        }
+    );
+}
+
+#[test]
+fn copy_source_is_kept_with_entry() {
+    let harness = Harness::start(Harness::config());
+    let mut client = harness.client();
+    let source = Source {
+        cwd: Some("/home/me/project".to_owned()),
+        term: Some("xterm-256color".to_owned()),
+        tty: None,
+        hostname: Some("laptop".to_owned()),
+    };
+    let entry = Entry::text("snippet".to_owned(), None).with_source(Some(source.clone()));
+    client.copy(entry, false, None, None).expect("copy failed");
+    let (entry, ..) = client.find(None, None).expect("find failed");
+    assert_eq!(entry.source, Some(source));
+}
+
+#[test]
+fn search_matches_text_across_groups() {
+    let harness = Harness::start(Harness::config());
+    let mut client = harness.client();
+    let copy = |client: &mut Client, text: &str, group: &str| {
+        let entry = Entry::text(text.to_owned(), None);
+        let options = CopyOptions {
+            group: Some(group.to_owned()),
+            create: true,
+            ..Default::default()
+        };
+        client.copy_with(entry, options).expect("copy failed");
+    };
+    copy(&mut client, "cargo build --release", "term");
+    copy(&mut client, "unrelated", "term");
+    copy(&mut client, "CARGO_HOME=/opt/cargo", "work");
+    let image = Entry::data(b"\x89PNG\r\n\x1a\ncargo", Some("image/png".to_owned()));
+    client
+        .copy(image, false, Some("work".to_owned()), None)
+        .expect("copy into existing group failed");
+    let groups = vec!["term".to_owned(), "work".to_owned()];
+    let matches = client
+        .search("cargo".to_owned(), groups, 80)
+        .expect("search failed");
+    let found: Vec<_> = matches
+        .iter()
+        .map(|m| (m.group.as_str(), m.previews.len()))
+        .collect();
+    assert_eq!(found, [("term", 1), ("work", 1)]);
+    assert_eq!(matches[1].previews[0].preview, "CARGO_HOME=/opt/cargo");
+    // the default group is the term group
+    let matches = client
+        .search("release".to_owned(), vec![], 80)
+        .expect("search failed");
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].group, "term");
+}
@@ -0,0 +1,27 @@
+//! Focused Window Lookup Tests
+
+use serde_json::json;
+use wclipd::focus::{hyprland_app, sway_app};
+
+#[test]
+fn focused_app_is_found_in_compositor_output() {
+    let window = json!({"class": "org.keepassxc.KeePassXC", "title": "vault"});
+    assert_eq!(
+        hyprland_app(&window).as_deref(),
+        Some("org.keepassxc.KeePassXC")
+    );
+    assert_eq!(hyprland_app(&json!({})), None);
+    let tree = json!({
+        "focused": false,
+        "nodes": [{
+            "focused": false,
+            "nodes": [{"focused": false, "app_id": "foot"}],
+            "floating_nodes": [{
+                "focused": true,
+                "app_id": null,
+                "window_properties": {"class": "Bitwarden"}
+            }]
+        }]
+    });
+    assert_eq!(sway_app(&tree).as_deref(), Some("Bitwarden"));
+}
@@ -0,0 +1,43 @@
+//! Built-In MIME Sniffing Tests
+
+use wclipd::mime::{charset, decode_charset, decode_text, encode_charset, sniff_mime};
+
+#[test]
+fn sniffs_magic_numbers_and_text() {
+    assert_eq!(
+        sniff_mime(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+        Some("image/png")
+    );
+    assert_eq!(
+        sniff_mime(b"\xff\xd8\xff\xe0\0\x10JFIF"),
+        Some("image/jpeg")
+    );
+    assert_eq!(sniff_mime(b"GIF89a\x01\0"), Some("image/gif"));
+    assert_eq!(sniff_mime(b"%PDF-1.7\n"), Some("application/pdf"));
+    assert_eq!(sniff_mime("héllo\twörld\n".as_bytes()), Some("text/plain"));
+    assert_eq!(sniff_mime(b"\0\x01\x02binary"), None);
+    assert_eq!(sniff_mime(b"\xc3\x28"), None);
+}
+
+#[test]
+fn decodes_latin1_and_invalid_text() {
+    assert_eq!(decode_text("héllo".as_bytes(), &[]), "héllo");
+    assert_eq!(decode_text(b"caf\xe9", &[]), "café");
+    assert_eq!(decode_text(b"bad \x81 byte", &[]), "bad \u{fffd} byte");
+    let hints = vec!["text/plain;charset=ISO-8859-1".to_owned()];
+    assert_eq!(decode_text(b"\xc3\xa9", &hints), "Ã©");
+}
+
+#[test]
+fn converts_legacy_charsets() {
+    assert_eq!(charset("text/plain;charset=Shift_JIS"), Some("Shift_JIS"));
+    assert_eq!(charset("text/plain"), None);
+    let hints = vec!["text/plain;charset=windows-1251".to_owned()];
+    assert_eq!(decode_text(b"\xcf\xf0\xe8", &hints), "При");
+    assert_eq!(
+        decode_charset(b"\x82\xa0", "shift_jis").as_deref(),
+        Some("あ")
+    );
+    assert_eq!(encode_charset("café", "latin1"), Some(b"caf\xe9".to_vec()));
+    assert_eq!(encode_charset("café", "no-such-charset"), None);
+}
@@ -0,0 +1,14 @@
+//! Command-Line Snippet Joining Tests
+
+use wclipd::prompt::join;
+
+#[test]
+fn joins_continued_and_bulleted_lines() {
+    let command = "docker run \\\n  --rm \\\n  -it alpine\n";
+    assert_eq!(join(command), "docker run --rm -it alpine");
+    let bullets = "- apt update\n- apt upgrade\n\n* reboot";
+    assert_eq!(join(bullets), "apt update apt upgrade reboot");
+    assert_eq!(join("1. cd src\n2) make"), "cd src make");
+    // only leading list markers are stripped
+    assert_eq!(join("echo 1.5\n-v"), "echo 1.5 -v");
+}
@@ -0,0 +1,41 @@
+//! History Statistics Report Tests
+
+use wclipd::backend::Record;
+use wclipd::clipboard::Entry;
+use wclipd::report::{domains, HistoryReport};
+
+#[test]
+fn domains_are_extracted_from_text() {
+    let text = "see <https://www.GitHub.com/a?b> and http://user@docs.rs:80/x, not ftp://x.y";
+    let found: Vec<_> = domains(text).into_iter().collect();
+    assert_eq!(found, ["docs.rs", "github.com"]);
+}
+
+#[test]
+fn report_ranks_domains_mimes_and_sizes() {
+    let records = [
+        Record::new(
+            0,
+            Entry::text("https://a.com/1 https://a.com/2".to_owned(), None),
+        ),
+        Record::new(1, Entry::text("https://b.org".to_owned(), None)),
+        Record::new(2, Entry::text("https://b.org/x".to_owned(), None)),
+        Record::new(
+            3,
+            Entry::data(b"\x89PNG\r\n\x1a\n", Some("image/png".to_owned())),
+        ),
+    ];
+    let report = HistoryReport::build(records.iter().map(|r| ("term", r)));
+    assert_eq!(report.entries, 4);
+    assert_eq!(report.hours.iter().sum::<usize>(), 4);
+    let domains: Vec<_> = report
+        .domains
+        .iter()
+        .map(|(d, n)| (d.as_str(), *n))
+        .collect();
+    assert_eq!(domains, [("b.org", 2), ("a.com", 1)]);
+    assert_eq!(report.mimes[0], ("text/plain".to_owned(), 3));
+    assert_eq!(report.mimes[1], ("image/png".to_owned(), 1));
+    assert_eq!(report.largest[0].index, 0);
+    assert_eq!(report.largest.last().map(|e| e.index), Some(3));
+}
@@ -0,0 +1,65 @@
+//! Other Clipboard Manager Detection Tests
+
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::Duration;
+
+use wclipd::backend::Storage;
+use wclipd::client::Client;
+use wclipd::config::{DaemonConfig, TakeoverPolicy};
+use wclipd::daemon::{Daemon, DaemonHandle};
+use wclipd::provider::InMemory;
+
+/// Start Daemon with an In-Memory Clipboard Provider
+fn start(socket: PathBuf, other_managers: TakeoverPolicy) -> DaemonHandle {
+    let cfg = DaemonConfig {
+        other_managers,
+        ..Default::default()
+    };
+    Daemon::with_backend(socket, cfg, Storage::Memory.backend())
+        .expect("failed to build daemon")
+        .with_provider(Arc::new(InMemory::new()))
+        .start()
+}
+
+#[test]
+fn other_managers_are_reported_or_refused() {
+    // run a process named like a known clipboard manager
+    let dir = std::env::temp_dir().join(format!("wclipd-takeover-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("failed to create test dir");
+    let fake = dir.join("clipman");
+    std::fs::copy("/bin/sleep", &fake).expect("failed to copy sleep");
+    let mut manager = Command::new(&fake).arg("30").spawn().expect("spawn failed");
+
+    let socket = dir.join("daemon.sock");
+    let handle = start(socket.clone(), TakeoverPolicy::Warn);
+    let status = (0..500)
+        .find_map(|_| {
+            sleep(Duration::from_millis(10));
+            Client::new(socket.clone()).ok()?.status().ok()
+        })
+        .expect("daemon did not start");
+    let check = status
+        .health
+        .iter()
+        .find(|d| d.check == "clipboard_managers")
+        .expect("missing diagnostic");
+    assert!(check.ok);
+    assert!(check
+        .detail
+        .as_deref()
+        .unwrap_or_default()
+        .contains("clipman"));
+    handle.stop();
+    handle.join().expect("daemon failed");
+
+    let result = start(socket, TakeoverPolicy::Refuse).join();
+    let err = result.expect_err("daemon started beside another manager");
+    assert!(err.to_string().contains("clipman"));
+
+    let _ = manager.kill();
+    let _ = manager.wait();
+    let _ = std::fs::remove_dir_all(&dir);
+}